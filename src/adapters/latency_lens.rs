@@ -23,6 +23,8 @@ use std::time::Duration;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use crate::config::ThroughputConfig;
+
 /// Latency profile data consumed from LLM-Latency-Lens
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsumedLatencyProfile {
@@ -317,6 +319,19 @@ impl ConsumedLatencyProfile {
     }
 }
 
+/// Utility to convert consumed throughput to a model's token rate
+impl ConsumedThroughputData {
+    /// Token rate splitting the measured throughput across the concurrent
+    /// capacity, with the measured throughput as the model's global ceiling
+    pub fn throughput_config(&self) -> ThroughputConfig {
+        ThroughputConfig {
+            tokens_per_second: self.tokens_per_second / self.concurrent_capacity.max(1) as f64,
+            global_tokens_per_second: Some(self.tokens_per_second),
+            per_key_tokens_per_second: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -375,4 +390,22 @@ mod tests {
         let itl = profile.sample_itl(0.0);
         assert_eq!(itl, Duration::from_micros(30_000));
     }
+
+    #[test]
+    fn test_throughput_config() {
+        let data = ConsumedThroughputData {
+            provider: "openai".to_string(),
+            model: "gpt-4".to_string(),
+            tokens_per_second: 400.0,
+            requests_per_second: 2.0,
+            concurrent_capacity: 8,
+            window_duration: Duration::from_secs(60),
+            measured_at: 0,
+        };
+
+        let config = data.throughput_config();
+        assert_eq!(config.tokens_per_second, 50.0);
+        assert_eq!(config.global_tokens_per_second, Some(400.0));
+        assert!(config.validate().is_ok());
+    }
 }
@@ -18,6 +18,7 @@ use super::{
     Cli, Commands, ServeCommand, GenerateCommand, GenerateAction,
     ConfigCommand, ConfigAction, HealthCommand, ModelsCommand,
     BenchmarkCommand, ClientCommand, ClientAction, RunCommand,
+    CalibrateCommand,
 };
 
 /// Execute the CLI command
@@ -42,6 +43,7 @@ pub async fn execute(cli: Cli) -> Result<()> {
         Commands::Benchmark(cmd) => execute_benchmark(cmd).await,
         Commands::Client(cmd) => execute_client(cmd).await,
         Commands::Run(cmd) => execute_run(cmd).await,
+        Commands::Calibrate(cmd) => execute_calibrate(cmd),
         Commands::Version => execute_version(),
     }
 }
@@ -800,6 +802,35 @@ fn execute_version() -> Result<()> {
     Ok(())
 }
 
+/// Execute the calibrate command
+fn execute_calibrate(cmd: CalibrateCommand) -> Result<()> {
+    use crate::latency::{calibrate, LatencyTraces};
+
+    let input = std::fs::read_to_string(&cmd.input)
+        .with_context(|| format!("Failed to read traces from {:?}", cmd.input))?;
+    let calibration = calibrate(&LatencyTraces::parse(&input)?);
+    if calibration.models.is_empty() {
+        bail!("No latency observations in {:?}", cmd.input);
+    }
+
+    let profiles = calibration.profiles();
+    let output = match cmd.format.as_str() {
+        "json" => serde_json::to_string_pretty(&profiles)?,
+        _ => serde_yaml::to_string(&profiles)?,
+    };
+
+    // The report goes to stderr so stdout stays a usable profile file
+    eprint!("{}", calibration.report());
+    match cmd.output {
+        Some(path) => {
+            std::fs::write(&path, &output)?;
+            eprintln!("Wrote {} profile(s) to {:?}", profiles.len(), path);
+        }
+        None => println!("{}", output),
+    }
+    Ok(())
+}
+
 /// Execute the run command (canonical benchmarks)
 async fn execute_run(cmd: RunCommand) -> Result<()> {
     use crate::benchmarks::{
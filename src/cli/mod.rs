@@ -73,6 +73,9 @@ pub enum Commands {
     /// Run canonical benchmarks and output results
     Run(RunCommand),
 
+    /// Fit latency profiles to recorded traces
+    Calibrate(CalibrateCommand),
+
     /// Show version and build information
     Version,
 }
@@ -101,6 +104,21 @@ pub struct RunCommand {
     pub quiet: bool,
 }
 
+/// Fit latency profiles to recorded traces
+#[derive(Parser, Debug)]
+pub struct CalibrateCommand {
+    /// JSONL of per-request latencies, or latency-lens profile JSON
+    pub input: PathBuf,
+
+    /// Write the profiles to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Output format (yaml, json)
+    #[arg(short, long, default_value = "yaml")]
+    pub format: String,
+}
+
 /// Start the simulator server
 #[derive(Parser, Debug)]
 pub struct ServeCommand {
@@ -15,6 +15,8 @@ pub struct ChaosConfig {
     pub global_probability: f64,
     /// Error injection rules
     pub errors: Vec<ErrorInjectionRule>,
+    /// Faults injected into streaming responses after they have started
+    pub stream_faults: Vec<StreamFaultRule>,
     /// Circuit breaker configuration
     pub circuit_breaker: CircuitBreakerConfig,
     /// Rate limiting configuration
@@ -27,6 +29,7 @@ impl Default for ChaosConfig {
             enabled: false,
             global_probability: 1.0,
             errors: vec![],
+            stream_faults: vec![],
             circuit_breaker: CircuitBreakerConfig::default(),
             rate_limiting: RateLimitConfig::default(),
         }
@@ -47,6 +50,12 @@ impl ChaosConfig {
                 param: Some(format!("chaos.errors[{}]", i)),
             })?;
         }
+        for (i, rule) in self.stream_faults.iter().enumerate() {
+            rule.validate().map_err(|e| SimulationError::Validation {
+                message: format!("Invalid stream fault rule {}: {}", i, e),
+                param: Some(format!("chaos.stream_faults[{}]", i)),
+            })?;
+        }
         self.circuit_breaker.validate()?;
         self.rate_limiting.validate()?;
         Ok(())
@@ -115,6 +124,71 @@ impl ErrorInjectionRule {
     }
 }
 
+/// Rule for injecting a fault into a stream mid-flight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamFaultRule {
+    /// Name of the rule
+    pub name: String,
+    /// Type of fault to inject
+    pub fault_type: StreamFaultType,
+    /// Probability of injection (0.0-1.0)
+    pub probability: f64,
+    /// Models this rule applies to (empty = all)
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Tokens streamed before the fault
+    #[serde(default)]
+    pub after_tokens: usize,
+    /// Length of a stall (ms), meant to outlast the client's timeout
+    pub stall_ms: Option<u64>,
+    /// Custom error message
+    pub message: Option<String>,
+    /// Whether the rule is enabled
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl StreamFaultRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Rule name cannot be empty".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.probability) {
+            return Err("Probability must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Check if this rule applies to the given model
+    pub fn applies_to_model(&self, model: &str) -> bool {
+        self.models.is_empty() || self.models.iter().any(|m| m == model || model.starts_with(m))
+    }
+
+    /// Length of a stall
+    pub fn stall(&self) -> Duration {
+        Duration::from_millis(self.stall_ms.unwrap_or(120_000))
+    }
+}
+
+/// Faults a stream can meet after it has started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamFaultType {
+    /// The connection drops without the stream ending
+    Disconnect,
+    /// An error is reported in-band and the stream ends
+    ErrorEvent,
+    /// The stream goes quiet, then carries on
+    Stall,
+    /// The end-of-stream marker (`[DONE]`, `message_stop`) never arrives
+    MissingDone,
+    /// A chunk is sent twice
+    DuplicateChunk,
+    /// Two consecutive chunks arrive swapped, the early one under the next
+    /// choice, tool call or content block index
+    OutOfOrder,
+}
+
 /// Circuit breaker configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -435,6 +509,24 @@ mod tests {
         assert!(invalid.validate().is_err());
     }
 
+    #[test]
+    fn test_stream_fault_rule_from_yaml() {
+        let yaml = "enabled: true\nstream_faults:\n  - {name: drop, fault_type: disconnect, probability: 0.5, after_tokens: 3}\n";
+        let config: ChaosConfig = serde_yaml::from_str(yaml).unwrap();
+
+        let rule = &config.stream_faults[0];
+        assert_eq!(rule.fault_type, StreamFaultType::Disconnect);
+        assert_eq!(rule.after_tokens, 3);
+        assert_eq!(rule.stall(), Duration::from_secs(120));
+        assert!(config.validate().is_ok());
+
+        let invalid = ChaosConfig {
+            stream_faults: vec![StreamFaultRule { probability: 2.0, ..rule.clone() }],
+            ..config
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_rule_applies_to_model() {
         let rule = ErrorInjectionRule {
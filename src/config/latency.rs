@@ -1,8 +1,9 @@
 //! Latency simulation configuration
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
+use super::LatencyTimeline;
 use crate::error::{SimulationError, SimulatorResult};
 
 /// Latency simulation configuration
@@ -17,6 +18,12 @@ pub struct LatencyConfig {
     pub default_profile: String,
     /// Named latency profiles
     pub profiles: HashMap<String, LatencyProfile>,
+    /// Compute and report latency without sleeping all of it
+    pub virtual_time: VirtualTimeConfig,
+    /// Cold-start behaviour by model ID; models not listed are always warm
+    pub cold_start: HashMap<String, ColdStartConfig>,
+    /// Phases scaling latency or swapping profiles over time
+    pub timeline: LatencyTimeline,
 }
 
 impl Default for LatencyConfig {
@@ -28,6 +35,8 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::normal(50.0, 10.0),
             itl: LatencyDistribution::normal(15.0, 3.0),
             overhead: Duration::from_millis(5),
+            prefill: Some(PrefillConfig::linear(10.0)),
+            ..Default::default()
         });
 
         // Standard profile (typical cloud)
@@ -35,6 +44,8 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::normal(200.0, 50.0),
             itl: LatencyDistribution::normal(30.0, 8.0),
             overhead: Duration::from_millis(10),
+            prefill: Some(PrefillConfig::linear(40.0)),
+            ..Default::default()
         });
 
         // Slow profile (congested/distant)
@@ -42,6 +53,8 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::normal(500.0, 100.0),
             itl: LatencyDistribution::normal(60.0, 15.0),
             overhead: Duration::from_millis(20),
+            prefill: Some(PrefillConfig::linear(80.0)),
+            ..Default::default()
         });
 
         // GPT-4 realistic profile
@@ -49,6 +62,8 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::log_normal(300.0, 150.0),
             itl: LatencyDistribution::log_normal(40.0, 15.0),
             overhead: Duration::from_millis(15),
+            prefill: Some(PrefillConfig::linear(120.0)),
+            ..Default::default()
         });
 
         // Claude realistic profile
@@ -56,6 +71,8 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::log_normal(250.0, 100.0),
             itl: LatencyDistribution::log_normal(35.0, 12.0),
             overhead: Duration::from_millis(12),
+            prefill: Some(PrefillConfig::linear(80.0)),
+            ..Default::default()
         });
 
         // Gemini realistic profile
@@ -63,6 +80,8 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::log_normal(200.0, 80.0),
             itl: LatencyDistribution::log_normal(25.0, 10.0),
             overhead: Duration::from_millis(10),
+            prefill: Some(PrefillConfig::linear(50.0)),
+            ..Default::default()
         });
 
         // Zero latency (for testing)
@@ -70,6 +89,7 @@ impl Default for LatencyConfig {
             ttft: LatencyDistribution::fixed(0.0),
             itl: LatencyDistribution::fixed(0.0),
             overhead: Duration::from_millis(0),
+            ..Default::default()
         });
 
         Self {
@@ -77,6 +97,9 @@ impl Default for LatencyConfig {
             multiplier: 1.0,
             default_profile: "standard".to_string(),
             profiles,
+            virtual_time: VirtualTimeConfig::default(),
+            cold_start: HashMap::new(),
+            timeline: LatencyTimeline::default(),
         }
     }
 }
@@ -95,12 +118,28 @@ impl LatencyConfig {
                 param: Some("latency.default_profile".to_string()),
             });
         }
+        if !(self.virtual_time.time_scale >= 0.0 && self.virtual_time.time_scale.is_finite()) {
+            return Err(SimulationError::Validation {
+                message: "virtual time scale must be a non-negative number".to_string(),
+                param: Some("latency.virtual_time.time_scale".to_string()),
+            });
+        }
         for (name, profile) in &self.profiles {
             profile.validate().map_err(|e| SimulationError::Validation {
                 message: format!("Invalid latency profile '{}': {}", name, e),
                 param: Some(format!("latency.profiles.{}", name)),
             })?;
         }
+        self.timeline.validate(&self.profiles).map_err(|e| SimulationError::Validation {
+            message: format!("Invalid latency timeline: {}", e),
+            param: Some("latency.timeline".to_string()),
+        })?;
+        for (model, cold_start) in &self.cold_start {
+            cold_start.validate().map_err(|e| SimulationError::Validation {
+                message: format!("Invalid cold start for '{}': {}", model, e),
+                param: Some(format!("latency.cold_start.{}", model)),
+            })?;
+        }
         Ok(())
     }
 
@@ -114,10 +153,80 @@ impl LatencyConfig {
         self.profiles.get(&self.default_profile)
             .expect("Default profile should always exist")
     }
+
+    /// Real time slept per simulated second
+    pub fn sleep_scale(&self) -> f64 {
+        if self.virtual_time.enabled {
+            self.virtual_time.time_scale
+        } else {
+            1.0
+        }
+    }
 }
 
-/// A latency profile defining timing characteristics
+/// Virtual time
+///
+/// Latency is still sampled and reported in timestamps, usage durations and
+/// headers, but only `time_scale` of it is actually slept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VirtualTimeConfig {
+    /// Run on virtual time
+    pub enabled: bool,
+    /// Real time slept per simulated second; 0 skips sleeping entirely
+    pub time_scale: f64,
+}
+
+/// Cold starts of a model scaled like a serverless deployment
+///
+/// The model runs on instances, `warm_pool` of which are kept warm. A
+/// request arriving when every instance is busy scales up a new one, and once
+/// the model has been idle for `idle_timeout_secs` it scales back down to the
+/// warm pool. The first `warmup_requests` requests after each scale-up, or
+/// after startup with no warm pool, pay `penalty` on top of their TTFT.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColdStartConfig {
+    /// Extra time to first token of a cold request (milliseconds)
+    pub penalty: LatencyDistribution,
+    /// Requests served cold after each scale-up
+    pub warmup_requests: usize,
+    /// Instances warm from startup and never scaled down
+    pub warm_pool: usize,
+    /// Idle time after which instances beyond the warm pool are released;
+    /// never when unset (seconds)
+    pub idle_timeout_secs: Option<f64>,
+}
+
+impl Default for ColdStartConfig {
+    fn default() -> Self {
+        Self {
+            penalty: LatencyDistribution::log_normal(2000.0, 800.0),
+            warmup_requests: 1,
+            warm_pool: 0,
+            idle_timeout_secs: Some(300.0),
+        }
+    }
+}
+
+impl ColdStartConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(timeout) = self.idle_timeout_secs {
+            if !(timeout >= 0.0 && timeout.is_finite()) {
+                return Err("idle_timeout_secs must be a non-negative number".to_string());
+            }
+        }
+        self.penalty.validate()
+    }
+
+    /// Idle time after which the model scales down to its warm pool
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_secs.map(Duration::from_secs_f64)
+    }
+}
+
+/// A latency profile defining timing characteristics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LatencyProfile {
     /// Time to First Token distribution (milliseconds)
     pub ttft: LatencyDistribution,
@@ -126,14 +235,159 @@ pub struct LatencyProfile {
     /// Fixed overhead per request
     #[serde(with = "duration_millis")]
     pub overhead: Duration,
+    /// Prompt processing time added to TTFT, growing with input length
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefill: Option<PrefillConfig>,
+    /// Overhead per output token, added to the fixed overhead (milliseconds)
+    #[serde(default)]
+    pub overhead_per_output_token_ms: f64,
+    /// How each inter-token latency depends on the ones before it
+    #[serde(default, skip_serializing_if = "ItlCorrelation::is_independent")]
+    pub itl_correlation: ItlCorrelation,
+    /// Occasional long pauses mid-stream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stalls: Option<StallConfig>,
 }
 
 impl LatencyProfile {
     pub fn validate(&self) -> Result<(), String> {
         self.ttft.validate()?;
         self.itl.validate()?;
+        if let Some(prefill) = &self.prefill {
+            prefill.validate()?;
+        }
+        if self.overhead_per_output_token_ms < 0.0 {
+            return Err("overhead_per_output_token_ms cannot be negative".to_string());
+        }
+        self.itl_correlation.validate()?;
+        if matches!(self.itl_correlation, ItlCorrelation::Ar1 { .. }) && !self.itl.mean().is_finite() {
+            return Err("ar1 ITL correlation needs an ITL distribution with a finite mean".to_string());
+        }
+        if let Some(stalls) = &self.stalls {
+            stalls.validate()?;
+        }
         Ok(())
     }
+
+    /// Whether every inter-token latency is an independent draw from `itl`
+    pub fn itl_is_independent(&self) -> bool {
+        self.itl_correlation.is_independent() && self.stalls.is_none()
+    }
+
+    /// Prefill time in milliseconds for a prompt of `input_tokens`
+    pub fn prefill_ms(&self, input_tokens: usize) -> f64 {
+        self.prefill.as_ref().map_or(0.0, |p| p.duration_ms(input_tokens))
+    }
+
+    /// Overhead in milliseconds for a response of `output_tokens`
+    pub fn overhead_ms(&self, output_tokens: usize) -> f64 {
+        self.overhead.as_secs_f64() * 1000.0 + self.overhead_per_output_token_ms * output_tokens as f64
+    }
+}
+
+/// Prompt processing time as a function of input length
+///
+/// The time is `ms_per_1k_tokens * (input_tokens / 1000) ^ exponent`; an
+/// exponent above 1 models attention cost growing faster than linearly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrefillConfig {
+    /// Milliseconds per 1000 input tokens
+    pub ms_per_1k_tokens: f64,
+    /// Growth exponent (1.0 = linear)
+    #[serde(default = "default_prefill_exponent")]
+    pub exponent: f64,
+}
+
+impl PrefillConfig {
+    /// Prefill time proportional to input length
+    pub fn linear(ms_per_1k_tokens: f64) -> Self {
+        Self {
+            ms_per_1k_tokens,
+            exponent: 1.0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ms_per_1k_tokens < 0.0 {
+            return Err("prefill ms_per_1k_tokens cannot be negative".to_string());
+        }
+        if self.exponent <= 0.0 {
+            return Err("prefill exponent must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Prefill time in milliseconds for a prompt of `input_tokens`
+    pub fn duration_ms(&self, input_tokens: usize) -> f64 {
+        self.ms_per_1k_tokens * (input_tokens as f64 / 1000.0).powf(self.exponent)
+    }
+}
+
+fn default_prefill_exponent() -> f64 {
+    1.0
+}
+
+/// Dependence between successive inter-token latencies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum ItlCorrelation {
+    /// Every ITL is an independent draw
+    #[default]
+    Independent,
+    /// First-order autoregressive: each ITL pulls `phi` of the previous
+    /// one's deviation from the mean, so slow tokens follow slow tokens
+    Ar1 { phi: f64 },
+    /// Markov-modulated fast and slow regimes; in the slow regime ITLs are
+    /// `slow_multiplier` times longer. `enter_slow` and `exit_slow` are the
+    /// per-token switching probabilities.
+    Regimes {
+        slow_multiplier: f64,
+        enter_slow: f64,
+        exit_slow: f64,
+    },
+}
+
+impl ItlCorrelation {
+    pub fn is_independent(&self) -> bool {
+        matches!(self, Self::Independent)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Independent => Ok(()),
+            Self::Ar1 { phi } if !(-1.0 < *phi && *phi < 1.0) => {
+                Err("ar1 phi must be between -1 and 1 exclusive".to_string())
+            }
+            Self::Ar1 { .. } => Ok(()),
+            Self::Regimes { slow_multiplier, enter_slow, exit_slow } => {
+                if *slow_multiplier <= 0.0 {
+                    return Err("regimes slow_multiplier must be positive".to_string());
+                }
+                if !(0.0..=1.0).contains(enter_slow) || !(0.0..=1.0).contains(exit_slow) {
+                    return Err("regimes switching probabilities must be between 0 and 1".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Long pauses inserted before random tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StallConfig {
+    /// Chance that any one token is preceded by a stall
+    pub probability: f64,
+    /// Length of a stall, added to the token's ITL (milliseconds)
+    pub duration: LatencyDistribution,
+}
+
+impl StallConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.probability) {
+            return Err("stall probability must be between 0 and 1".to_string());
+        }
+        self.duration.validate()
+    }
 }
 
 /// Statistical distribution for latency values
@@ -152,6 +406,29 @@ pub enum LatencyDistribution {
     Exponential { mean_ms: f64 },
     /// Pareto distribution (for modeling tail latency)
     Pareto { scale_ms: f64, shape: f64 },
+    /// Gamma distribution
+    Gamma { shape: f64, scale_ms: f64 },
+    /// Weibull distribution
+    Weibull { scale_ms: f64, shape: f64 },
+    /// Observed latencies: resampled from `samples`, or interpolated
+    /// between the points of a percentile table such as
+    /// `{p50: 120, p90: 300, p99: 900, p999: 2500}`
+    Empirical {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        samples: Vec<f64>,
+        #[serde(default, skip_serializing_if = "PercentileTable::is_empty")]
+        percentiles: PercentileTable,
+    },
+    /// Weighted combination of distributions, e.g. a fast mode and a slow tail
+    Mixture { components: Vec<MixtureComponent> },
+}
+
+/// One weighted part of a mixture distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixtureComponent {
+    /// Relative weight; weights need not sum to 1
+    pub weight: f64,
+    pub distribution: LatencyDistribution,
 }
 
 impl LatencyDistribution {
@@ -179,6 +456,39 @@ impl LatencyDistribution {
         Self::Pareto { scale_ms, shape }
     }
 
+    pub fn gamma(shape: f64, scale_ms: f64) -> Self {
+        Self::Gamma { shape, scale_ms }
+    }
+
+    pub fn weibull(scale_ms: f64, shape: f64) -> Self {
+        Self::Weibull { scale_ms, shape }
+    }
+
+    /// Resample observed latencies
+    pub fn empirical(samples: Vec<f64>) -> Self {
+        Self::Empirical { samples, percentiles: PercentileTable::default() }
+    }
+
+    /// Interpolate a percentile table, e.g. `[("p50", 120.0), ("p99", 900.0)]`
+    pub fn percentiles(table: &[(&str, f64)]) -> Self {
+        Self::Empirical {
+            samples: Vec::new(),
+            percentiles: table.iter()
+                .map(|(p, ms)| (p.to_string(), *ms))
+                .collect::<BTreeMap<_, _>>()
+                .into(),
+        }
+    }
+
+    /// Weighted combination of `(weight, distribution)` pairs
+    pub fn mixture(components: Vec<(f64, LatencyDistribution)>) -> Self {
+        Self::Mixture {
+            components: components.into_iter()
+                .map(|(weight, distribution)| MixtureComponent { weight, distribution })
+                .collect(),
+        }
+    }
+
     pub fn validate(&self) -> Result<(), String> {
         match self {
             Self::Fixed { value_ms } if *value_ms < 0.0 => {
@@ -199,10 +509,55 @@ impl LatencyDistribution {
             Self::Pareto { scale_ms, shape } if *scale_ms <= 0.0 || *shape <= 0.0 => {
                 Err("Pareto scale and shape must be positive".to_string())
             }
+            Self::Gamma { shape, scale_ms } if *shape <= 0.0 || *scale_ms <= 0.0 => {
+                Err("Gamma shape and scale must be positive".to_string())
+            }
+            Self::Weibull { scale_ms, shape } if *scale_ms <= 0.0 || *shape <= 0.0 => {
+                Err("Weibull scale and shape must be positive".to_string())
+            }
+            Self::Empirical { samples, percentiles } => {
+                match (samples.is_empty(), percentiles.is_empty()) {
+                    (true, true) => return Err("Empirical distribution needs samples or percentiles".to_string()),
+                    (false, false) => return Err("Empirical distribution takes samples or percentiles, not both".to_string()),
+                    _ => {}
+                }
+                if samples.iter().any(|s| !s.is_finite() || *s < 0.0) {
+                    return Err("Empirical samples must be non-negative".to_string());
+                }
+                percentiles.validate()
+            }
+            Self::Mixture { components } => {
+                if components.is_empty() {
+                    return Err("Mixture needs at least one component".to_string());
+                }
+                if components.iter().any(|c| !c.weight.is_finite() || c.weight < 0.0) {
+                    return Err("Mixture weights cannot be negative".to_string());
+                }
+                if components.iter().map(|c| c.weight).sum::<f64>() <= 0.0 {
+                    return Err("Mixture weights must not all be zero".to_string());
+                }
+                components.iter().try_for_each(|c| c.distribution.validate())
+            }
             _ => Ok(()),
         }
     }
 
+    /// Name of the distribution, as written in configuration
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Fixed { .. } => "fixed",
+            Self::Normal { .. } => "normal",
+            Self::LogNormal { .. } => "log_normal",
+            Self::Uniform { .. } => "uniform",
+            Self::Exponential { .. } => "exponential",
+            Self::Pareto { .. } => "pareto",
+            Self::Gamma { .. } => "gamma",
+            Self::Weibull { .. } => "weibull",
+            Self::Empirical { .. } => "empirical",
+            Self::Mixture { .. } => "mixture",
+        }
+    }
+
     /// Get the expected/mean value of this distribution
     pub fn mean(&self) -> f64 {
         match self {
@@ -215,10 +570,163 @@ impl LatencyDistribution {
                 (shape * scale_ms) / (shape - 1.0)
             }
             Self::Pareto { .. } => f64::INFINITY,
+            Self::Gamma { shape, scale_ms } => shape * scale_ms,
+            Self::Weibull { scale_ms, shape } => scale_ms * gamma_fn(1.0 + 1.0 / shape),
+            Self::Empirical { samples, .. } if !samples.is_empty() => {
+                samples.iter().sum::<f64>() / samples.len() as f64
+            }
+            // The quantile function is piecewise linear, so its integral is exact
+            Self::Empirical { percentiles, .. } => {
+                percentiles.points().windows(2)
+                    .map(|w| (w[1].0 - w[0].0) * (w[0].1 + w[1].1) / 2.0)
+                    .sum()
+            }
+            Self::Mixture { components } => {
+                let total: f64 = components.iter().map(|c| c.weight).sum();
+                components.iter()
+                    .map(|c| c.weight * c.distribution.mean())
+                    .sum::<f64>() / total
+            }
         }
     }
 }
 
+/// Percentile table of an empirical distribution, with its interpolation
+/// points computed once when the table is built rather than on every draw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, f64>", into = "BTreeMap<String, f64>")]
+pub struct PercentileTable {
+    table: BTreeMap<String, f64>,
+    points: Result<Vec<(f64, f64)>, String>,
+}
+
+impl PercentileTable {
+    /// Whether the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// The table's `percentile_points`, empty if it is invalid
+    pub fn points(&self) -> &[(f64, f64)] {
+        self.points.as_deref().unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.points.as_ref().map(|_| ()).map_err(Clone::clone)
+    }
+}
+
+impl Default for PercentileTable {
+    fn default() -> Self {
+        BTreeMap::new().into()
+    }
+}
+
+impl From<BTreeMap<String, f64>> for PercentileTable {
+    fn from(table: BTreeMap<String, f64>) -> Self {
+        let points = percentile_points(&table);
+        Self { table, points }
+    }
+}
+
+impl From<PercentileTable> for BTreeMap<String, f64> {
+    fn from(table: PercentileTable) -> Self {
+        table.table
+    }
+}
+
+impl std::ops::Deref for PercentileTable {
+    type Target = BTreeMap<String, f64>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
+    }
+}
+
+/// Points `(quantile, ms)` of a percentile table, sorted and extended to
+/// quantiles 0 and 1 along the slope of the outermost segments
+pub fn percentile_points(table: &BTreeMap<String, f64>) -> Result<Vec<(f64, f64)>, String> {
+    let mut points = table.iter()
+        .map(|(key, ms)| match parse_percentile(key) {
+            Some(q) if ms.is_finite() && *ms >= 0.0 => Ok((q, *ms)),
+            Some(_) => Err(format!("Percentile {} must be non-negative", key)),
+            None => Err(format!("Invalid percentile '{}', expected e.g. p50, p99 or p999", key)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    if points.windows(2).any(|w| w[1].1 < w[0].1) {
+        return Err("Percentile latencies must not decrease as the percentile rises".to_string());
+    }
+
+    let extend = |a: (f64, f64), b: (f64, f64), q: f64| {
+        if b.0 > a.0 {
+            (q, (a.1 + (b.1 - a.1) * (q - a.0) / (b.0 - a.0)).max(0.0))
+        } else {
+            (q, a.1)
+        }
+    };
+    if let (Some(&first), Some(&last)) = (points.first(), points.last()) {
+        if first.0 > 0.0 {
+            let next = points.get(1).copied().unwrap_or(first);
+            points.insert(0, extend(first, next, 0.0));
+        }
+        if last.0 < 1.0 {
+            let previous = points[points.len().saturating_sub(2)];
+            points.push(extend(previous, last, 1.0));
+        }
+    }
+    Ok(points)
+}
+
+/// Quantile `q` of a percentile table, from its `percentile_points`
+pub fn interpolate_percentile(points: &[(f64, f64)], q: f64) -> f64 {
+    let i = points.partition_point(|(p, _)| *p < q);
+    match (i.checked_sub(1).and_then(|i| points.get(i)), points.get(i)) {
+        (Some(&(q0, v0)), Some(&(q1, v1))) if q1 > q0 => v0 + (v1 - v0) * (q - q0) / (q1 - q0),
+        (_, Some(&(_, v))) | (Some(&(_, v)), None) => v,
+        (None, None) => 0.0,
+    }
+}
+
+/// Quantile named like `p50`, `p99`, `p999` (99.9) or `p99.9`, as a fraction
+fn parse_percentile(key: &str) -> Option<f64> {
+    let digits = key.strip_prefix('p')?;
+    let percent: f64 = if digits.contains('.') || digits.len() <= 2 || digits == "100" {
+        digits.parse().ok()?
+    } else {
+        format!("{}.{}", &digits[..2], &digits[2..]).parse().ok()?
+    };
+    (0.0..=100.0).contains(&percent).then_some(percent / 100.0)
+}
+
+/// Gamma function by the Lanczos approximation
+pub(crate) fn gamma_fn(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        std::f64::consts::PI / ((std::f64::consts::PI * x).sin() * gamma_fn(1.0 - x))
+    } else {
+        let x = x - 1.0;
+        let t = x + G + 0.5;
+        let sum = COEFFICIENTS[1..].iter().enumerate()
+            .fold(COEFFICIENTS[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+        (2.0 * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * sum
+    }
+}
+
 impl Default for LatencyDistribution {
     fn default() -> Self {
         Self::Normal {
@@ -283,6 +791,49 @@ mod tests {
         assert_eq!(LatencyDistribution::uniform(0.0, 100.0).mean(), 50.0);
     }
 
+    #[test]
+    fn test_prefill_grows_with_input() {
+        let linear = PrefillConfig::linear(100.0);
+        assert_eq!(linear.duration_ms(0), 0.0);
+        assert!((linear.duration_ms(100_000) - 10_000.0).abs() < 1e-6);
+
+        let superlinear = PrefillConfig { ms_per_1k_tokens: 100.0, exponent: 1.5 };
+        assert!((superlinear.duration_ms(4_000) - 800.0).abs() < 1e-6);
+        assert!(PrefillConfig { ms_per_1k_tokens: 1.0, exponent: 0.0 }.validate().is_err());
+
+        let profile = LatencyProfile {
+            overhead: Duration::from_millis(10),
+            overhead_per_output_token_ms: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(profile.prefill_ms(50_000), 0.0);
+        assert!((profile.overhead_ms(100) - 60.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_percentile_table() {
+        let table: BTreeMap<String, f64> = [("p50", 100.0), ("p999", 1000.0), ("p90", 200.0)]
+            .into_iter()
+            .map(|(p, ms)| (p.to_string(), ms))
+            .collect();
+        let points = percentile_points(&table).unwrap();
+        assert_eq!(PercentileTable::from(table.clone()).points(), points.as_slice());
+
+        assert_eq!(points.first(), Some(&(0.0, 0.0)));
+        assert_eq!(points[1], (0.5, 100.0));
+        assert!((points[3].0 - 0.999).abs() < 1e-12);
+        assert!((interpolate_percentile(&points, 0.7) - 150.0).abs() < 1e-9);
+        assert!(interpolate_percentile(&points, 1.0) > 1000.0);
+
+        let yaml = "type: empirical\npercentiles: {p50: 120, p99: 80}\n";
+        let decreasing: LatencyDistribution = serde_yaml::from_str(yaml).unwrap();
+        assert!(decreasing.validate().is_err());
+        assert!(serde_yaml::to_string(&decreasing).unwrap().contains("p99: 80.0"));
+        assert!(LatencyDistribution::percentiles(&[("median", 1.0)]).validate().is_err());
+        assert!(LatencyDistribution::empirical(vec![]).validate().is_err());
+        assert!(LatencyDistribution::mixture(vec![(0.0, LatencyDistribution::fixed(1.0))]).validate().is_err());
+    }
+
     #[test]
     fn test_get_profile() {
         let config = LatencyConfig::default();
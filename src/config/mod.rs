@@ -8,14 +8,19 @@
 
 mod models;
 mod latency;
+mod timeline;
 mod chaos;
+mod moderation;
 pub mod security;
 
 pub use models::*;
 pub use latency::*;
+pub use timeline::*;
 pub use chaos::*;
+pub use moderation::*;
 pub use security::SecurityConfig;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -35,6 +40,10 @@ pub struct SimulatorConfig {
     pub latency: LatencyConfig,
     /// Chaos engineering settings
     pub chaos: ChaosConfig,
+    /// Content moderation settings
+    pub moderation: ModerationConfig,
+    /// Corpus for models using the Markov strategy without their own
+    pub corpus: Option<CorpusConfig>,
     /// Telemetry settings
     pub telemetry: TelemetryConfig,
     /// Security settings
@@ -44,6 +53,10 @@ pub struct SimulatorConfig {
     pub default_provider: Provider,
     /// Seed for deterministic behavior (None = random)
     pub seed: Option<u64>,
+    /// Deterministic IDs and timestamps
+    pub deterministic: DeterministicConfig,
+    /// Server-side conversation sessions
+    pub sessions: SessionConfig,
 }
 
 impl Default for SimulatorConfig {
@@ -56,6 +69,14 @@ impl Default for SimulatorConfig {
         models.insert("gpt-4o".to_string(), ModelConfig::gpt4o());
         models.insert("gpt-4o-mini".to_string(), ModelConfig::gpt4o_mini());
         models.insert("gpt-3.5-turbo".to_string(), ModelConfig::gpt35_turbo());
+        models.insert("gpt-4o-realtime-preview".to_string(), ModelConfig::gpt4o_realtime());
+        models.insert("dall-e-2".to_string(), ModelConfig::dall_e_2());
+        models.insert("dall-e-3".to_string(), ModelConfig::dall_e_3());
+        models.insert("tts-1".to_string(), ModelConfig::tts_1());
+        models.insert("tts-1-hd".to_string(), ModelConfig::tts_1_hd());
+        models.insert("whisper-1".to_string(), ModelConfig::whisper_1());
+        models.insert("omni-moderation-latest".to_string(), ModelConfig::omni_moderation());
+        models.insert("text-moderation-latest".to_string(), ModelConfig::text_moderation());
 
         // Default Anthropic models
         models.insert("claude-3-5-sonnet-20241022".to_string(), ModelConfig::claude_35_sonnet());
@@ -67,6 +88,10 @@ impl Default for SimulatorConfig {
         models.insert("gemini-1.5-pro".to_string(), ModelConfig::gemini_15_pro());
         models.insert("gemini-1.5-flash".to_string(), ModelConfig::gemini_15_flash());
 
+        // Default Ollama models
+        models.insert("llama3.2".to_string(), ModelConfig::llama_32());
+        models.insert("nomic-embed-text".to_string(), ModelConfig::nomic_embed_text());
+
         // Default embedding models
         models.insert("text-embedding-ada-002".to_string(), ModelConfig::embedding_ada());
         models.insert("text-embedding-3-small".to_string(), ModelConfig::embedding_3_small());
@@ -77,10 +102,14 @@ impl Default for SimulatorConfig {
             models,
             latency: LatencyConfig::default(),
             chaos: ChaosConfig::default(),
+            moderation: ModerationConfig::default(),
+            corpus: None,
             telemetry: TelemetryConfig::default(),
             security: SecurityConfig::default(),
             default_provider: Provider::OpenAI,
             seed: None,
+            deterministic: DeterministicConfig::default(),
+            sessions: SessionConfig::default(),
         }
     }
 }
@@ -146,11 +175,36 @@ impl SimulatorConfig {
         self.server.validate()?;
         self.latency.validate()?;
         self.chaos.validate()?;
+        self.moderation.validate()?;
+
+        if self.sessions.enabled && self.sessions.max_sessions == 0 {
+            return Err(SimulationError::Validation {
+                message: "max_sessions must be greater than 0".to_string(),
+                param: Some("sessions.max_sessions".to_string()),
+            });
+        }
+
+        if let Some(corpus) = &self.corpus {
+            corpus.validate().map_err(|e| SimulationError::Validation {
+                message: e,
+                param: Some("corpus".to_string()),
+            })?;
+        }
 
         for (name, model) in &self.models {
             model.validate().map_err(|e| {
                 SimulationError::Config(format!("Invalid model config '{}': {}", name, e))
             })?;
+
+            if matches!(model.generation.strategy, GenerationStrategy::Markov)
+                && model.generation.corpus.is_none()
+                && self.corpus.is_none()
+            {
+                return Err(SimulationError::Config(format!(
+                    "Invalid model config '{}': markov strategy requires a corpus",
+                    name
+                )));
+            }
         }
 
         Ok(())
@@ -180,10 +234,14 @@ impl SimulatorConfig {
                 ..Default::default()
             },
             chaos: ChaosConfig::default(),
+            moderation: ModerationConfig::default(),
+            corpus: None,
             telemetry: TelemetryConfig::default(),
             security: SecurityConfig::default(),
             default_provider: Provider::OpenAI,
             seed: None,
+            deterministic: DeterministicConfig::default(),
+            sessions: SessionConfig::default(),
         }
     }
 
@@ -303,6 +361,72 @@ impl Default for TelemetryConfig {
     }
 }
 
+/// Deterministic output for golden-file testing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeterministicConfig {
+    /// Derive response IDs from a counter seeded by `seed` instead of random UUIDs
+    pub ids: bool,
+    /// Clock for `created` timestamps
+    pub clock: ClockConfig,
+}
+
+/// Server-side conversation sessions
+///
+/// Requests carrying the session header, or else a `user` field, are
+/// answered with the session's earlier turns prepended to their messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Track sessions at all
+    pub enabled: bool,
+    /// Request header naming the session
+    pub header: String,
+    /// Idle time after which a session is dropped
+    pub ttl_secs: u64,
+    /// Sessions kept at once; the least recently active is evicted first
+    pub max_sessions: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            header: "x-session-id".to_string(),
+            ttl_secs: 1800,
+            max_sessions: 10_000,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Idle time after which a session is dropped
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+}
+
+/// Source of response timestamps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ClockConfig {
+    /// Wall-clock time
+    #[default]
+    System,
+    /// Every timestamp is `at`
+    Fixed { at: DateTime<Utc> },
+    /// Starts at `start` and advances `step_ms` on every reading
+    Virtual {
+        start: DateTime<Utc>,
+        #[serde(default = "default_clock_step_ms")]
+        step_ms: u64,
+    },
+}
+
+fn default_clock_step_ms() -> u64 {
+    1000
+}
+
 /// Helper module for Duration serialization
 mod humantime_serde {
     use serde::{Deserialize, Deserializer, Serializer};
@@ -375,10 +499,34 @@ mod tests {
         assert!(model.unwrap().supports_streaming);
     }
 
+    #[test]
+    fn test_deterministic_config_parsing() {
+        let config: SimulatorConfig = serde_yaml::from_str(
+            "deterministic:\n  ids: true\n  clock:\n    mode: virtual\n    start: 2024-01-01T00:00:00Z\n",
+        ).unwrap();
+
+        assert!(config.deterministic.ids);
+        assert!(matches!(config.deterministic.clock, ClockConfig::Virtual { step_ms: 1000, .. }));
+        assert_eq!(SimulatorConfig::default().deterministic.clock, ClockConfig::System);
+    }
+
     #[test]
     fn test_invalid_port() {
         let mut config = SimulatorConfig::default();
         config.server.port = 0;
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_markov_requires_corpus() {
+        let mut config = SimulatorConfig::default();
+        config.models.get_mut("gpt-4").unwrap().generation.strategy = GenerationStrategy::Markov;
+        assert!(config.validate().is_err());
+
+        config.corpus = Some(CorpusConfig {
+            files: vec!["Cargo.toml".into()],
+            order: 2,
+        });
+        assert!(config.validate().is_ok());
+    }
 }
@@ -1,6 +1,9 @@
 //! Model configuration definitions
 
+use std::path::PathBuf;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use crate::tokenizer::TokenizerKind;
 use crate::types::Provider;
 
 /// Configuration for a specific model
@@ -25,12 +28,20 @@ pub struct ModelConfig {
     pub is_embedding: bool,
     /// Embedding dimensions (for embedding models)
     pub embedding_dimensions: Option<usize>,
+    /// How embedding vectors are derived from input text
+    pub embedding_mode: EmbeddingMode,
     /// Default response template
     pub default_response: Option<String>,
     /// Response generation config
     pub generation: GenerationConfig,
     /// Latency profile override
     pub latency_profile: Option<String>,
+    /// Tokenizer override; defaults by provider and model family
+    pub tokenizer: Option<TokenizerKind>,
+    /// Concurrency limit; unlimited when unset
+    pub capacity: Option<CapacityConfig>,
+    /// Token rate pacing in place of sampled ITLs; unset samples ITLs
+    pub throughput: Option<ThroughputConfig>,
 }
 
 impl Default for ModelConfig {
@@ -45,9 +56,13 @@ impl Default for ModelConfig {
             supports_vision: false,
             is_embedding: false,
             embedding_dimensions: None,
+            embedding_mode: EmbeddingMode::default(),
             default_response: None,
             generation: GenerationConfig::default(),
             latency_profile: None,
+            tokenizer: None,
+            capacity: None,
+            throughput: None,
         }
     }
 }
@@ -67,9 +82,24 @@ impl ModelConfig {
         if self.is_embedding && self.embedding_dimensions.is_none() {
             return Err("Embedding models must specify embedding_dimensions".to_string());
         }
+        if let Some(corpus) = &self.generation.corpus {
+            corpus.validate()?;
+        }
+        if let Some(capacity) = &self.capacity {
+            capacity.validate()?;
+        }
+        if let Some(throughput) = &self.throughput {
+            throughput.validate()?;
+        }
         Ok(())
     }
 
+    /// Tokenizer used for usage, context limits and stream chunking
+    pub fn tokenizer(&self) -> TokenizerKind {
+        self.tokenizer
+            .unwrap_or_else(|| TokenizerKind::for_model(self.provider, &self.id))
+    }
+
     // =========== OpenAI Models ===========
 
     pub fn gpt4() -> Self {
@@ -137,6 +167,60 @@ impl ModelConfig {
         }
     }
 
+    pub fn gpt4o_realtime() -> Self {
+        Self {
+            id: "gpt-4o-realtime-preview".to_string(),
+            provider: Provider::OpenAI,
+            context_length: 128_000,
+            max_output_tokens: 4096,
+            supports_streaming: true,
+            supports_functions: true,
+            supports_vision: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn dall_e_2() -> Self {
+        Self::media("dall-e-2", 1000)
+    }
+
+    pub fn dall_e_3() -> Self {
+        Self::media("dall-e-3", 4000)
+    }
+
+    pub fn tts_1() -> Self {
+        Self::media("tts-1", 4096)
+    }
+
+    pub fn tts_1_hd() -> Self {
+        Self::media("tts-1-hd", 4096)
+    }
+
+    pub fn whisper_1() -> Self {
+        Self::media("whisper-1", 448)
+    }
+
+    pub fn omni_moderation() -> Self {
+        Self::media("omni-moderation-latest", 32_768)
+    }
+
+    pub fn text_moderation() -> Self {
+        Self::media("text-moderation-latest", 32_768)
+    }
+
+    /// Image, audio and moderation models: no chat features, a prompt-sized context
+    fn media(id: &str, context_length: usize) -> Self {
+        Self {
+            id: id.to_string(),
+            provider: Provider::OpenAI,
+            context_length,
+            supports_streaming: false,
+            supports_functions: false,
+            supports_vision: false,
+            ..Default::default()
+        }
+    }
+
     // =========== Anthropic Models ===========
 
     pub fn claude_35_sonnet() -> Self {
@@ -219,6 +303,36 @@ impl ModelConfig {
         }
     }
 
+    // =========== Ollama Models ===========
+
+    pub fn llama_32() -> Self {
+        Self {
+            id: "llama3.2".to_string(),
+            provider: Provider::Ollama,
+            context_length: 131_072,
+            max_output_tokens: 4096,
+            supports_streaming: true,
+            supports_functions: true,
+            supports_vision: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn nomic_embed_text() -> Self {
+        Self {
+            id: "nomic-embed-text".to_string(),
+            provider: Provider::Ollama,
+            context_length: 8192,
+            max_output_tokens: 0,
+            supports_streaming: false,
+            supports_functions: false,
+            supports_vision: false,
+            is_embedding: true,
+            embedding_dimensions: Some(768),
+            ..Default::default()
+        }
+    }
+
     // =========== Embedding Models ===========
 
     pub fn embedding_ada() -> Self {
@@ -279,6 +393,8 @@ pub struct GenerationConfig {
     pub strategy: GenerationStrategy,
     /// Response templates for simulation
     pub templates: Vec<String>,
+    /// Corpus for the Markov strategy; defaults to the global corpus
+    pub corpus: Option<CorpusConfig>,
 }
 
 impl Default for GenerationConfig {
@@ -293,6 +409,7 @@ impl Default for GenerationConfig {
                 "Let me think about this step by step.".to_string(),
                 "That's an interesting question. Here's what I can tell you:".to_string(),
             ],
+            corpus: None,
         }
     }
 }
@@ -312,6 +429,146 @@ pub enum GenerationStrategy {
     Fixed,
     /// Random tokens
     Random,
+    /// N-gram chain trained on a text corpus
+    Markov,
+}
+
+/// Serving capacity of a model
+///
+/// Up to `slots` requests are served at once, decoding as one batch whose
+/// inter-token latency grows with the batch size. Further requests wait for
+/// a slot, which delays their first token, and once `max_queue` are waiting
+/// new requests are rejected as overloaded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapacityConfig {
+    /// Requests served concurrently
+    pub slots: usize,
+    /// Fractional ITL increase with every slot busy (0.5 = 1.5x slower)
+    pub batch_slowdown: f64,
+    /// Requests allowed to wait for a slot
+    pub max_queue: usize,
+    /// Status of rejected requests: 429, or 529 as Anthropic reports
+    /// overload; defaults by provider
+    pub overload_status: Option<u16>,
+    /// Retry-After advertised on rejection (seconds)
+    pub retry_after_secs: u64,
+}
+
+impl Default for CapacityConfig {
+    fn default() -> Self {
+        Self {
+            slots: 8,
+            batch_slowdown: 0.5,
+            max_queue: 32,
+            overload_status: None,
+            retry_after_secs: 1,
+        }
+    }
+}
+
+impl CapacityConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.slots == 0 {
+            return Err("capacity slots must be greater than 0".to_string());
+        }
+        if self.batch_slowdown < 0.0 {
+            return Err("capacity batch_slowdown cannot be negative".to_string());
+        }
+        if let Some(status) = self.overload_status {
+            if status != 429 && status != 529 {
+                return Err(format!("capacity overload_status must be 429 or 529, got {}", status));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Token throughput of a model
+///
+/// Every response decodes at `tokens_per_second` instead of by sampled ITLs.
+/// Streams of the model also share the optional aggregate ceilings, across
+/// all callers and per API key, so they slow down as they contend for them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThroughputConfig {
+    /// Decode speed of a single response
+    pub tokens_per_second: f64,
+    /// Tokens per second shared by all streams of the model
+    pub global_tokens_per_second: Option<f64>,
+    /// Tokens per second shared by the streams of each API key
+    pub per_key_tokens_per_second: Option<f64>,
+}
+
+impl Default for ThroughputConfig {
+    fn default() -> Self {
+        Self {
+            tokens_per_second: 50.0,
+            global_tokens_per_second: None,
+            per_key_tokens_per_second: None,
+        }
+    }
+}
+
+impl ThroughputConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let rates = [
+            Some(self.tokens_per_second),
+            self.global_tokens_per_second,
+            self.per_key_tokens_per_second,
+        ];
+        if rates.into_iter().flatten().any(|rate| !(rate > 0.0 && rate.is_finite())) {
+            return Err("throughput rates must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Time between tokens of a single response
+    pub fn token_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.tokens_per_second)
+    }
+}
+
+/// How simulated embeddings are computed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingMode {
+    /// Projected character n-gram and word features; similar texts are near
+    #[default]
+    Semantic,
+    /// Pseudo-random vector seeded by the input hash
+    Hash,
+}
+
+/// Text corpus for the Markov generation strategy
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CorpusConfig {
+    /// Text or markdown files to train on
+    pub files: Vec<PathBuf>,
+    /// Words of context per prediction
+    #[serde(default = "default_markov_order")]
+    pub order: usize,
+}
+
+fn default_markov_order() -> usize {
+    2
+}
+
+impl CorpusConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.files.is_empty() {
+            return Err("corpus must list at least one file".to_string());
+        }
+        if !(1..=4).contains(&self.order) {
+            return Err("corpus order must be between 1 and 4".to_string());
+        }
+        for file in &self.files {
+            if !file.is_file() {
+                return Err(format!("corpus file not found: {}", file.display()));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -343,6 +600,17 @@ mod tests {
         assert!(!config.supports_streaming);
     }
 
+    #[test]
+    fn test_corpus_validation() {
+        let corpus = CorpusConfig {
+            files: vec![PathBuf::from("Cargo.toml")],
+            order: 2,
+        };
+        assert!(corpus.validate().is_ok());
+        assert!(CorpusConfig { order: 0, ..corpus.clone() }.validate().is_err());
+        assert!(CorpusConfig { files: vec![PathBuf::from("missing.txt")], ..corpus }.validate().is_err());
+    }
+
     #[test]
     fn test_invalid_config() {
         let config = ModelConfig {
@@ -0,0 +1,145 @@
+//! Content moderation configuration
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::error::{SimulationError, SimulatorResult};
+
+/// Categories reported by the moderation endpoint
+pub const MODERATION_CATEGORIES: &[&str] = &[
+    "harassment",
+    "harassment/threatening",
+    "hate",
+    "hate/threatening",
+    "illicit",
+    "illicit/violent",
+    "self-harm",
+    "self-harm/instructions",
+    "self-harm/intent",
+    "sexual",
+    "sexual/minors",
+    "violence",
+    "violence/graphic",
+];
+
+/// Content moderation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModerationConfig {
+    /// Apply the rules to chat input and refuse flagged requests
+    pub filter_completions: bool,
+    /// Score at or above which a category is flagged
+    pub flag_threshold: f64,
+    /// Upper bound of the seeded noise score for unmatched categories
+    pub baseline_score: f64,
+    /// Category trigger rules
+    pub rules: Vec<ModerationRule>,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            filter_completions: false,
+            flag_threshold: 0.5,
+            baseline_score: 0.01,
+            rules: vec![],
+        }
+    }
+}
+
+impl ModerationConfig {
+    pub fn validate(&self) -> SimulatorResult<()> {
+        if !(0.0..=1.0).contains(&self.flag_threshold) {
+            return Err(SimulationError::Validation {
+                message: "flag_threshold must be between 0.0 and 1.0".to_string(),
+                param: Some("moderation.flag_threshold".to_string()),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.baseline_score) {
+            return Err(SimulationError::Validation {
+                message: "baseline_score must be between 0.0 and 1.0".to_string(),
+                param: Some("moderation.baseline_score".to_string()),
+            });
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate().map_err(|e| SimulationError::Validation {
+                message: format!("Invalid moderation rule {}: {}", i, e),
+                param: Some(format!("moderation.rules[{}]", i)),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Rule that raises a category score when input matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationRule {
+    /// Category this rule scores, e.g. `violence`
+    pub category: String,
+    /// Case-insensitive substrings that trigger the rule
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Regular expressions that trigger the rule
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Score reported when the rule matches (0.0-1.0)
+    #[serde(default = "default_rule_score")]
+    pub score: f64,
+}
+
+fn default_rule_score() -> f64 {
+    0.95
+}
+
+impl ModerationRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if !MODERATION_CATEGORIES.contains(&self.category.as_str()) {
+            return Err(format!("Unknown moderation category: {}", self.category));
+        }
+        if self.keywords.is_empty() && self.patterns.is_empty() {
+            return Err("Rule needs at least one keyword or pattern".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.score) {
+            return Err("Score must be between 0.0 and 1.0".to_string());
+        }
+        for pattern in &self.patterns {
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(category: &str) -> ModerationRule {
+        ModerationRule {
+            category: category.to_string(),
+            keywords: vec!["attack".to_string()],
+            patterns: vec![],
+            score: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        assert!(rule("violence").validate().is_ok());
+        assert!(rule("not-a-category").validate().is_err());
+
+        let bad_pattern = ModerationRule {
+            patterns: vec!["(unclosed".to_string()],
+            ..rule("hate")
+        };
+        assert!(bad_pattern.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config = ModerationConfig {
+            flag_threshold: 1.5,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(ModerationConfig::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,439 @@
+//! Latency timeline configuration
+//!
+//! Soak tests need latency that changes while they run. A timeline lists
+//! phases, each in effect for a while after server start or after every match
+//! of a cron schedule, that scale latency or swap the profiles of models.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::LatencyProfile;
+
+/// Phases of degraded or recovering latency over time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyTimeline {
+    /// Phases in order of precedence; the first in effect applies
+    pub phases: Vec<TimelinePhase>,
+    /// Restart phases timed from server start after this many seconds
+    pub cycle_secs: Option<f64>,
+}
+
+impl LatencyTimeline {
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+
+    pub fn validate(&self, profiles: &HashMap<String, LatencyProfile>) -> Result<(), String> {
+        if let Some(cycle) = self.cycle_secs {
+            if !(cycle > 0.0 && cycle.is_finite()) {
+                return Err("timeline cycle_secs must be positive".to_string());
+            }
+        }
+        for (i, phase) in self.phases.iter().enumerate() {
+            phase.validate(profiles).map_err(|e| format!("phase {}: {}", i, e))?;
+        }
+        Ok(())
+    }
+
+    /// Phase in effect `elapsed` after server start, at wall-clock time `now`
+    pub fn active(&self, elapsed: Duration, now: DateTime<Utc>) -> Option<&TimelinePhase> {
+        self.active_phase(elapsed, now).map(|(_, phase)| phase)
+    }
+
+    /// Position and phase in effect `elapsed` after server start, at
+    /// wall-clock time `now`
+    pub fn active_phase(&self, elapsed: Duration, now: DateTime<Utc>) -> Option<(usize, &TimelinePhase)> {
+        let mut since_start = elapsed.as_secs_f64();
+        if let Some(cycle) = self.cycle_secs {
+            since_start %= cycle;
+        }
+        self.phases.iter()
+            .enumerate()
+            .find(|(_, phase)| phase.is_active(since_start, now))
+    }
+}
+
+/// One phase of a latency timeline
+///
+/// A phase starts `start_secs` after server start, or at every match of
+/// `cron`, and lasts `duration_secs`. Phases timed from start last for the
+/// rest of the run, or cycle, when no duration is given.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimelinePhase {
+    /// Label logged when the phase takes effect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Start, in seconds after server start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_secs: Option<f64>,
+    /// Start, at every match of a five-field cron expression in UTC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cron: Option<CronSchedule>,
+    /// Length of the phase (seconds)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    /// Factor on the latency multiplier while the phase is in effect
+    pub multiplier: f64,
+    /// Latency profile of every model while the phase is in effect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Latency profiles by model ID, taking precedence over `profile`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub models: HashMap<String, String>,
+}
+
+impl Default for TimelinePhase {
+    fn default() -> Self {
+        Self {
+            name: None,
+            start_secs: None,
+            cron: None,
+            duration_secs: None,
+            multiplier: 1.0,
+            profile: None,
+            models: HashMap::new(),
+        }
+    }
+}
+
+impl TimelinePhase {
+    /// Phase from `start_secs` to `end_secs` after server start
+    pub fn between(start_secs: f64, end_secs: f64) -> Self {
+        Self {
+            start_secs: Some(start_secs),
+            duration_secs: Some(end_secs - start_secs),
+            ..Default::default()
+        }
+    }
+
+    /// Phase lasting `duration_secs` from every match of `cron`
+    pub fn cron(cron: CronSchedule, duration_secs: f64) -> Self {
+        Self {
+            cron: Some(cron),
+            duration_secs: Some(duration_secs),
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self, profiles: &HashMap<String, LatencyProfile>) -> Result<(), String> {
+        match (self.start_secs, &self.cron) {
+            (Some(start), None) if start >= 0.0 && start.is_finite() => {}
+            (Some(_), None) => return Err("start_secs must be a non-negative number".to_string()),
+            (None, Some(_)) if self.duration_secs.is_none() => {
+                return Err("cron phases need duration_secs".to_string());
+            }
+            (None, Some(_)) => {}
+            _ => return Err("phase needs exactly one of start_secs and cron".to_string()),
+        }
+        if let Some(duration) = self.duration_secs {
+            if !(duration > 0.0 && duration.is_finite()) {
+                return Err("duration_secs must be positive".to_string());
+            }
+        }
+        if !(self.multiplier >= 0.0 && self.multiplier.is_finite()) {
+            return Err("multiplier must be a non-negative number".to_string());
+        }
+        for profile in self.profile.iter().chain(self.models.values()) {
+            if !profiles.contains_key(profile) {
+                return Err(format!("profile '{}' not found", profile));
+            }
+        }
+        Ok(())
+    }
+
+    /// Profile `model` uses during the phase, if swapped
+    pub fn profile_for(&self, model: &str) -> Option<&str> {
+        self.models.get(model).or(self.profile.as_ref()).map(String::as_str)
+    }
+
+    fn is_active(&self, since_start: f64, now: DateTime<Utc>) -> bool {
+        let duration = self.duration_secs.unwrap_or(f64::INFINITY);
+        if let Some(start) = self.start_secs {
+            return since_start >= start && since_start - start < duration;
+        }
+
+        self.cron.as_ref()
+            .is_some_and(|schedule| schedule.matched_within(now, Duration::from_secs_f64(duration)))
+    }
+}
+
+/// Five-field cron expression: minute, hour, day of month, month, day of week
+///
+/// Fields take `*`, numbers, ranges `a-b`, steps `*/n` or `a-b/n`, and
+/// comma-separated lists of those. Sunday is 0 or 7. Parsed once when read
+/// from configuration, and written back as the original expression.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether days of month and of week are restricted, which then match
+    /// either way as in standard cron
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// Whether `time`, to the minute, matches
+    pub fn matches(&self, time: DateTime<Utc>) -> bool {
+        bit(self.minutes, time.minute())
+            && bit(self.hours, time.hour())
+            && bit(self.months, time.month())
+            && self.day_matches(time)
+    }
+
+    /// Whether a minute matching the schedule began less than `window` before `now`
+    pub fn matched_within(&self, now: DateTime<Utc>, window: Duration) -> bool {
+        let window = chrono::Duration::from_std(window).unwrap_or(chrono::Duration::MAX);
+        let earliest = now.checked_sub_signed(window).unwrap_or(DateTime::<Utc>::MIN_UTC);
+        self.previous_match(now, earliest)
+            .is_some_and(|minute| now - minute < window)
+    }
+
+    /// Latest matching minute at or before `time`, if one falls at or after
+    /// `earliest`
+    ///
+    /// Walks back a field at a time, skipping whole months, days and hours
+    /// that don't match, so the cost doesn't grow with the window.
+    pub fn previous_match(&self, time: DateTime<Utc>, earliest: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let minute = chrono::Duration::minutes(1);
+        let mut t = time.with_second(0)?.with_nanosecond(0)?;
+
+        while t >= earliest {
+            if !bit(self.months, t.month()) {
+                t = Utc.with_ymd_and_hms(t.year(), t.month(), 1, 0, 0, 0).single()? - minute;
+                continue;
+            }
+            if !self.day_matches(t) {
+                t = t.with_hour(0)?.with_minute(0)? - minute;
+                continue;
+            }
+            match highest_up_to(self.hours, t.hour()) {
+                None => {
+                    t = t.with_hour(0)?.with_minute(0)? - minute;
+                    continue;
+                }
+                Some(hour) if hour < t.hour() => t = t.with_hour(hour)?.with_minute(59)?,
+                Some(_) => {}
+            }
+            match highest_up_to(self.minutes, t.minute()) {
+                Some(m) => return Some(t.with_minute(m)?).filter(|t| *t >= earliest),
+                None => t = t.with_minute(0)? - minute,
+            }
+        }
+        None
+    }
+
+    /// Whether the day of `time` matches. When days of month and of week
+    /// are both restricted, either matches, as in standard cron.
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let day = bit(self.days, time.day());
+        let weekday = bit(self.weekdays, time.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+/// Whether `mask` allows `value`
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Highest value at most `value` that `mask` allows
+fn highest_up_to(mask: u64, value: u32) -> Option<u32> {
+    let allowed = mask & (u64::MAX >> (63 - value));
+    (allowed != 0).then(|| 63 - allowed.leading_zeros())
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = String;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        expression.parse()
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(schedule: CronSchedule) -> Self {
+        schedule.expression
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!("cron '{}' must have five fields", expression));
+        };
+
+        let field = |text: &str, min: u32, max: u32| {
+            cron_field(text, min, max).map_err(|e| format!("cron '{}': {}", expression, e))
+        };
+        let mut weekday_mask = field(weekdays, 0, 7)?;
+        if weekday_mask & (1 << 7) != 0 {
+            weekday_mask |= 1;
+        }
+
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: field(minutes, 0, 59)?,
+            hours: field(hours, 0, 23)?,
+            days: field(days, 1, 31)?,
+            months: field(months, 1, 12)?,
+            weekdays: weekday_mask,
+            any_day: days == "*",
+            any_weekday: weekdays == "*",
+        })
+    }
+}
+
+/// Bit mask of the values a cron field allows
+fn cron_field(text: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step cannot be 0".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let value = |v: &str| -> Result<u32, String> {
+            let v: u32 = v.parse().map_err(|_| format!("invalid value '{}'", v))?;
+            if (min..=max).contains(&v) {
+                Ok(v)
+            } else {
+                Err(format!("{} is outside {}-{}", v, min, max))
+            }
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((from, to)) => (value(from)?, value(to)?),
+                // `a/n` runs from a to the end of the field
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if from > to {
+            return Err(format!("range '{}' is reversed", range));
+        }
+
+        for v in (from..=to).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        // A Wednesday
+        Utc.with_ymd_and_hms(2025, 1, 15, hour, minute, 30).unwrap()
+    }
+
+    #[test]
+    fn test_relative_phases_and_cycle() {
+        let degraded = TimelinePhase { multiplier: 4.0, ..TimelinePhase::between(300.0, 600.0) };
+        let recovery = TimelinePhase { multiplier: 2.0, ..TimelinePhase::between(600.0, 900.0) };
+        let mut timeline = LatencyTimeline { phases: vec![degraded, recovery], cycle_secs: None };
+        let now = at(12, 0);
+
+        let multiplier = |timeline: &LatencyTimeline, secs: u64| {
+            timeline.active(Duration::from_secs(secs), now).map(|p| p.multiplier)
+        };
+        assert_eq!(multiplier(&timeline, 100), None);
+        assert_eq!(multiplier(&timeline, 300), Some(4.0));
+        assert_eq!(multiplier(&timeline, 700), Some(2.0));
+        assert_eq!(multiplier(&timeline, 1000), None);
+
+        timeline.cycle_secs = Some(1200.0);
+        assert_eq!(multiplier(&timeline, 1200 + 400), Some(4.0));
+    }
+
+    #[test]
+    fn test_cron_phase_window() {
+        let timeline = LatencyTimeline {
+            phases: vec![TimelinePhase::cron("*/15 9-17 * * 1-5".parse().unwrap(), 300.0)],
+            cycle_secs: None,
+        };
+        let active = |time| timeline.active(Duration::ZERO, time).is_some();
+
+        assert!(active(at(9, 0)));
+        assert!(active(at(9, 34)));
+        assert!(!active(at(9, 35)));
+        assert!(!active(at(8, 59)));
+        assert!(!active(at(9, 0) + chrono::Duration::days(3)), "Saturday");
+    }
+
+    #[test]
+    fn test_cron_parsing() {
+        let schedule: CronSchedule = "0 2 1 * 0".parse().unwrap();
+        assert!(schedule.matches(Utc.with_ymd_and_hms(2025, 2, 1, 2, 0, 0).unwrap()), "first of the month");
+        assert!(schedule.matches(Utc.with_ymd_and_hms(2025, 2, 2, 2, 0, 0).unwrap()), "Sunday");
+        assert!(!schedule.matches(Utc.with_ymd_and_hms(2025, 2, 3, 2, 0, 0).unwrap()));
+
+        assert!("* * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+    }
+
+    #[test]
+    fn test_previous_match_agrees_with_scan() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let window = chrono::Duration::days(30);
+        for expression in ["*/15 9-17 * * 1-5", "0 2 1 * 0", "30 23 29 2 *", "5,55 */6 * 1-3 *"] {
+            let schedule: CronSchedule = expression.parse().unwrap();
+            for step in 0..40 {
+                let now = start + chrono::Duration::minutes(step * 3001 + 17);
+                let scan = (0..window.num_minutes())
+                    .map(|m| now.with_second(0).unwrap() - chrono::Duration::minutes(m))
+                    .find(|t| schedule.matches(*t));
+                assert_eq!(schedule.previous_match(now, now - window), scan, "{} at {}", expression, now);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cron_round_trips_through_config() {
+        let phase: TimelinePhase = serde_yaml::from_str("cron: '*/15 9-17 * * 1-5'\nduration_secs: 300\n").unwrap();
+        assert_eq!(phase.cron, Some("*/15 9-17 * * 1-5".parse().unwrap()));
+        assert!(serde_yaml::to_string(&phase).unwrap().contains("cron: '*/15 9-17 * * 1-5'"));
+        assert!(serde_yaml::from_str::<TimelinePhase>("cron: '61 * * * *'\n").is_err());
+    }
+
+    #[test]
+    fn test_phase_validation() {
+        let profiles = super::super::LatencyConfig::default().profiles;
+        let swap = |profile: &str| TimelinePhase {
+            profile: Some(profile.to_string()),
+            ..TimelinePhase::between(0.0, 60.0)
+        };
+        assert!(swap("slow").validate(&profiles).is_ok());
+        assert!(swap("missing").validate(&profiles).is_err());
+        assert!(TimelinePhase::default().validate(&profiles).is_err());
+        assert!(TimelinePhase { duration_secs: None, ..TimelinePhase::cron("0 * * * *".parse().unwrap(), 1.0) }
+            .validate(&profiles).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! Synthetic audio generation
+//!
+//! Produces deterministic PCM16 audio (tones and silence) and simple
+//! WAV/MP3 containers for the simulated voice endpoints. No speech is synthesized; the audio only
+//! has to be well-formed and of plausible length.
+
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+/// Sample rate of generated PCM16 audio (Hz, mono)
+pub const PCM16_SAMPLE_RATE: u32 = 24_000;
+
+/// Amount of audio emitted for each spoken token
+pub const AUDIO_PER_TOKEN: Duration = Duration::from_millis(60);
+
+/// Tone generator producing little-endian PCM16 mono samples.
+///
+/// Keeps its phase across calls so consecutive chunks join without clicks.
+#[derive(Debug, Clone)]
+pub struct ToneSynthesizer {
+    frequency_hz: f64,
+    amplitude: f64,
+    sample_rate: u32,
+    position: u64,
+}
+
+impl ToneSynthesizer {
+    /// Create a synthesizer for a sine tone at the given frequency
+    pub fn new(frequency_hz: f64) -> Self {
+        Self {
+            frequency_hz,
+            amplitude: 0.2,
+            sample_rate: PCM16_SAMPLE_RATE,
+            position: 0,
+        }
+    }
+
+    /// Generate `duration` of tone
+    pub fn tone(&mut self, duration: Duration) -> Vec<u8> {
+        let count = self.sample_count(duration);
+        let mut bytes = Vec::with_capacity(count * 2);
+
+        for _ in 0..count {
+            let t = self.position as f64 / self.sample_rate as f64;
+            let value = (TAU * self.frequency_hz * t).sin() * self.amplitude * i16::MAX as f64;
+            bytes.extend_from_slice(&(value as i16).to_le_bytes());
+            self.position += 1;
+        }
+
+        bytes
+    }
+
+    /// Generate `duration` of silence
+    pub fn silence(&mut self, duration: Duration) -> Vec<u8> {
+        let count = self.sample_count(duration);
+        self.position += count as u64;
+        vec![0u8; count * 2]
+    }
+
+    fn sample_count(&self, duration: Duration) -> usize {
+        (duration.as_secs_f64() * self.sample_rate as f64).round() as usize
+    }
+}
+
+impl Default for ToneSynthesizer {
+    fn default() -> Self {
+        Self::new(440.0)
+    }
+}
+
+/// Playback duration of a PCM16 mono buffer at the default sample rate
+pub fn pcm16_duration(byte_len: usize) -> Duration {
+    Duration::from_secs_f64((byte_len / 2) as f64 / PCM16_SAMPLE_RATE as f64)
+}
+
+/// Spoken length of one word at normal speed
+const SPEECH_PER_WORD: Duration = Duration::from_millis(320);
+
+/// Pause after each spoken word at normal speed
+const PAUSE_PER_WORD: Duration = Duration::from_millis(80);
+
+/// Samples per MPEG-1 Layer III frame
+const MP3_FRAME_SAMPLES: u32 = 1152;
+
+/// Sample rate of generated MP3 audio (Hz)
+const MP3_SAMPLE_RATE: u32 = 44_100;
+
+/// Header of a 128 kbps, 44.1 kHz, mono MPEG-1 Layer III frame without CRC
+const MP3_FRAME_HEADER: [u8; 4] = [0xFF, 0xFB, 0x90, 0xC0];
+
+/// Length of a frame with the header above (144 * bitrate / sample rate)
+const MP3_FRAME_LEN: usize = 417;
+
+/// Duration of synthesized speech for `text` at the given speed
+pub fn speech_duration(text: &str, speed: f32) -> Duration {
+    let words = text.split_whitespace().count().max(1) as u32;
+    (SPEECH_PER_WORD + PAUSE_PER_WORD).div_f64(speed.max(0.25) as f64) * words
+}
+
+/// Synthesize PCM16 "speech" for `text`: one tone per word, then a pause
+pub fn synthesize_speech(text: &str, speed: f32) -> Vec<u8> {
+    let speed = speed.max(0.25) as f64;
+    let tone = SPEECH_PER_WORD.div_f64(speed);
+    let pause = PAUSE_PER_WORD.div_f64(speed);
+
+    let mut synth = ToneSynthesizer::default();
+    let mut pcm = Vec::new();
+    for _ in 0..text.split_whitespace().count().max(1) {
+        pcm.extend(synth.tone(tone));
+        pcm.extend(synth.silence(pause));
+    }
+    pcm
+}
+
+/// Wrap PCM16 mono samples in a RIFF/WAVE container
+pub fn encode_wav(pcm: &[u8], sample_rate: u32) -> Vec<u8> {
+    let data_len = pcm.len() as u32;
+    let mut wav = Vec::with_capacity(44 + pcm.len());
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    wav.extend_from_slice(pcm);
+
+    wav
+}
+
+/// Encode `duration` of silence as a stream of MPEG-1 Layer III frames.
+///
+/// Every frame has zeroed side information and main data, which decoders
+/// play back as silence.
+pub fn encode_silent_mp3(duration: Duration) -> Vec<u8> {
+    let frame_duration = MP3_FRAME_SAMPLES as f64 / MP3_SAMPLE_RATE as f64;
+    let frames = (duration.as_secs_f64() / frame_duration).ceil().max(1.0) as usize;
+
+    let mut frame = vec![0u8; MP3_FRAME_LEN];
+    frame[..4].copy_from_slice(&MP3_FRAME_HEADER);
+    frame.repeat(frames)
+}
+
+/// Estimate the playback duration of an uploaded audio file.
+///
+/// WAV files are measured from their header; anything else is assumed to
+/// be compressed at 128 kbps.
+pub fn estimate_audio_duration(bytes: &[u8]) -> Duration {
+    if bytes.len() >= 44 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+        let byte_rate = u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]);
+        if byte_rate > 0 {
+            return Duration::from_secs_f64((bytes.len() - 44) as f64 / byte_rate as f64);
+        }
+    }
+    Duration::from_secs_f64(bytes.len() as f64 / 16_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tone_length() {
+        let mut synth = ToneSynthesizer::default();
+        let audio = synth.tone(Duration::from_millis(100));
+
+        assert_eq!(audio.len(), 2 * 2_400);
+        assert_eq!(pcm16_duration(audio.len()), Duration::from_millis(100));
+        assert!(audio.iter().any(|b| *b != 0));
+    }
+
+    #[test]
+    fn test_silence_is_zeroed() {
+        let mut synth = ToneSynthesizer::default();
+        let audio = synth.silence(Duration::from_millis(10));
+
+        assert_eq!(audio.len(), 2 * 240);
+        assert!(audio.iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_phase_continuity() {
+        let mut chunked = ToneSynthesizer::default();
+        let mut joined = chunked.tone(Duration::from_millis(5));
+        joined.extend(chunked.tone(Duration::from_millis(5)));
+
+        let mut whole = ToneSynthesizer::default();
+        assert_eq!(joined, whole.tone(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_speech_scales_with_text_and_speed() {
+        let short = speech_duration("hello", 1.0);
+        let long = speech_duration("hello there general kenobi", 1.0);
+        let fast = speech_duration("hello there general kenobi", 2.0);
+
+        assert_eq!(short, Duration::from_millis(400));
+        assert_eq!(long, short * 4);
+        assert_eq!(fast, long / 2);
+        assert_eq!(pcm16_duration(synthesize_speech("hello there", 1.0).len()), speech_duration("hello there", 1.0));
+    }
+
+    #[test]
+    fn test_wav_round_trip() {
+        let pcm = synthesize_speech("one two three", 1.0);
+        let wav = encode_wav(&pcm, PCM16_SAMPLE_RATE);
+
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(wav.len(), pcm.len() + 44);
+        assert_eq!(estimate_audio_duration(&wav), Duration::from_millis(1200));
+    }
+
+    #[test]
+    fn test_silent_mp3_frames() {
+        let mp3 = encode_silent_mp3(Duration::from_secs(1));
+
+        assert_eq!(mp3.len() % MP3_FRAME_LEN, 0);
+        assert_eq!(mp3.len() / MP3_FRAME_LEN, 39);
+        assert_eq!(&mp3[..4], &MP3_FRAME_HEADER);
+    }
+}
@@ -0,0 +1,204 @@
+//! Per-model serving capacity
+//!
+//! A pool holds a fixed number of slots. A request takes a slot for as long
+//! as its response takes to produce; when none is free it waits in a
+//! bounded queue, and when the queue is full it is rejected as overloaded.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::CapacityConfig;
+use crate::error::{SimulationError, SimulatorResult};
+use crate::types::Provider;
+
+/// Slots and wait queue of one model
+#[derive(Debug)]
+pub struct CapacityPool {
+    config: CapacityConfig,
+    /// Status of rejections, 429 or 529
+    overload_status: u16,
+    slots: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+    active: Arc<AtomicUsize>,
+}
+
+impl CapacityPool {
+    pub fn new(config: CapacityConfig, provider: Provider) -> Self {
+        let overload_status = config.overload_status.unwrap_or(match provider {
+            Provider::Anthropic => 529,
+            _ => 429,
+        });
+
+        Self {
+            slots: Arc::new(Semaphore::new(config.slots)),
+            overload_status,
+            queued: Arc::new(AtomicUsize::new(0)),
+            active: Arc::new(AtomicUsize::new(0)),
+            config,
+        }
+    }
+
+    /// Take a slot, waiting behind earlier requests if all are busy
+    pub async fn acquire(&self) -> SimulatorResult<Slot> {
+        if let Ok(permit) = self.slots.clone().try_acquire_owned() {
+            return Ok(self.admit(permit));
+        }
+
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.config.max_queue {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(self.overloaded());
+        }
+
+        // Leave the queue even if the caller gives up waiting
+        let _waiting = QueueEntry(self.queued.clone());
+        let permit = self.slots.clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| SimulationError::Internal("capacity pool closed".to_string()))?;
+        Ok(self.admit(permit))
+    }
+
+    /// Requests waiting for a slot
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Requests holding a slot
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub fn config(&self) -> &CapacityConfig {
+        &self.config
+    }
+
+    /// Snapshot of the pool's load
+    pub fn status(&self) -> CapacityStatus {
+        CapacityStatus {
+            slots: self.config.slots,
+            active: self.active(),
+            queued: self.queued(),
+            max_queue: self.config.max_queue,
+        }
+    }
+
+    fn admit(&self, permit: OwnedSemaphorePermit) -> Slot {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        let busy = (active - 1) as f64 / (self.config.slots.max(2) - 1) as f64;
+
+        Slot {
+            _permit: permit,
+            active: self.active.clone(),
+            itl_factor: 1.0 + self.config.batch_slowdown * busy.min(1.0),
+        }
+    }
+
+    fn overloaded(&self) -> SimulationError {
+        let retry_after = Duration::from_secs(self.config.retry_after_secs);
+        if self.overload_status == 529 {
+            SimulationError::Overloaded { retry_after }
+        } else {
+            SimulationError::RateLimitExceeded { retry_after }
+        }
+    }
+}
+
+/// A request's place in a pool, released on drop
+#[derive(Debug)]
+pub struct Slot {
+    _permit: OwnedSemaphorePermit,
+    active: Arc<AtomicUsize>,
+    itl_factor: f64,
+}
+
+impl Slot {
+    /// ITL multiplier from the batch the request joined
+    pub fn itl_factor(&self) -> f64 {
+        self.itl_factor
+    }
+
+    /// Keep the slot busy while the response is produced
+    pub fn hold_for(self, duration: Duration) {
+        if duration.is_zero() {
+            return;
+        }
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            drop(self);
+        });
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct QueueEntry(Arc<AtomicUsize>);
+
+impl Drop for QueueEntry {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Load of a capacity pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CapacityStatus {
+    pub slots: usize,
+    pub active: usize,
+    pub queued: usize,
+    pub max_queue: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(slots: usize, max_queue: usize, provider: Provider) -> Arc<CapacityPool> {
+        Arc::new(CapacityPool::new(
+            CapacityConfig { slots, max_queue, batch_slowdown: 1.0, ..Default::default() },
+            provider,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_queue_then_reject() {
+        let pool = pool(1, 1, Provider::OpenAI);
+
+        let first = pool.acquire().await.unwrap();
+        assert_eq!(first.itl_factor(), 1.0);
+
+        let waiting = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.acquire().await.map(|_| ()) }
+        });
+        while pool.queued() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let rejected = pool.acquire().await.unwrap_err();
+        assert!(matches!(rejected, SimulationError::RateLimitExceeded { .. }));
+
+        drop(first);
+        waiting.await.unwrap().unwrap();
+        assert_eq!(pool.status(), CapacityStatus { slots: 1, active: 0, queued: 0, max_queue: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_batch_slows_tokens_and_anthropic_overload() {
+        let pool = pool(3, 0, Provider::Anthropic);
+
+        let slots = [pool.acquire().await.unwrap(), pool.acquire().await.unwrap(), pool.acquire().await.unwrap()];
+        assert_eq!(slots.iter().map(Slot::itl_factor).collect::<Vec<_>>(), vec![1.0, 1.5, 2.0]);
+        assert_eq!(pool.active(), 3);
+
+        let rejected = pool.acquire().await.unwrap_err();
+        assert_eq!(rejected.status_code().as_u16(), 529);
+    }
+}
@@ -2,11 +2,11 @@
 
 use rand::prelude::*;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 
-use crate::config::{ChaosConfig, ErrorInjectionRule, CircuitBreakerConfig};
+use crate::config::{ChaosConfig, ErrorInjectionRule, CircuitBreakerConfig, StreamFaultType};
 use crate::error::{SimulationError, InjectedErrorType};
 
 /// Chaos engineering engine for error injection and circuit breaking
@@ -33,6 +33,17 @@ impl ChaosEngine {
 
     /// Maybe inject an error based on configuration
     pub fn maybe_inject_error(&self, model: &str, endpoint: &str) -> Option<SimulationError> {
+        self.maybe_inject_error_seeded(model, endpoint, None)
+    }
+
+    /// Maybe inject an error, with decisions drawn from `seed` when given
+    /// so the same request meets the same fate
+    pub fn maybe_inject_error_seeded(
+        &self,
+        model: &str,
+        endpoint: &str,
+        seed: Option<u64>,
+    ) -> Option<SimulationError> {
         if !self.config.is_active() {
             return None;
         }
@@ -46,15 +57,19 @@ impl ChaosEngine {
             }
         }
 
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
         // Check rate limiting
         if self.config.rate_limiting.enabled {
-            if let Some(error) = self.check_rate_limit(model) {
+            if let Some(error) = self.check_rate_limit(model, &mut rng) {
                 return Some(error);
             }
         }
 
         // Check error injection rules
-        let mut rng = rand::thread_rng();
 
         for rule in &self.config.errors {
             if !rule.enabled {
@@ -69,6 +84,11 @@ impl ChaosEngine {
                 continue;
             }
 
+            // Protocol faults break chat responses, which other endpoints lack
+            if rule.error_type.is_protocol_fault() && endpoint != "/chat/completions" {
+                continue;
+            }
+
             // Apply global probability multiplier
             let effective_prob = rule.probability * self.config.global_probability;
 
@@ -80,6 +100,31 @@ impl ChaosEngine {
         None
     }
 
+    /// Maybe pick a fault for a stream of `model` to meet mid-flight, drawn
+    /// from `seed` when given
+    pub fn maybe_stream_fault_seeded(&self, model: &str, seed: Option<u64>) -> Option<StreamFault> {
+        if !self.config.is_active() {
+            return None;
+        }
+
+        // Kept apart from the draws deciding up-front errors
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed ^ 0x5354_5245_414d),
+            None => StdRng::from_entropy(),
+        };
+
+        self.config.stream_faults.iter()
+            .filter(|rule| rule.enabled && rule.applies_to_model(model))
+            .find(|rule| rng.gen::<f64>() < rule.probability * self.config.global_probability)
+            .map(|rule| StreamFault {
+                fault_type: rule.fault_type,
+                after_tokens: rule.after_tokens,
+                stall: rule.stall(),
+                message: rule.message.clone()
+                    .unwrap_or_else(|| "Injected stream error".to_string()),
+            })
+    }
+
     /// Create an error from a rule
     fn create_error(&self, rule: &ErrorInjectionRule) -> SimulationError {
         let message = rule.message.clone()
@@ -95,6 +140,14 @@ impl ChaosEngine {
                 InjectedErrorType::AuthenticationError => 401,
                 InjectedErrorType::InvalidRequest => 400,
                 InjectedErrorType::ContextLengthExceeded => 400,
+                // Protocol faults malform responses that claim success
+                InjectedErrorType::TruncatedJson
+                | InjectedErrorType::WrongContentType
+                | InjectedErrorType::InvalidUtf8
+                | InjectedErrorType::MissingDataPrefix
+                | InjectedErrorType::MislabeledGzip
+                | InjectedErrorType::OversizedHeaders
+                | InjectedErrorType::ErrorPayload => 200,
             }
         });
 
@@ -164,12 +217,11 @@ impl ChaosEngine {
     }
 
     /// Check rate limit
-    fn check_rate_limit(&self, model: &str) -> Option<SimulationError> {
+    fn check_rate_limit(&self, model: &str, rng: &mut StdRng) -> Option<SimulationError> {
         // Simplified rate limiting - in production would use token bucket
         let limit = self.config.rate_limiting.get_limit(model);
 
         // Simple probabilistic rate limiting for simulation
-        let mut rng = rand::thread_rng();
         let rate_limit_prob = 1.0 / limit.requests_per_minute as f64;
 
         if rng.gen::<f64>() < rate_limit_prob {
@@ -206,6 +258,17 @@ impl Clone for ChaosEngine {
     }
 }
 
+/// A fault one stream meets after its first `after_tokens` tokens
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamFault {
+    pub fault_type: StreamFaultType,
+    pub after_tokens: usize,
+    /// Length of a stall
+    pub stall: Duration,
+    /// Message of an in-band error
+    pub message: String,
+}
+
 /// Circuit breaker state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CircuitState {
@@ -331,6 +394,7 @@ pub struct CircuitBreakerStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::StreamFaultRule;
 
     #[test]
     fn test_chaos_engine_disabled() {
@@ -392,6 +456,70 @@ mod tests {
         assert!(engine.maybe_inject_error("claude-3", "/chat").is_none());
     }
 
+    #[test]
+    fn test_seeded_decisions_repeat() {
+        let config = ChaosConfig {
+            enabled: true,
+            global_probability: 1.0,
+            errors: vec![ErrorInjectionRule {
+                name: "coin_flip".to_string(),
+                error_type: InjectedErrorType::ServerError,
+                probability: 0.5,
+                models: vec![],
+                endpoints: vec![],
+                message: None,
+                status_code: None,
+                delay_ms: None,
+                enabled: true,
+            }],
+            ..Default::default()
+        };
+
+        let engine = ChaosEngine::new(config);
+        let fates: Vec<bool> = (0..20)
+            .map(|seed| engine.maybe_inject_error_seeded("gpt-4", "/chat", Some(seed)).is_some())
+            .collect();
+        let again: Vec<bool> = (0..20)
+            .map(|seed| engine.maybe_inject_error_seeded("gpt-4", "/chat", Some(seed)).is_some())
+            .collect();
+
+        assert_eq!(fates, again);
+        assert!(fates.contains(&true) && fates.contains(&false));
+    }
+
+    #[test]
+    fn test_stream_faults_follow_seed() {
+        let config = ChaosConfig {
+            enabled: true,
+            stream_faults: vec![StreamFaultRule {
+                name: "coin_flip".to_string(),
+                fault_type: StreamFaultType::Stall,
+                probability: 0.5,
+                models: vec!["gpt-4".to_string()],
+                after_tokens: 2,
+                stall_ms: Some(500),
+                message: None,
+                enabled: true,
+            }],
+            ..Default::default()
+        };
+
+        let engine = ChaosEngine::new(config);
+        let faults: Vec<Option<StreamFault>> = (0..20)
+            .map(|seed| engine.maybe_stream_fault_seeded("gpt-4", Some(seed)))
+            .collect();
+        let again: Vec<Option<StreamFault>> = (0..20)
+            .map(|seed| engine.maybe_stream_fault_seeded("gpt-4", Some(seed)))
+            .collect();
+
+        assert_eq!(faults, again);
+        assert!(faults.iter().any(Option::is_none));
+        let fault = faults.into_iter().flatten().next().unwrap();
+        assert_eq!((fault.after_tokens, fault.stall), (2, Duration::from_millis(500)));
+
+        assert!(engine.maybe_stream_fault_seeded("claude-3", Some(0)).is_none());
+    }
+
     #[test]
     fn test_circuit_breaker() {
         let config = CircuitBreakerConfig {
@@ -0,0 +1,93 @@
+//! Timestamps for responses
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::time::Instant;
+
+use crate::config::ClockConfig;
+
+/// Clock behind every `created` timestamp
+#[derive(Debug)]
+pub struct Clock {
+    config: ClockConfig,
+    /// Readings taken so far, for the virtual clock
+    ticks: AtomicI64,
+    /// Wall time at creation, advanced by tokio's clock so that the system
+    /// clock follows paused tokio time
+    origin: (DateTime<Utc>, Instant),
+}
+
+impl Clock {
+    pub fn new(config: ClockConfig) -> Self {
+        Self {
+            config,
+            ticks: AtomicI64::new(0),
+            origin: (Utc::now(), Instant::now()),
+        }
+    }
+
+    /// Current time; each reading advances a virtual clock by one step
+    pub fn now(&self) -> DateTime<Utc> {
+        match &self.config {
+            ClockConfig::System => self.wall(),
+            ClockConfig::Fixed { at } => *at,
+            ClockConfig::Virtual { start, step_ms } => {
+                let tick = self.ticks.fetch_add(1, Ordering::Relaxed);
+                *start + Duration::milliseconds(tick * *step_ms as i64)
+            }
+        }
+    }
+
+    /// Current wall-clock time, which fixed and virtual clocks leave running
+    pub fn wall(&self) -> DateTime<Utc> {
+        self.origin.0 + elapsed(self.origin.1)
+    }
+
+    /// Current time in Unix seconds
+    pub fn timestamp(&self) -> i64 {
+        self.now().timestamp()
+    }
+
+    /// Current time plus `skipped` simulated time that was not slept; only
+    /// the system clock moves, the others don't follow elapsed time
+    pub fn now_after(&self, skipped: std::time::Duration) -> DateTime<Utc> {
+        match &self.config {
+            ClockConfig::System => self.now() + Duration::from_std(skipped).unwrap_or_default(),
+            _ => self.now(),
+        }
+    }
+}
+
+fn elapsed(since: Instant) -> Duration {
+    Duration::from_std(since.elapsed()).unwrap_or_default()
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(ClockConfig::System)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock() {
+        let at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = Clock::new(ClockConfig::Fixed { at });
+        assert_eq!(clock.timestamp(), 1_700_000_000);
+        assert_eq!(clock.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_virtual_clock_advances_per_reading() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = Clock::new(ClockConfig::Virtual { start, step_ms: 1500 });
+
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start + Duration::milliseconds(1500));
+        assert_eq!(clock.timestamp(), 1_700_000_003);
+    }
+}
@@ -0,0 +1,147 @@
+//! Semantic embedding simulation
+//!
+//! Text is reduced to weighted lexical features (words, word bigrams and
+//! character n-grams), and each feature is projected onto a fixed random
+//! ±1 direction derived from its hash. Texts sharing features therefore
+//! point the same way, so cosine similarity tracks lexical overlap. Every
+//! dimension is drawn independently, which keeps prefixes of a vector
+//! meaningful for Matryoshka-style truncation.
+
+use std::collections::BTreeMap;
+
+use super::ids::splitmix64;
+
+/// Weight of a whole word
+const WORD_WEIGHT: f32 = 1.0;
+/// Weight of two consecutive words
+const BIGRAM_WEIGHT: f32 = 0.5;
+/// Weight of each character n-gram of a word
+const NGRAM_WEIGHT: f32 = 0.25;
+/// Character n-gram lengths
+const NGRAM_SIZES: [usize; 2] = [3, 4];
+
+/// Unit-length embedding of `text` with `dimensions` components
+pub fn semantic_embedding(text: &str, dimensions: usize) -> Vec<f32> {
+    let mut embedding = vec![0.0f32; dimensions];
+
+    for (feature, weight) in features(text) {
+        // Dampen repeated features so one word can't dominate
+        let weight = weight.sqrt();
+        for (i, value) in embedding.iter_mut().enumerate() {
+            let bits = splitmix64(feature ^ splitmix64(i as u64));
+            *value += if bits >> 63 == 0 { weight } else { -weight };
+        }
+    }
+
+    if embedding.iter().all(|x| *x == 0.0) {
+        // Blank text has no features
+        if let Some(first) = embedding.first_mut() {
+            *first = 1.0;
+        }
+    }
+
+    normalize(embedding)
+}
+
+/// Keep the first `dimensions` components and rescale to unit length
+pub fn truncate_embedding(mut embedding: Vec<f32>, dimensions: usize) -> Vec<f32> {
+    embedding.truncate(dimensions);
+    normalize(embedding)
+}
+
+/// Hashed lexical features of a text with their summed weights, in a
+/// fixed order so the float sums are reproducible
+fn features(text: &str) -> BTreeMap<u64, f32> {
+    let mut features = BTreeMap::new();
+    let mut add = |kind: &str, value: &str, weight: f32| {
+        *features.entry(fnv1a(kind, value)).or_insert(0.0) += weight;
+    };
+
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+
+    for word in &words {
+        let bare = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if !bare.is_empty() {
+            add("w", bare, WORD_WEIGHT);
+        }
+
+        // Boundary markers distinguish prefixes and suffixes
+        let chars: Vec<char> = format!("<{}>", word).chars().collect();
+        for size in NGRAM_SIZES {
+            for gram in chars.windows(size) {
+                add("c", &gram.iter().collect::<String>(), NGRAM_WEIGHT);
+            }
+        }
+    }
+
+    for pair in words.windows(2) {
+        add("b", &pair.join(" "), BIGRAM_WEIGHT);
+    }
+
+    features
+}
+
+/// FNV-1a hash of a feature, stable across platforms and toolchains
+fn fnv1a(kind: &str, value: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in kind.bytes().chain([0]).chain(value.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn normalize(mut embedding: Vec<f32>) -> Vec<f32> {
+    let magnitude: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if magnitude > 0.0 {
+        for x in &mut embedding {
+            *x /= magnitude;
+        }
+    }
+    embedding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn test_unit_length_and_deterministic() {
+        let embedding = semantic_embedding("The quick brown fox", 256);
+        assert_eq!(embedding.len(), 256);
+        assert!((cosine(&embedding, &embedding) - 1.0).abs() < 1e-4);
+        assert_eq!(embedding, semantic_embedding("The quick brown fox", 256));
+    }
+
+    #[test]
+    fn test_similarity_tracks_overlap() {
+        let query = semantic_embedding("How do I reset my password?", 512);
+        let close = semantic_embedding("Steps to reset a forgotten password", 512);
+        let far = semantic_embedding("Quarterly revenue grew in Europe", 512);
+
+        assert!(cosine(&query, &close) > cosine(&query, &far) + 0.2);
+        assert!(cosine(&query, &semantic_embedding("how do i reset my password", 512)) > 0.9);
+    }
+
+    #[test]
+    fn test_truncation_keeps_neighbours() {
+        let query = truncate_embedding(semantic_embedding("kubernetes pod scheduling", 3072), 256);
+        let close = truncate_embedding(semantic_embedding("scheduling pods in kubernetes", 3072), 256);
+        let far = truncate_embedding(semantic_embedding("chocolate cake recipe", 3072), 256);
+
+        assert_eq!(query.len(), 256);
+        assert!((cosine(&query, &query) - 1.0).abs() < 1e-4);
+        assert!(cosine(&query, &close) > cosine(&query, &far));
+    }
+
+    #[test]
+    fn test_blank_text() {
+        let embedding = semantic_embedding("   ", 8);
+        assert_eq!(embedding[0], 1.0);
+    }
+}
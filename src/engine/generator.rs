@@ -1,38 +1,45 @@
 //! Response generation implementation
 
 use rand::prelude::*;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::HashMap;
 
-use crate::config::GenerationConfig;
-use crate::types::Message;
+use super::hash::StableHasher;
+use super::markov::{extract_keywords, MarkovChain};
+use super::recall::recall_answer;
+use super::sampling::{SamplingParams, WordSampler};
+use crate::config::{CorpusConfig, GenerationConfig};
+use crate::tokenizer::TokenizerKind;
+use crate::types::{Message, Role};
 
 /// Generates simulated LLM responses
 pub struct ResponseGenerator {
-    rng: StdRng,
+    seed: Option<u64>,
     templates: Vec<String>,
+    chains: HashMap<CorpusConfig, MarkovChain>,
 }
 
 impl ResponseGenerator {
-    /// Create a new generator with random seed
+    /// Create a new generator, random unless seeded
     pub fn new(seed: Option<u64>) -> Self {
-        let rng = match seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
-
         Self {
-            rng,
+            seed,
             templates: default_templates(),
+            chains: HashMap::new(),
         }
     }
 
     /// Create with a specific seed
     pub fn with_seed(seed: u64) -> Self {
-        Self {
-            rng: StdRng::seed_from_u64(seed),
-            templates: default_templates(),
+        Self::new(Some(seed))
+    }
+
+    /// Train a Markov chain on a corpus, once per distinct corpus
+    pub fn train_corpus(&mut self, corpus: &CorpusConfig) -> Result<(), String> {
+        if !self.chains.contains_key(corpus) {
+            let chain = MarkovChain::from_corpus(corpus)?;
+            self.chains.insert(corpus.clone(), chain);
         }
+        Ok(())
     }
 
     /// Generate a response based on input messages
@@ -42,21 +49,75 @@ impl ResponseGenerator {
         max_tokens: u32,
         config: &GenerationConfig,
     ) -> (String, u32) {
-        let mut rng = self.rng.clone();
+        self.generate_response_for(
+            messages,
+            max_tokens,
+            config,
+            TokenizerKind::default(),
+            &SamplingParams::default(),
+        )
+    }
+
+    /// Generate a response under the request's sampling parameters, capped
+    /// at `max_tokens` as counted by `tokenizer`. A code fence the cap cuts
+    /// open is closed within the budget.
+    ///
+    /// Seeded runs, seeded requests and temperature 0 draw from a hash of
+    /// the seeds and the prompt, so output is reproducible per prompt. The
+    /// length spans the configured range at the default temperature of 1
+    /// and above, and narrows to its middle as temperature falls to 0;
+    /// higher temperatures also widen the vocabulary.
+    /// A question about something the user said earlier, such as their
+    /// name, is answered first.
+    pub fn generate_response_for(
+        &self,
+        messages: &[Message],
+        max_tokens: u32,
+        config: &GenerationConfig,
+        tokenizer: TokenizerKind,
+        sampling: &SamplingParams,
+    ) -> (String, u32) {
+        let tokens = self.generate_tokens_for(messages, max_tokens, config, tokenizer, sampling);
+        let count = tokens.len() as u32;
+        (tokens.concat(), count)
+    }
+
+    /// Generate a response as `tokenizer` tokens, as
+    /// [`generate_response_for`](Self::generate_response_for) does, for
+    /// callers that stream or count them
+    pub fn generate_tokens_for(
+        &self,
+        messages: &[Message],
+        max_tokens: u32,
+        config: &GenerationConfig,
+        tokenizer: TokenizerKind,
+        sampling: &SamplingParams,
+    ) -> Vec<String> {
+        let seed = request_seed(self.seed, sampling.seed, messages)
+            .or_else(|| sampling.is_greedy().then(|| prompt_seed(None, None, messages)));
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut sampler = WordSampler::new(sampling, tokenizer);
 
         // Determine target length
-        let target_tokens = rng.gen_range(config.min_tokens..=config.max_tokens.min(max_tokens));
+        let max = config.max_tokens.min(max_tokens);
+        let min = config.min_tokens.min(max);
+        let half_range = (max - min) as f64 / 2.0 * sampling.length_spread();
+        let offset = if half_range > 0.0 { rng.gen_range(-half_range..=half_range) } else { 0.0 };
+        let target_tokens = ((min + max) as f64 / 2.0 + offset).round() as u32;
 
         // Generate based on strategy
         let content = match &config.strategy {
             crate::config::GenerationStrategy::Template => {
-                self.generate_from_templates(messages, target_tokens as usize, &mut rng, config)
+                self.generate_from_templates(messages, target_tokens as usize, &mut sampler, &mut rng, config)
             }
             crate::config::GenerationStrategy::Lorem => {
-                generate_lorem(target_tokens as usize, &mut rng)
+                generate_lorem(target_tokens as usize, &mut sampler, &mut rng)
             }
             crate::config::GenerationStrategy::Echo => {
-                self.generate_echo(messages, target_tokens as usize)
+                self.generate_echo(messages, target_tokens as usize, &mut sampler, &mut rng)
             }
             crate::config::GenerationStrategy::Fixed => {
                 config.templates.first()
@@ -64,14 +125,33 @@ impl ResponseGenerator {
                     .unwrap_or_else(|| "This is a simulated response.".to_string())
             }
             crate::config::GenerationStrategy::Random => {
-                generate_random_text(target_tokens as usize, &mut rng)
+                generate_random_text(target_tokens as usize, &mut sampler, &mut rng)
+            }
+            crate::config::GenerationStrategy::Markov => {
+                match config.corpus.as_ref().and_then(|c| self.chains.get(c)) {
+                    Some(chain) if !chain.is_empty() => {
+                        self.generate_markov(chain, messages, target_tokens as usize, &mut sampler, &mut rng)
+                    }
+                    _ => self.generate_from_templates(messages, target_tokens as usize, &mut sampler, &mut rng, config),
+                }
             }
         };
 
-        // Estimate actual tokens
-        let actual_tokens = estimate_tokens(&content);
+        // Answer questions about earlier turns before the filler text
+        let content = match recall_answer(messages) {
+            Some(answer) if !matches!(config.strategy, crate::config::GenerationStrategy::Fixed) => {
+                format!("{} {}", answer, content)
+            }
+            _ => content,
+        };
 
-        (content, actual_tokens)
+        // Tokenize once, trimming text that overshot the limit
+        let mut tokens = tokenizer.split(&content);
+        if tokens.len() > max_tokens as usize {
+            tokens.truncate(max_tokens as usize);
+            close_code_fence(&mut tokens, tokenizer);
+        }
+        tokens
     }
 
     /// Generate response from templates
@@ -79,26 +159,27 @@ impl ResponseGenerator {
         &self,
         messages: &[Message],
         target_tokens: usize,
+        sampler: &mut WordSampler,
         rng: &mut StdRng,
         config: &GenerationConfig,
     ) -> String {
-        let templates = if config.templates.is_empty() {
-            &self.templates
+        let templates: Vec<&str> = if config.templates.is_empty() {
+            self.templates.iter().map(String::as_str).collect()
         } else {
-            &config.templates
+            config.templates.iter().map(String::as_str).collect()
         };
 
         // Select base template
-        let template = templates.choose(rng).unwrap_or(&self.templates[0]);
+        let template = sampler.choose(&templates, rng);
 
         // Build response
-        let mut response = template.clone();
+        let mut response = template.to_string();
 
         // Expand to target length
         let target_chars = target_tokens * 4; // ~4 chars per token
         while response.len() < target_chars {
             response.push_str("\n\n");
-            response.push_str(self.generate_paragraph(messages, rng));
+            response.push_str(self.generate_paragraph(messages, sampler, rng));
         }
 
         // Truncate if needed
@@ -113,8 +194,27 @@ impl ResponseGenerator {
         response
     }
 
-    /// Generate a contextual paragraph
-    fn generate_paragraph(&self, messages: &[Message], rng: &mut StdRng) -> &str {
+    /// Generate corpus text conditioned on the last user message
+    fn generate_markov(
+        &self,
+        chain: &MarkovChain,
+        messages: &[Message],
+        target_tokens: usize,
+        sampler: &mut WordSampler,
+        rng: &mut StdRng,
+    ) -> String {
+        let prompt = messages.iter()
+            .rev()
+            .find(|m| m.role == Role::User)
+            .map(|m| m.text())
+            .unwrap_or_default();
+
+        // ~0.75 words per token
+        chain.generate(&extract_keywords(&prompt), target_tokens * 3 / 4, sampler, rng)
+    }
+
+    /// Generate a contextual paragraph, avoiding repeats under penalties
+    fn generate_paragraph(&self, messages: &[Message], sampler: &mut WordSampler, rng: &mut StdRng) -> &'static str {
         // Context-aware paragraph selection based on last message
         let last_message = messages.last().map(|m| m.text()).unwrap_or_default();
 
@@ -129,11 +229,17 @@ impl ResponseGenerator {
             GENERAL_RESPONSES
         };
 
-        paragraphs.choose(rng).unwrap_or(&GENERAL_RESPONSES[0])
+        sampler.choose(paragraphs, rng)
     }
 
     /// Generate echo response
-    fn generate_echo(&self, messages: &[Message], target_tokens: usize) -> String {
+    fn generate_echo(
+        &self,
+        messages: &[Message],
+        target_tokens: usize,
+        sampler: &mut WordSampler,
+        rng: &mut StdRng,
+    ) -> String {
         let last_message = messages.last()
             .map(|m| m.text())
             .unwrap_or_default();
@@ -146,10 +252,7 @@ impl ResponseGenerator {
         // Pad to target length
         let target_chars = target_tokens * 4;
         if response.len() < target_chars {
-            let padding = generate_lorem(
-                (target_chars - response.len()) / 4,
-                &mut rand::thread_rng(),
-            );
+            let padding = generate_lorem((target_chars - response.len()) / 4, sampler, rng);
             format!("{}\n\n{}", response, padding)
         } else {
             response
@@ -159,9 +262,7 @@ impl ResponseGenerator {
     /// Generate an embedding vector
     pub fn generate_embedding(&self, dimensions: usize, input: &str) -> Vec<f32> {
         // Use input hash for determinism
-        let mut hasher = DefaultHasher::new();
-        input.hash(&mut hasher);
-        let seed = hasher.finish();
+        let seed = StableHasher::new().str(input).finish();
 
         let mut rng = StdRng::seed_from_u64(seed);
         let mut embedding: Vec<f32> = (0..dimensions)
@@ -179,27 +280,19 @@ impl ResponseGenerator {
         embedding
     }
 
-    /// Tokenize text for streaming (simple word-based)
-    pub fn tokenize(&self, text: &str) -> Vec<String> {
-        let mut tokens = Vec::new();
-        let mut current = String::new();
-
-        for c in text.chars() {
-            current.push(c);
-            // Split on spaces, punctuation, or every ~4 chars
-            if c.is_whitespace() || c.is_ascii_punctuation() || current.len() >= 4 {
-                if !current.is_empty() {
-                    tokens.push(current.clone());
-                    current.clear();
-                }
-            }
-        }
+    /// Generate a transcript of roughly `word_count` words for an audio file
+    pub fn generate_transcript(&self, word_count: usize, audio: &[u8]) -> String {
+        // Use audio hash for determinism
+        let mut rng = StdRng::seed_from_u64(StableHasher::new().bytes(audio).finish());
+        let sampling = SamplingParams::default();
+        let mut sampler = WordSampler::new(&sampling, TokenizerKind::default());
 
-        if !current.is_empty() {
-            tokens.push(current);
-        }
+        generate_lorem(word_count.max(1), &mut sampler, &mut rng)
+    }
 
-        tokens
+    /// Tokenize text for streaming with the default tokenizer
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        TokenizerKind::default().split(text)
     }
 }
 
@@ -209,14 +302,26 @@ impl Default for ResponseGenerator {
     }
 }
 
-/// Estimate token count from text
-fn estimate_tokens(text: &str) -> u32 {
-    // Rough estimation: ~4 characters per token
-    ((text.len() as f64 / 4.0).ceil() as u32).max(1)
+/// Close a code fence left open by truncation, making room for the
+/// closing fence within the same token budget
+fn close_code_fence(tokens: &mut Vec<String>, tokenizer: TokenizerKind) {
+    let is_open = |tokens: &[String]| tokens.concat().matches("```").count() % 2 == 1;
+    if !is_open(tokens) {
+        return;
+    }
+
+    let closing = tokenizer.split("\n```");
+    if closing.len() > tokens.len() {
+        return;
+    }
+    tokens.truncate(tokens.len() - closing.len());
+    if is_open(tokens) {
+        tokens.extend(closing);
+    }
 }
 
 /// Generate lorem ipsum text
-fn generate_lorem<R: rand::Rng>(target_tokens: usize, rng: &mut R) -> String {
+fn generate_lorem<R: rand::Rng>(target_tokens: usize, sampler: &mut WordSampler, rng: &mut R) -> String {
     let words = [
         "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit",
         "sed", "do", "eiusmod", "tempor", "incididunt", "ut", "labore", "et", "dolore",
@@ -232,7 +337,7 @@ fn generate_lorem<R: rand::Rng>(target_tokens: usize, rng: &mut R) -> String {
     let target_words = target_tokens; // Roughly 1 token per word
 
     for _ in 0..target_words {
-        result.push(*words.choose(rng).unwrap());
+        result.push(sampler.choose(&words, rng));
     }
 
     // Capitalize first word and add punctuation
@@ -260,7 +365,7 @@ fn generate_lorem<R: rand::Rng>(target_tokens: usize, rng: &mut R) -> String {
 }
 
 /// Generate random text from a vocabulary
-fn generate_random_text(target_tokens: usize, rng: &mut StdRng) -> String {
+fn generate_random_text(target_tokens: usize, sampler: &mut WordSampler, rng: &mut StdRng) -> String {
     let vocab = [
         "the", "a", "is", "are", "was", "were", "have", "has", "had", "do", "does",
         "did", "will", "would", "could", "should", "may", "might", "must", "can",
@@ -271,12 +376,41 @@ fn generate_random_text(target_tokens: usize, rng: &mut StdRng) -> String {
     ];
 
     let words: Vec<&str> = (0..target_tokens)
-        .map(|_| *vocab.choose(rng).unwrap())
+        .map(|_| sampler.choose(&vocab, rng))
         .collect();
 
     words.join(" ")
 }
 
+/// Seed of a request: a hash of the global seed, the request's own seed
+/// and the conversation. `None` when neither seed is set.
+pub fn request_seed(global: Option<u64>, request: Option<u64>, messages: &[Message]) -> Option<u64> {
+    (global.is_some() || request.is_some()).then(|| prompt_seed(global, request, messages))
+}
+
+/// Seed of a request to a non-chat endpoint: a hash of the global seed,
+/// the endpoint and the request's inputs. `None` without a global seed.
+pub fn input_seed<I>(global: Option<u64>, endpoint: &str, inputs: I) -> Option<u64>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    global.map(|global| {
+        inputs.into_iter()
+            .fold(StableHasher::new().u64(global).str(endpoint), |hasher, input| hasher.bytes(input.as_ref()))
+            .finish()
+    })
+}
+
+/// Hash of the seeds and the conversation text
+fn prompt_seed(global: Option<u64>, request: Option<u64>, messages: &[Message]) -> u64 {
+    let mut hasher = StableHasher::new().opt_u64(global).opt_u64(request);
+    for message in messages {
+        hasher = hasher.u64(message.role as u64).str(&message.text());
+    }
+    hasher.finish()
+}
+
 /// Default response templates
 fn default_templates() -> Vec<String> {
     vec![
@@ -397,12 +531,168 @@ mod tests {
 
         assert!(!tokens.is_empty());
         assert!(tokens.len() > 3); // Should split into multiple tokens
+        assert_eq!(tokens.concat(), "Hello, world! How are you?");
+    }
+
+    #[test]
+    fn test_response_capped_by_tokenizer() {
+        let gen = ResponseGenerator::with_seed(42);
+        let messages = vec![Message::user("Test")];
+        let config = GenerationConfig {
+            strategy: GenerationStrategy::Fixed,
+            templates: vec!["One two three four five six seven eight".to_string()],
+            min_tokens: 1,
+            max_tokens: 3,
+            corpus: None,
+        };
+
+        let (response, tokens) = gen.generate_response_for(
+            &messages,
+            3,
+            &config,
+            TokenizerKind::Cl100kBase,
+            &SamplingParams::default(),
+        );
+        assert_eq!(response, "One two three");
+        assert_eq!(tokens, 3);
+    }
+
+    #[test]
+    fn test_truncation_closes_code_fence() {
+        let gen = ResponseGenerator::with_seed(42);
+        let messages = vec![Message::user("Test")];
+        let config = GenerationConfig {
+            strategy: GenerationStrategy::Fixed,
+            templates: vec!["Example:\n```rust\nfn main() {\n    println!(\"hello\");\n}\n```\nDone.".to_string()],
+            min_tokens: 1,
+            max_tokens: 12,
+            corpus: None,
+        };
+
+        let (response, tokens) = gen.generate_response_for(
+            &messages,
+            12,
+            &config,
+            TokenizerKind::Cl100kBase,
+            &SamplingParams::default(),
+        );
+        assert!(response.ends_with("\n```"), "{:?}", response);
+        assert_eq!(response.matches("```").count(), 2);
+        assert!(tokens <= 12);
+    }
+
+    fn lorem_config() -> GenerationConfig {
+        GenerationConfig {
+            strategy: GenerationStrategy::Lorem,
+            min_tokens: 50,
+            max_tokens: 150,
+            ..Default::default()
+        }
+    }
+
+    fn distinct_words(text: &str) -> usize {
+        let mut words: Vec<String> = text.split_whitespace()
+            .map(|w| w.trim_matches('.').to_lowercase())
+            .collect();
+        words.sort();
+        words.dedup();
+        words.len()
+    }
+
+    #[test]
+    fn test_seeded_generation_follows_prompt() {
+        let gen = ResponseGenerator::with_seed(42);
+        let config = lorem_config();
+        let generate = |prompt: &str, seed: Option<u64>| {
+            gen.generate_response_for(
+                &[Message::user(prompt)],
+                500,
+                &config,
+                TokenizerKind::default(),
+                &SamplingParams { seed, ..Default::default() },
+            ).0
+        };
+
+        assert_eq!(generate("First", None), generate("First", None));
+        assert_ne!(generate("First", None), generate("Second", None));
+        assert_ne!(generate("First", None), generate("First", Some(7)));
+    }
+
+    #[test]
+    fn test_request_seed() {
+        let messages = [Message::user("Hi")];
+        assert_eq!(request_seed(None, None, &messages), None);
+        assert_eq!(request_seed(None, Some(1), &messages), request_seed(None, Some(1), &messages));
+        assert_ne!(request_seed(Some(1), None, &messages), request_seed(None, Some(1), &messages));
+
+        // Seeds must survive toolchain updates for runs to replay
+        assert_eq!(request_seed(Some(42), None, &messages), Some(17383961659969005814));
+        assert_eq!(input_seed(None, "/embeddings", ["Hi"]), None);
+        assert_eq!(input_seed(Some(42), "/embeddings", ["Hi"]), Some(9417000211643172938));
+    }
+
+    #[test]
+    fn test_zero_temperature_is_deterministic_by_prompt() {
+        let greedy = SamplingParams { temperature: 0.0, ..Default::default() };
+        let generate = |prompt: &str| {
+            ResponseGenerator::new(None).generate_response_for(
+                &[Message::user(prompt)],
+                500,
+                &lorem_config(),
+                TokenizerKind::default(),
+                &greedy,
+            ).0
+        };
+
+        assert_eq!(generate("Same prompt"), generate("Same prompt"));
+        assert_ne!(generate("Same prompt"), generate("Other prompt"));
+    }
+
+    #[test]
+    fn test_temperature_widens_vocabulary_and_length() {
+        let generate = |temperature: f32, seed: u64| {
+            ResponseGenerator::with_seed(seed).generate_response_for(
+                &[Message::user("Test")],
+                500,
+                &lorem_config(),
+                TokenizerKind::default(),
+                &SamplingParams { temperature, ..Default::default() },
+            ).0
+        };
+
+        let cold: Vec<String> = (0..10).map(|seed| generate(0.0, seed)).collect();
+        let hot: Vec<String> = (0..10).map(|seed| generate(2.0, seed)).collect();
+
+        let variety = |texts: &[String]| texts.iter().map(|t| distinct_words(t)).sum::<usize>();
+        assert!(variety(&hot) > variety(&cold));
+
+        let lengths = |texts: &[String]| {
+            let mut lengths: Vec<usize> = texts.iter().map(|t| t.split_whitespace().count()).collect();
+            lengths.sort();
+            lengths.dedup();
+            lengths.len()
+        };
+        assert_eq!(lengths(&cold), 1);
+        assert!(lengths(&hot) > 1);
     }
 
     #[test]
-    fn test_token_estimation() {
-        assert_eq!(estimate_tokens("test"), 1);
-        assert_eq!(estimate_tokens("hello world"), 3);
-        assert!(estimate_tokens("") >= 1);
+    fn test_penalties_reduce_repetition() {
+        let generate = |penalty: f32| {
+            ResponseGenerator::with_seed(7).generate_response_for(
+                &[Message::user("Test")],
+                500,
+                &lorem_config(),
+                TokenizerKind::default(),
+                &SamplingParams {
+                    temperature: 0.5,
+                    presence_penalty: penalty,
+                    frequency_penalty: penalty,
+                    ..Default::default()
+                },
+            ).0
+        };
+
+        assert!(distinct_words(&generate(1.0)) > distinct_words(&generate(0.0)));
     }
 }
@@ -0,0 +1,71 @@
+//! Stable hashing for seeds and fingerprints
+//!
+//! `DefaultHasher` and the standard `Hash` impls may change between Rust
+//! releases, which would silently change seeded output. Values here are fed
+//! as explicit little-endian bytes to FNV-1a, whose output is fixed by its
+//! specification.
+
+use std::hash::Hasher;
+
+use fnv::FnvHasher;
+
+/// FNV-1a over explicitly encoded values
+#[derive(Default)]
+pub struct StableHasher(FnvHasher);
+
+impl StableHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a byte string, prefixed with its length so fields can't run together
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.write(&(bytes.len() as u64).to_le_bytes());
+        self.0.write(bytes);
+        self
+    }
+
+    pub fn str(self, text: &str) -> Self {
+        self.bytes(text.as_bytes())
+    }
+
+    pub fn u64(mut self, value: u64) -> Self {
+        self.0.write(&value.to_le_bytes());
+        self
+    }
+
+    /// Feed an optional value, distinguishing `None` from every `Some`
+    pub fn opt_u64(mut self, value: Option<u64>) -> Self {
+        match value {
+            Some(value) => {
+                self.0.write(&[1]);
+                self.u64(value)
+            }
+            None => {
+                self.0.write(&[0]);
+                self
+            }
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_is_pinned() {
+        // FNV-1a of the empty input is its offset basis
+        assert_eq!(StableHasher::new().finish(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(StableHasher::new().str("a").finish(), StableHasher::new().str("a").finish());
+        assert_ne!(
+            StableHasher::new().str("ab").str("c").finish(),
+            StableHasher::new().str("a").str("bc").finish()
+        );
+        assert_ne!(StableHasher::new().opt_u64(None).finish(), StableHasher::new().opt_u64(Some(0)).finish());
+    }
+}
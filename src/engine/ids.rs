@@ -0,0 +1,85 @@
+//! Response ID generation
+//!
+//! IDs are random by default. In deterministic mode they come from a
+//! counter mixed with the configured seed, so a replayed sequence of
+//! requests yields the same IDs in the same order.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use uuid::Uuid;
+
+/// Generates hex suffixes for response IDs
+#[derive(Debug)]
+pub struct IdGenerator {
+    /// Seed of the counter, when deterministic
+    seed: Option<u64>,
+    counter: AtomicU64,
+}
+
+impl IdGenerator {
+    /// Random UUID-based IDs
+    pub fn random() -> Self {
+        Self {
+            seed: None,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// IDs derived from a counter seeded with `seed`
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether IDs repeat across runs
+    pub fn is_deterministic(&self) -> bool {
+        self.seed.is_some()
+    }
+
+    /// `prefix` followed by `len` (at most 32) lowercase hex digits
+    pub fn id(&self, prefix: &str, len: usize) -> String {
+        let hex = match self.seed {
+            Some(seed) => {
+                let n = self.counter.fetch_add(1, Ordering::Relaxed);
+                let high = splitmix64(seed ^ splitmix64(n));
+                format!("{:016x}{:016x}", high, splitmix64(high))
+            }
+            None => Uuid::new_v4().simple().to_string(),
+        };
+        format!("{}{}", prefix, &hex[..len.min(hex.len())])
+    }
+}
+
+/// SplitMix64 finalizer, stable across platforms and toolchains
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_ids_repeat() {
+        let first = IdGenerator::seeded(42);
+        let second = IdGenerator::seeded(42);
+
+        let ids: Vec<String> = (0..3).map(|_| first.id("chatcmpl-", 24)).collect();
+        assert_eq!(ids, (0..3).map(|_| second.id("chatcmpl-", 24)).collect::<Vec<_>>());
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[0].len(), "chatcmpl-".len() + 24);
+        assert_ne!(ids[0], IdGenerator::seeded(7).id("chatcmpl-", 24));
+    }
+
+    #[test]
+    fn test_random_ids() {
+        let ids = IdGenerator::random();
+        assert!(!ids.is_deterministic());
+        assert_ne!(ids.id("msg_", 24), ids.id("msg_", 24));
+    }
+}
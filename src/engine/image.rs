@@ -0,0 +1,123 @@
+//! Synthetic image generation
+//!
+//! Renders deterministic placeholder PNGs for the simulated image
+//! endpoints. The same seed and size always produce the same bytes, so
+//! images can be served by URL without being stored.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Edge length of the blocks in the placeholder pattern (pixels)
+const BLOCK_SIZE: u32 = 32;
+
+/// A placeholder image that can be rendered on demand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaceholderImage {
+    pub seed: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PlaceholderImage {
+    pub fn new(seed: u64, width: u32, height: u32) -> Self {
+        Self { seed, width, height }
+    }
+
+    /// Path under which the image is served
+    pub fn path(&self) -> String {
+        format!("/v1/images/placeholder/{}/{}x{}.png", self.seed, self.width, self.height)
+    }
+
+    /// Encode the image as PNG
+    pub fn render(&self) -> Vec<u8> {
+        render_placeholder_png(self.seed, self.width, self.height)
+    }
+}
+
+/// Render a grid of seeded colour blocks as an RGB PNG
+pub fn render_placeholder_png(seed: u64, width: u32, height: u32) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let columns = width.div_ceil(BLOCK_SIZE) as usize;
+    let rows = height.div_ceil(BLOCK_SIZE) as usize;
+
+    // Blocks vary around a base colour so the image reads as one picture
+    let base: [u8; 3] = rng.gen();
+    let palette: Vec<[u8; 3]> = (0..columns * rows)
+        .map(|_| {
+            let mut color = base;
+            for channel in &mut color {
+                *channel = channel.wrapping_add(rng.gen_range(0..64));
+            }
+            color
+        })
+        .collect();
+
+    let mut pixels = Vec::with_capacity((width * height * 3) as usize);
+    for y in 0..height {
+        let row = (y / BLOCK_SIZE) as usize;
+        for x in 0..width {
+            let column = (x / BLOCK_SIZE) as usize;
+            pixels.extend_from_slice(&palette[row * columns + column]);
+        }
+    }
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Fast);
+
+    // Writing into a Vec only fails on invalid dimensions, which callers reject
+    let mut writer = encoder.write_header().expect("valid PNG header");
+    writer.write_image_data(&pixels).expect("valid PNG data");
+    writer.finish().expect("PNG finished");
+
+    bytes
+}
+
+/// Parse a placeholder file name of the form `WIDTHxHEIGHT.png`
+pub fn parse_placeholder_size(file: &str) -> Option<(u32, u32)> {
+    let (width, height) = file.strip_suffix(".png")?.split_once('x')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    if (1..=4096).contains(&width) && (1..=4096).contains(&height) {
+        Some((width, height))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[test]
+    fn test_render_is_deterministic() {
+        let a = render_placeholder_png(7, 64, 48);
+        let b = render_placeholder_png(7, 64, 48);
+        let c = render_placeholder_png(8, 64, 48);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a[..8], PNG_SIGNATURE);
+    }
+
+    #[test]
+    fn test_render_dimensions() {
+        let bytes = render_placeholder_png(1, 100, 50);
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let reader = decoder.read_info().unwrap();
+
+        assert_eq!(reader.info().width, 100);
+        assert_eq!(reader.info().height, 50);
+    }
+
+    #[test]
+    fn test_parse_placeholder_size() {
+        assert_eq!(parse_placeholder_size("256x512.png"), Some((256, 512)));
+        assert_eq!(parse_placeholder_size("256x512"), None);
+        assert_eq!(parse_placeholder_size("0x512.png"), None);
+        assert_eq!(parse_placeholder_size("9999x1.png"), None);
+    }
+}
@@ -0,0 +1,357 @@
+//! Corpus-trained Markov text generation
+//!
+//! Corpus text is split into whitespace-separated words with explicit line
+//! breaks, so markdown structure (headings, lists, code fences and their
+//! indentation) survives the round trip. Generation starts from a line that
+//! mentions the prompt's keywords when one exists, and prefers keyword
+//! successors along the way.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::prelude::*;
+
+use super::sampling::WordSampler;
+use crate::config::CorpusConfig;
+
+/// Line break word
+const NEWLINE: &str = "\n";
+
+/// Weight multiplier for successors matching a prompt keyword
+const KEYWORD_BIAS: u32 = 4;
+
+/// Common words never used as keywords
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "also", "been", "before", "being", "could", "does", "doing",
+    "each", "from", "have", "here", "into", "just", "like", "more", "most", "much", "only",
+    "other", "over", "please", "should", "some", "such", "than", "that", "their", "them",
+    "then", "there", "these", "they", "this", "those", "very", "what", "when", "where",
+    "which", "while", "will", "with", "would", "your",
+];
+
+/// N-gram model over a word corpus
+#[derive(Debug, Clone, Default)]
+pub struct MarkovChain {
+    order: usize,
+    vocab: Vec<String>,
+    /// Words of the corpus, as vocabulary ids
+    words: Vec<u32>,
+    /// Successor counts per `order`-word state
+    transitions: HashMap<Vec<u32>, Vec<(u32, u32)>>,
+    /// Positions where a line starts
+    line_starts: Vec<usize>,
+    /// Line starts by the normalized words on that line
+    keyword_index: HashMap<String, Vec<usize>>,
+}
+
+impl MarkovChain {
+    /// Train on the files of a corpus
+    pub fn from_corpus(corpus: &CorpusConfig) -> Result<Self, String> {
+        let texts = corpus.files.iter()
+            .map(|file| {
+                std::fs::read_to_string(file)
+                    .map_err(|e| format!("Failed to read corpus file {}: {}", file.display(), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::train(texts.iter().map(String::as_str), corpus.order))
+    }
+
+    /// Train on documents with `order` words of context
+    pub fn train<'a>(documents: impl IntoIterator<Item = &'a str>, order: usize) -> Self {
+        let mut chain = Self {
+            order: order.max(1),
+            ..Default::default()
+        };
+        let mut ids: HashMap<String, u32> = HashMap::new();
+
+        for document in documents {
+            for line in document.lines() {
+                let start = chain.words.len();
+                for word in split_line(line) {
+                    let id = *ids.entry(word.to_string()).or_insert_with(|| {
+                        chain.vocab.push(word.to_string());
+                        (chain.vocab.len() - 1) as u32
+                    });
+                    chain.words.push(id);
+                }
+                if chain.words.len() > start {
+                    chain.line_starts.push(start);
+                    for word in &chain.words[start..] {
+                        let key = normalize(&chain.vocab[*word as usize]);
+                        if key.len() >= 4 {
+                            chain.keyword_index.entry(key).or_default().push(start);
+                        }
+                    }
+                }
+                chain.push_newline(&mut ids);
+            }
+            // Separate documents with a blank line
+            chain.push_newline(&mut ids);
+        }
+
+        for window in chain.words.windows(chain.order + 1) {
+            let successors = chain.transitions.entry(window[..chain.order].to_vec()).or_default();
+            let next = window[chain.order];
+            match successors.iter_mut().find(|(id, _)| *id == next) {
+                Some((_, count)) => *count += 1,
+                None => successors.push((next, 1)),
+            }
+        }
+
+        for starts in chain.keyword_index.values_mut() {
+            starts.dedup();
+        }
+
+        chain
+    }
+
+    /// Whether the corpus had no usable text
+    pub fn is_empty(&self) -> bool {
+        self.line_starts.is_empty()
+    }
+
+    /// Generate about `target_words` words, biased toward `keywords`.
+    ///
+    /// Generation continues past the target to the end of the current
+    /// sentence or line, and closes any code fence left open. Successors
+    /// are drawn through `sampler`, so temperature and penalties apply.
+    pub fn generate<R: Rng>(
+        &self,
+        keywords: &[String],
+        target_words: usize,
+        sampler: &mut WordSampler,
+        rng: &mut R,
+    ) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+
+        let keywords: HashSet<&str> = keywords.iter().map(String::as_str).collect();
+        let limit = target_words.max(1) * 2 + 20;
+
+        let mut output: Vec<u32> = Vec::new();
+        let mut word_count = 0;
+        let mut state = self.start_state(&keywords, rng);
+
+        for &word in &state {
+            output.push(word);
+            word_count += usize::from(!self.is_newline(word));
+        }
+
+        while word_count < limit {
+            if word_count >= target_words && self.at_boundary(&output) {
+                break;
+            }
+
+            let next = match self.transitions.get(&state) {
+                Some(successors) => self.choose_successor(successors, &keywords, sampler, rng),
+                None => {
+                    // Dead end: continue from a fresh line
+                    output.push(self.newline_id());
+                    state = self.start_state(&keywords, rng);
+                    output.extend_from_slice(&state);
+                    word_count += state.iter().filter(|w| !self.is_newline(**w)).count();
+                    continue;
+                }
+            };
+
+            output.push(next);
+            word_count += usize::from(!self.is_newline(next));
+            state.remove(0);
+            state.push(next);
+        }
+
+        let mut text = self.render(&output);
+        if self.open_fence(&output) {
+            text.push_str("\n```");
+        }
+        text
+    }
+
+    /// Pick a starting state, preferring lines that mention a keyword
+    fn start_state<R: Rng>(&self, keywords: &HashSet<&str>, rng: &mut R) -> Vec<u32> {
+        let matching: Vec<usize> = keywords.iter()
+            .filter_map(|k| self.keyword_index.get(*k))
+            .flatten()
+            .copied()
+            .collect();
+
+        let candidates = if matching.is_empty() { &self.line_starts } else { &matching };
+        let start = *candidates.choose(rng).unwrap_or(&0);
+        let end = (start + self.order).min(self.words.len());
+        self.words[start..end].to_vec()
+    }
+
+    /// Weighted successor choice with a bias toward keywords
+    fn choose_successor<R: Rng>(
+        &self,
+        successors: &[(u32, u32)],
+        keywords: &HashSet<&str>,
+        sampler: &mut WordSampler,
+        rng: &mut R,
+    ) -> u32 {
+        let words: Vec<&str> = successors.iter().map(|(id, _)| self.vocab[*id as usize].as_str()).collect();
+        let weights: Vec<f64> = successors.iter()
+            .zip(&words)
+            .map(|((_, count), word)| {
+                let bias = if keywords.contains(normalize(word).as_str()) { KEYWORD_BIAS } else { 1 };
+                (count * bias) as f64
+            })
+            .collect();
+
+        successors[sampler.choose_weighted(&words, &weights, rng)].0
+    }
+
+    /// End of a sentence or line outside any code block
+    fn at_boundary(&self, output: &[u32]) -> bool {
+        let Some(last) = output.last() else {
+            return false;
+        };
+        let word = &self.vocab[*last as usize];
+        let ends_sentence = word == NEWLINE || word.ends_with(['.', '!', '?']);
+        ends_sentence && !self.open_fence(output)
+    }
+
+    /// Whether an odd number of code fences has been emitted
+    fn open_fence(&self, output: &[u32]) -> bool {
+        output.iter()
+            .filter(|w| self.vocab[**w as usize].trim_start().starts_with("```"))
+            .count() % 2 == 1
+    }
+
+    fn is_newline(&self, word: u32) -> bool {
+        self.vocab[word as usize] == NEWLINE
+    }
+
+    fn newline_id(&self) -> u32 {
+        self.vocab.iter().position(|w| w == NEWLINE).unwrap_or(0) as u32
+    }
+
+    fn push_newline(&mut self, ids: &mut HashMap<String, u32>) {
+        let id = *ids.entry(NEWLINE.to_string()).or_insert_with(|| {
+            self.vocab.push(NEWLINE.to_string());
+            (self.vocab.len() - 1) as u32
+        });
+        self.words.push(id);
+    }
+
+    /// Join words with spaces, without spacing around line breaks
+    fn render(&self, output: &[u32]) -> String {
+        let mut text = String::new();
+        for word in output {
+            let word = &self.vocab[*word as usize];
+            if word != NEWLINE && !text.is_empty() && !text.ends_with('\n') {
+                text.push(' ');
+            }
+            text.push_str(word);
+        }
+        text.trim_end().to_string()
+    }
+}
+
+/// Keywords of a prompt: distinctive words of four or more letters
+pub fn extract_keywords(text: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = text.split_whitespace()
+        .map(normalize)
+        .filter(|w| w.len() >= 4 && !STOPWORDS.contains(&w.as_str()))
+        .collect();
+    keywords.sort();
+    keywords.dedup();
+    keywords
+}
+
+/// Split a line into words, keeping its indentation on the first word
+fn split_line(line: &str) -> Vec<&str> {
+    let line = line.trim_end();
+    let indent = line.len() - line.trim_start().len();
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    if indent > 0 {
+        if let Some(first) = words.first_mut() {
+            *first = &line[..indent + first.len()];
+        }
+    }
+    words
+}
+
+/// Lowercase a word and strip surrounding punctuation
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SamplingParams;
+    use crate::tokenizer::TokenizerKind;
+
+    fn generate(chain: &MarkovChain, keywords: &[String], target_words: usize, seed: u64) -> String {
+        let params = SamplingParams::default();
+        let mut sampler = WordSampler::new(&params, TokenizerKind::default());
+        chain.generate(keywords, target_words, &mut sampler, &mut StdRng::seed_from_u64(seed))
+    }
+
+    const CORPUS: &str = "# Deployment guide\n\
+        \n\
+        Kubernetes clusters schedule pods onto nodes. The scheduler weighs resource requests.\n\
+        \n\
+        - Install the agent on every node.\n\
+        - Restart the service after upgrades.\n\
+        \n\
+        ```rust\n\
+        fn main() {\n    println!(\"hello\");\n}\n\
+        ```\n\
+        \n\
+        Databases replicate writes to followers. The leader acknowledges each write.\n";
+
+    #[test]
+    fn test_training_preserves_structure() {
+        let chain = MarkovChain::train([CORPUS], 2);
+        assert!(!chain.is_empty());
+        assert!(chain.vocab.iter().any(|w| w == "    println!(\"hello\");"));
+        assert!(chain.vocab.iter().any(|w| w == "```rust"));
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let chain = MarkovChain::train([CORPUS], 2);
+        let keywords = extract_keywords("Tell me about kubernetes");
+
+        let first = generate(&chain, &keywords, 20, 7);
+        let second = generate(&chain, &keywords, 20, 7);
+        assert_eq!(first, second);
+        assert!(!first.is_empty());
+    }
+
+    #[test]
+    fn test_generation_starts_near_keywords() {
+        let chain = MarkovChain::train([CORPUS], 2);
+        let keywords = extract_keywords("How do databases replicate?");
+
+        let text = generate(&chain, &keywords, 5, 1);
+        assert!(text.starts_with("Databases replicate"), "{}", text);
+    }
+
+    #[test]
+    fn test_code_fences_are_closed() {
+        let chain = MarkovChain::train([CORPUS], 2);
+        for seed in 0..20 {
+            let text = generate(&chain, &[], 30, seed);
+            assert_eq!(text.matches("```").count() % 2, 0, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_extract_keywords() {
+        assert_eq!(
+            extract_keywords("Which databases should I use for Kubernetes?"),
+            vec!["databases", "kubernetes"]
+        );
+    }
+
+    #[test]
+    fn test_empty_corpus() {
+        let chain = MarkovChain::train([""], 2);
+        assert!(chain.is_empty());
+        assert_eq!(generate(&chain, &[], 10, 0), "");
+    }
+}
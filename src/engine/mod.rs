@@ -9,19 +9,49 @@
 mod generator;
 mod chaos;
 mod state;
+mod moderation;
+mod markov;
+mod source;
+mod sampling;
+mod ids;
+mod clock;
+mod embedding;
+mod recall;
+mod capacity;
+mod warm_pool;
+mod throughput;
+mod hash;
+pub mod audio;
+pub mod image;
 
 pub use generator::*;
 pub use chaos::*;
 pub use state::*;
-
+pub use moderation::*;
+pub use markov::*;
+pub use source::*;
+pub use sampling::*;
+pub use ids::*;
+pub use clock::*;
+pub use embedding::*;
+pub use recall::*;
+pub use capacity::*;
+pub use warm_pool::*;
+pub use throughput::*;
+pub use hash::*;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use parking_lot::RwLock;
-use uuid::Uuid;
-
-use crate::config::{SimulatorConfig, ModelConfig};
-use crate::error::{SimulationError, SimulatorResult};
-use crate::latency::{LatencySimulator, LatencySchedule};
+use std::time::Duration;
+use tokio::time::Instant;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, StreamExt};
+use parking_lot::{Mutex, RwLock};
+
+use crate::config::{EmbeddingMode, GenerationStrategy, SimulatorConfig, ModelConfig, StreamFaultType, TimelinePhase};
+use crate::error::{ProtocolFault, SimulationError, SimulatorResult};
+use crate::latency::{LatencySimulator, LatencySchedule, Pacer};
 use crate::types::*;
 
 /// The main simulation engine
@@ -29,8 +59,19 @@ pub struct SimulationEngine {
     config: Arc<RwLock<SimulatorConfig>>,
     latency_sim: LatencySimulator,
     chaos_engine: ChaosEngine,
+    moderator: Moderator,
     generator: ResponseGenerator,
+    sources: RwLock<ResponseSources>,
+    fingerprint: String,
+    ids: IdGenerator,
+    clock: Clock,
     state: EngineState,
+    sessions: SessionStore,
+    capacity: HashMap<String, Arc<CapacityPool>>,
+    warm_pools: HashMap<String, Arc<WarmPool>>,
+    throughput: HashMap<String, Arc<ThroughputPool>>,
+    /// Position of the latency timeline phase last in effect
+    timeline_phase: Mutex<Option<usize>>,
     start_time: Instant,
 }
 
@@ -43,14 +84,32 @@ impl SimulationEngine {
         };
 
         let chaos_engine = ChaosEngine::new(config.chaos.clone());
-        let generator = ResponseGenerator::new(config.seed);
+        let moderator = Moderator::new(config.moderation.clone(), config.seed);
+        let mut generator = ResponseGenerator::new(config.seed);
+        train_corpora(&mut generator, &config);
+        let fingerprint = system_fingerprint(&config);
+        let ids = id_generator(&config);
+        let clock = Clock::new(config.deterministic.clock.clone());
+        let capacity = capacity_pools(&config);
+        let warm_pools = warm_pools(&config);
+        let throughput = throughput_pools(&config);
 
         Self {
             config: Arc::new(RwLock::new(config)),
             latency_sim,
             chaos_engine,
+            moderator,
             generator,
+            sources: RwLock::new(ResponseSources::default()),
+            fingerprint,
+            ids,
+            clock,
             state: EngineState::new(),
+            sessions: SessionStore::new(),
+            capacity,
+            warm_pools,
+            throughput,
+            timeline_phase: Mutex::new(None),
             start_time: Instant::now(),
         }
     }
@@ -75,15 +134,46 @@ impl SimulationEngine {
         };
 
         self.chaos_engine = ChaosEngine::new(config.chaos.clone());
+        self.moderator = Moderator::new(config.moderation.clone(), config.seed);
 
-        if let Some(seed) = config.seed {
-            self.generator = ResponseGenerator::with_seed(seed);
-        }
+        self.generator = ResponseGenerator::new(config.seed);
+        train_corpora(&mut self.generator, &config);
+        self.fingerprint = system_fingerprint(&config);
+        self.ids = id_generator(&config);
+        self.clock = Clock::new(config.deterministic.clock.clone());
+        self.capacity = capacity_pools(&config);
+        self.warm_pools = warm_pools(&config);
+        self.throughput = throughput_pools(&config);
 
         *self.config.write() = config;
         Ok(())
     }
 
+    /// Answer requests for `model` with `source` instead of the built-in generator
+    pub fn register_source(&self, model: impl Into<String>, source: Arc<dyn ResponseSource>) {
+        self.sources.write().register(model, source);
+    }
+
+    /// Answer requests for all models without their own source with `source`
+    pub fn set_default_source(&self, source: Arc<dyn ResponseSource>) {
+        self.sources.write().set_global(source);
+    }
+
+    /// Fingerprint of the current configuration, reported as `system_fingerprint`
+    pub fn system_fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Generator of response IDs
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
+    }
+
+    /// Clock of response timestamps
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
     /// Get engine uptime
     pub fn uptime(&self) -> Duration {
         self.start_time.elapsed()
@@ -93,15 +183,29 @@ impl SimulationEngine {
     pub async fn chat_completion(
         &self,
         request: &ChatCompletionRequest,
+    ) -> SimulatorResult<ChatCompletionResponse> {
+        self.chat_completion_in_session(request, None).await
+    }
+
+    /// Generate a chat completion continuing session `session_id`, or the
+    /// request's `user` if none, when sessions are enabled
+    pub async fn chat_completion_in_session(
+        &self,
+        request: &ChatCompletionRequest,
+        session_id: Option<&str>,
     ) -> SimulatorResult<ChatCompletionResponse> {
         let start = Instant::now();
         self.state.increment_requests();
 
+        let session = self.session_key(request, session_id);
+        let (request, turns) = self.with_history(request, session.as_deref());
+        let request = request.as_ref();
+
+        // Content, latency and chaos all follow the request's seed
+        let seed = request_seed(self.config.read().seed, request.seed, &request.messages);
+
         // Check for chaos injection
-        if let Some(error) = self.chaos_engine.maybe_inject_error(&request.model, "/chat/completions") {
-            self.state.increment_errors();
-            return Err(error);
-        }
+        let protocol_fault = self.chat_chaos(&request.model, seed)?;
 
         // Validate model exists
         let model_config = self.get_model_config(&request.model)?;
@@ -112,8 +216,11 @@ impl SimulationEngine {
             param: None,
         })?;
 
+        self.validate_images(request, &model_config)?;
+
         // Check context length
-        let input_tokens = request.estimate_input_tokens();
+        let tokenizer = model_config.tokenizer();
+        let input_tokens = request.estimate_input_tokens_for(model_config.provider, tokenizer);
         if input_tokens > model_config.context_length {
             return Err(SimulationError::ContextLengthExceeded {
                 current: input_tokens,
@@ -122,27 +229,46 @@ impl SimulationEngine {
         }
 
         // Generate response
-        let id = format!("chatcmpl-{}", Uuid::new_v4().to_string().replace("-", "")[..24].to_string());
+        let id = self.ids.id("chatcmpl-", 24);
         let max_tokens = request.effective_max_tokens().min(model_config.max_output_tokens as u32);
 
-        let (content, output_tokens) = self.generator.generate_response(
-            &request.messages,
-            max_tokens,
-            &model_config.generation,
-        );
+        // Wait for a serving slot
+        let (slot, queued) = self.acquire_slot(&request.model).await?;
 
-        let usage = Usage::new(input_tokens as u32, output_tokens);
+        let (generated, _, output_tokens) = self.generate(request, &model_config, max_tokens).await?;
+        self.record_turn(session.as_deref(), turns, &generated);
 
-        // Apply latency
-        let profile = model_config.latency_profile.as_deref();
-        let ttft = self.latency_sim.sample_ttft(profile);
-        let overhead = self.latency_sim.overhead(profile);
+        let usage = Usage::new(input_tokens as u32, output_tokens);
 
-        tokio::time::sleep(ttft + overhead).await;
+        // Apply latency: the whole response is ready once the last token is
+        let (latency, profile) = self.latency_for(&request.model, &model_config, seed);
+        let mut schedule = latency.generate_schedule_for(input_tokens, output_tokens as usize, profile.as_deref());
+        // Only the token rate applies; the shared ceilings hold streams alone
+        self.pace_throughput(&request.model, &latency, &mut schedule);
+        // The instance stays busy until the response is ready
+        let instance = self.cold_start(&request.model, &latency, &mut schedule);
+        if let Some(slot) = slot {
+            schedule.scale_token_delays(slot.itl_factor());
+            slot.hold_for(self.latency_sim.pacer().scaled(schedule.total_duration()));
+        }
 
-        let response = ChatCompletionResponse::simple(id, request.model.clone(), content, usage);
+        let skipped = self.pace(schedule.total_duration()).await + self.latency_sim.pacer().skipped(queued);
+        drop(instance);
+        schedule.queued = queued;
+
+        let mut response = ChatCompletionResponse::simple(id, request.model.clone(), generated.content, usage)
+            .with_system_fingerprint(self.fingerprint.as_str());
+        response.created = self.clock.now_after(skipped).timestamp();
+        response.timing = Some(schedule.server_timing());
+        response.protocol_fault = protocol_fault;
+        response.choices[0].finish_reason = Some(generated.finish_reason);
+        if !generated.tool_calls.is_empty() {
+            let message = &mut response.choices[0].message;
+            message.content = message.content.take().filter(|c| !c.is_empty());
+            message.tool_calls = Some(generated.tool_calls);
+        }
 
-        self.state.record_latency(start.elapsed());
+        self.state.record_latency(start.elapsed() + skipped);
         self.state.add_tokens(input_tokens as u64, output_tokens as u64);
 
         Ok(response)
@@ -152,14 +278,28 @@ impl SimulationEngine {
     pub async fn chat_completion_stream(
         &self,
         request: &ChatCompletionRequest,
+    ) -> SimulatorResult<StreamingResponse> {
+        self.chat_completion_stream_in_session(request, None).await
+    }
+
+    /// Generate a streaming chat completion continuing session
+    /// `session_id`, or the request's `user` if none, when sessions are enabled
+    pub async fn chat_completion_stream_in_session(
+        &self,
+        request: &ChatCompletionRequest,
+        session_id: Option<&str>,
     ) -> SimulatorResult<StreamingResponse> {
         self.state.increment_requests();
 
+        let session = self.session_key(request, session_id);
+        let (request, turns) = self.with_history(request, session.as_deref());
+        let request = request.as_ref();
+
+        // Content, latency and chaos all follow the request's seed
+        let seed = request_seed(self.config.read().seed, request.seed, &request.messages);
+
         // Check for chaos injection
-        if let Some(error) = self.chaos_engine.maybe_inject_error(&request.model, "/chat/completions") {
-            self.state.increment_errors();
-            return Err(error);
-        }
+        let protocol_fault = self.chat_chaos(&request.model, seed)?;
 
         // Validate model exists and supports streaming
         let model_config = self.get_model_config(&request.model)?;
@@ -177,8 +317,11 @@ impl SimulationEngine {
             param: None,
         })?;
 
+        self.validate_images(request, &model_config)?;
+
         // Check context length
-        let input_tokens = request.estimate_input_tokens();
+        let tokenizer = model_config.tokenizer();
+        let input_tokens = request.estimate_input_tokens_for(model_config.provider, tokenizer);
         if input_tokens > model_config.context_length {
             return Err(SimulationError::ContextLengthExceeded {
                 current: input_tokens,
@@ -187,21 +330,29 @@ impl SimulationEngine {
         }
 
         // Generate response tokens
-        let id = format!("chatcmpl-{}", Uuid::new_v4().to_string().replace("-", "")[..24].to_string());
+        let id = self.ids.id("chatcmpl-", 24);
         let max_tokens = request.effective_max_tokens().min(model_config.max_output_tokens as u32);
 
-        let (content, output_tokens) = self.generator.generate_response(
-            &request.messages,
-            max_tokens,
-            &model_config.generation,
-        );
+        // Wait for a serving slot, held until the stream would finish
+        let (slot, queued) = self.acquire_slot(&request.model).await?;
 
-        // Tokenize for streaming
-        let tokens = self.generator.tokenize(&content);
+        let (generated, tokens, output_tokens) = self.generate(request, &model_config, max_tokens).await?;
+        self.record_turn(session.as_deref(), turns, &generated);
 
         // Generate latency schedule
-        let profile = model_config.latency_profile.as_deref();
-        let schedule = self.latency_sim.generate_schedule(tokens.len(), profile);
+        let (latency, profile) = self.latency_for(&request.model, &model_config, seed);
+        let mut schedule = latency.generate_schedule_for(input_tokens, tokens.len(), profile.as_deref());
+        let throttle = self.pace_throughput(&request.model, &latency, &mut schedule);
+        let instance = self.cold_start(&request.model, &latency, &mut schedule);
+        let pacer = self.latency_sim.pacer();
+        if let Some(slot) = slot {
+            schedule.scale_token_delays(slot.itl_factor());
+            slot.hold_for(pacer.scaled(schedule.total_duration()));
+        }
+        if let Some(instance) = instance {
+            instance.hold_for(pacer.scaled(schedule.total_duration()));
+        }
+        schedule.queued = queued;
 
         let usage = Usage::new(input_tokens as u32, output_tokens);
         self.state.add_tokens(input_tokens as u64, output_tokens as u64);
@@ -210,8 +361,16 @@ impl SimulationEngine {
             id,
             model: request.model.clone(),
             tokens,
+            tool_calls: generated.tool_calls,
             schedule,
+            pacer,
+            throttle,
+            fault: self.chaos_engine.maybe_stream_fault_seeded(&request.model, seed),
+            protocol_fault,
             usage,
+            finish_reason: generated.finish_reason,
+            system_fingerprint: self.fingerprint.clone(),
+            created: self.clock.now(),
         })
     }
 
@@ -220,8 +379,9 @@ impl SimulationEngine {
         let start = Instant::now();
         self.state.increment_requests();
 
-        // Check for chaos injection
-        if let Some(error) = self.chaos_engine.maybe_inject_error(&request.model, "/embeddings") {
+        // Check for chaos injection, seeded by the input
+        let seed = input_seed(self.config.read().seed, "/embeddings", request.input.to_vec());
+        if let Some(error) = self.chaos_engine.maybe_inject_error_seeded(&request.model, "/embeddings", seed) {
             self.state.increment_errors();
             return Err(error);
         }
@@ -236,40 +396,562 @@ impl SimulationEngine {
             });
         }
 
-        let inputs = request.input.to_vec();
-        let dimensions = request.dimensions
-            .map(|d| d as usize)
-            .or(model_config.embedding_dimensions)
-            .unwrap_or(1536);
+        let encoding = match request.encoding_format.as_deref() {
+            None | Some("float") => EmbeddingEncoding::Float,
+            Some("base64") => EmbeddingEncoding::Base64,
+            Some(other) => {
+                return Err(SimulationError::Validation {
+                    message: format!("encoding_format must be 'float' or 'base64', got '{}'", other),
+                    param: Some("encoding_format".to_string()),
+                });
+            }
+        };
+
+        // Vectors are generated at the native size and shortened Matryoshka-style
+        let native = model_config.embedding_dimensions.unwrap_or(1536);
+        let dimensions = request.dimensions.map(|d| d as usize).unwrap_or(native);
+        if dimensions == 0 || dimensions > native {
+            return Err(SimulationError::Validation {
+                message: format!("dimensions must be between 1 and {} for model {}", native, request.model),
+                param: Some("dimensions".to_string()),
+            });
+        }
 
+        let inputs = request.input.to_vec();
         let mut embeddings = Vec::with_capacity(inputs.len());
         let mut total_tokens = 0u32;
 
         for input in &inputs {
-            let embedding = self.generator.generate_embedding(dimensions, input);
-            let tokens = (input.len() / 4).max(1) as u32;
+            let embedding = match model_config.embedding_mode {
+                EmbeddingMode::Semantic => semantic_embedding(input, native),
+                EmbeddingMode::Hash => self.generator.generate_embedding(native, input),
+            };
+            let embedding = if dimensions < native {
+                truncate_embedding(embedding, dimensions)
+            } else {
+                embedding
+            };
+            let tokens = model_config.tokenizer().count(input).max(1) as u32;
             total_tokens += tokens;
             embeddings.push(embedding);
         }
 
         // Apply latency
-        let ttft = self.latency_sim.sample_ttft(None);
-        tokio::time::sleep(ttft).await;
+        let (latency, profile) = self.latency_for(&request.model, &model_config, None);
+        let ttft = latency.sample_ttft_for(profile.as_deref(), total_tokens as usize);
+        let skipped = self.pace(ttft).await;
 
-        let response = EmbeddingsResponse::new(request.model.clone(), embeddings, total_tokens);
+        let response = EmbeddingsResponse::new(request.model.clone(), embeddings, total_tokens)
+            .with_encoding(encoding);
 
-        self.state.record_latency(start.elapsed());
+        self.state.record_latency(start.elapsed() + skipped);
         self.state.add_tokens(total_tokens as u64, 0);
 
         Ok(response)
     }
 
+    /// Generate placeholder images
+    pub async fn image_generation(
+        &self,
+        request: &ImageGenerationRequest,
+    ) -> SimulatorResult<Vec<image::PlaceholderImage>> {
+        let start = Instant::now();
+        self.state.increment_requests();
+
+        // Check for chaos injection, seeded by the input
+        let seed = input_seed(self.config.read().seed, "/images/generations", [&request.prompt]);
+        if let Some(error) = self.chaos_engine.maybe_inject_error_seeded(&request.model, "/images/generations", seed) {
+            self.state.increment_errors();
+            return Err(error);
+        }
+
+        let model_config = self.get_model_config(&request.model)?;
+
+        request.validate().map_err(|e| SimulationError::Validation {
+            message: e,
+            param: None,
+        })?;
+
+        // Seed each image from the prompt so identical requests match
+        let (width, height) = request.dimensions().unwrap_or((1024, 1024));
+        let global_seed = self.config.read().seed;
+        let images = (0..request.count())
+            .map(|index| {
+                let seed = StableHasher::new()
+                    .opt_u64(global_seed)
+                    .str(&request.model)
+                    .str(&request.prompt)
+                    .u64(index as u64)
+                    .finish();
+                image::PlaceholderImage::new(seed, width, height)
+            })
+            .collect();
+
+        // Apply latency
+        let (latency, profile) = self.latency_for(&request.model, &model_config, None);
+        let ttft = latency.sample_ttft(profile.as_deref());
+        let overhead = latency.overhead(profile.as_deref());
+
+        let skipped = self.pace(ttft + overhead).await;
+
+        self.state.record_latency(start.elapsed() + skipped);
+
+        Ok(images)
+    }
+
+    /// Synthesize speech, encoded in the requested format
+    pub async fn speech(&self, request: &SpeechRequest) -> SimulatorResult<Vec<u8>> {
+        let start = Instant::now();
+        self.state.increment_requests();
+
+        // Check for chaos injection, seeded by the input
+        let seed = input_seed(self.config.read().seed, "/audio/speech", [&request.input]);
+        if let Some(error) = self.chaos_engine.maybe_inject_error_seeded(&request.model, "/audio/speech", seed) {
+            self.state.increment_errors();
+            return Err(error);
+        }
+
+        let model_config = self.get_model_config(&request.model)?;
+
+        request.validate().map_err(|e| SimulationError::Validation {
+            message: e,
+            param: None,
+        })?;
+
+        let speed = request.speed.unwrap_or(1.0);
+        let bytes = match request.format() {
+            "mp3" => audio::encode_silent_mp3(audio::speech_duration(&request.input, speed)),
+            "wav" => audio::encode_wav(
+                &audio::synthesize_speech(&request.input, speed),
+                audio::PCM16_SAMPLE_RATE,
+            ),
+            _ => audio::synthesize_speech(&request.input, speed),
+        };
+
+        // Apply latency
+        let (latency, profile) = self.latency_for(&request.model, &model_config, None);
+        let ttft = latency.sample_ttft(profile.as_deref());
+        let overhead = latency.overhead(profile.as_deref());
+
+        let skipped = self.pace(ttft + overhead).await;
+
+        self.state.record_latency(start.elapsed() + skipped);
+        self.state.add_tokens(model_config.tokenizer().count(&request.input) as u64, 0);
+
+        Ok(bytes)
+    }
+
+    /// Transcribe an audio file
+    pub async fn transcription(
+        &self,
+        request: &TranscriptionRequest,
+    ) -> SimulatorResult<VerboseTranscriptionResponse> {
+        let start = Instant::now();
+        self.state.increment_requests();
+
+        // Check for chaos injection, seeded by the input
+        let seed = input_seed(self.config.read().seed, "/audio/transcriptions", [&request.file]);
+        if let Some(error) = self.chaos_engine.maybe_inject_error_seeded(&request.model, "/audio/transcriptions", seed) {
+            self.state.increment_errors();
+            return Err(error);
+        }
+
+        let model_config = self.get_model_config(&request.model)?;
+
+        request.validate().map_err(|e| SimulationError::Validation {
+            message: e,
+            param: None,
+        })?;
+
+        // Fixture transcript if configured, otherwise ~2.5 words per second
+        let duration = audio::estimate_audio_duration(&request.file).as_secs_f64();
+        let text = model_config.default_response.clone().unwrap_or_else(|| {
+            self.generator.generate_transcript((duration * 2.5).round() as usize, &request.file)
+        });
+
+        let include_words = request.timestamp_granularities.iter().any(|g| g == "word");
+        let mut response = transcript_with_timings(&text, duration, include_words);
+        if let Some(language) = &request.language {
+            response.language = language.clone();
+        }
+
+        // Apply latency
+        let (latency, profile) = self.latency_for(&request.model, &model_config, None);
+        let ttft = latency.sample_ttft(profile.as_deref());
+        let overhead = latency.overhead(profile.as_deref());
+
+        let skipped = self.pace(ttft + overhead).await;
+
+        self.state.record_latency(start.elapsed() + skipped);
+        self.state.add_tokens(0, model_config.tokenizer().count(&text) as u64);
+
+        Ok(response)
+    }
+
+    /// Classify inputs against the moderation rules
+    pub async fn moderations(&self, request: &ModerationRequest) -> SimulatorResult<ModerationResponse> {
+        let start = Instant::now();
+        self.state.increment_requests();
+
+        // Check for chaos injection, seeded by the input
+        let seed = input_seed(self.config.read().seed, "/moderations", request.input.to_vec());
+        if let Some(error) = self.chaos_engine.maybe_inject_error_seeded(&request.model, "/moderations", seed) {
+            self.state.increment_errors();
+            return Err(error);
+        }
+
+        let model_config = self.get_model_config(&request.model)?;
+
+        let inputs = request.input.to_vec();
+        let results = inputs.iter()
+            .map(|input| self.moderator.moderate(input))
+            .collect();
+
+        // Apply latency
+        let (latency, profile) = self.latency_for(&request.model, &model_config, None);
+        let ttft = latency.sample_ttft(profile.as_deref());
+        let skipped = self.pace(ttft).await;
+
+        let tokenizer = model_config.tokenizer();
+        let input_tokens: usize = inputs.iter().map(|i| tokenizer.count(i).max(1)).sum();
+        self.state.record_latency(start.elapsed() + skipped);
+        self.state.add_tokens(input_tokens as u64, 0);
+
+        Ok(ModerationResponse {
+            id: self.ids.id("modr-", 32),
+            model: request.model.clone(),
+            results,
+        })
+    }
+
+    /// Sleep out a simulated `delay`, returning the part skipped under virtual time
+    async fn pace(&self, delay: Duration) -> Duration {
+        let pacer = self.latency_sim.pacer();
+        pacer.sleep(delay).await;
+        pacer.skipped(delay)
+    }
+
+    /// Reject image input for non-vision models and undecodable inline images
+    fn validate_images(&self, request: &ChatCompletionRequest, model_config: &ModelConfig) -> SimulatorResult<()> {
+        for (i, message) in request.messages.iter().enumerate() {
+            let MessageContent::Parts(parts) = &message.content else {
+                continue;
+            };
+            for (j, part) in parts.iter().enumerate() {
+                let ContentPart::ImageUrl { image_url } = part else {
+                    continue;
+                };
+
+                if !model_config.supports_vision {
+                    let message = match model_config.provider {
+                        Provider::Anthropic => format!(
+                            "messages.{}.content.{}: image input is not supported by {}",
+                            i, j, request.model
+                        ),
+                        Provider::Google => format!(
+                            "Image input modality is not enabled for models/{}",
+                            request.model
+                        ),
+                        _ => "Invalid content type. image_url is only supported by certain models.".to_string(),
+                    };
+                    return Err(SimulationError::Validation {
+                        message,
+                        param: Some(format!("messages.[{}].content.[{}].type", i, j)),
+                    });
+                }
+
+                if let Err(e) = image_url.dimensions() {
+                    return Err(SimulationError::Validation {
+                        message: format!(
+                            "Invalid image: {}. Supported formats are png, jpeg, gif and webp.",
+                            e
+                        ),
+                        param: Some(format!("messages.[{}].content.[{}].image_url", i, j)),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Produce a response, its content split into tokens, and its output
+    /// token count.
+    ///
+    /// Refused requests are empty; otherwise a registered source answers,
+    /// falling back to the built-in generator.
+    async fn generate(
+        &self,
+        request: &ChatCompletionRequest,
+        model_config: &ModelConfig,
+        max_tokens: u32,
+    ) -> SimulatorResult<(SourceResponse, Vec<String>, u32)> {
+        let tokenizer = model_config.tokenizer();
+
+        if self.is_refused(request) {
+            let refusal = SourceResponse::text("").with_finish_reason(FinishReason::ContentFilter);
+            return Ok((refusal, Vec::new(), 0));
+        }
+
+        // Release the lock before awaiting the source
+        let source = self.sources.read().get(&request.model);
+        let Some(source) = source else {
+            let tokens = self.generator.generate_tokens_for(
+                &request.messages,
+                max_tokens,
+                &model_config.generation,
+                tokenizer,
+                &SamplingParams::from_request(request),
+            );
+            let output_tokens = tokens.len() as u32;
+            return Ok((SourceResponse::text(tokens.concat()), tokens, output_tokens));
+        };
+
+        let mut response = source.respond(request, model_config).await?;
+        // Seeded runs replace source-chosen ids too, so they repeat
+        for call in &mut response.tool_calls {
+            if call.id.is_empty() || self.ids.is_deterministic() {
+                call.id = self.ids.id("call_", 24);
+            }
+        }
+        let mut tokens = tokenizer.split(&response.content);
+        if tokens.len() > max_tokens as usize {
+            tokens.truncate(max_tokens as usize);
+            response.content = tokens.concat();
+            response.finish_reason = FinishReason::Length;
+        }
+
+        let output_tokens = tokens.len()
+            + response.tool_calls.iter()
+                .map(|call| tokenizer.count(&call.function.name) + tokenizer.count(&call.function.arguments))
+                .sum::<usize>();
+
+        Ok((response, tokens, output_tokens as u32))
+    }
+
+    /// Up-front chaos for a chat request to `model`: an error to fail with, or
+    /// a protocol fault to break the response with
+    fn chat_chaos(&self, model: &str, seed: Option<u64>) -> SimulatorResult<Option<ProtocolFault>> {
+        let Some(error) = self.chaos_engine.maybe_inject_error_seeded(model, "/chat/completions", seed) else {
+            return Ok(None);
+        };
+        self.state.increment_errors();
+        match error {
+            SimulationError::Injected { error_type, message, status_code } if error_type.is_protocol_fault() => {
+                Ok(Some(ProtocolFault { fault_type: error_type, status_code, message }))
+            }
+            error => Err(error),
+        }
+    }
+
+    /// Take a serving slot of a model with limited capacity, with the
+    /// simulated time spent queueing for it
+    async fn acquire_slot(&self, model: &str) -> SimulatorResult<(Option<Slot>, Duration)> {
+        let Some(pool) = self.capacity.get(model) else {
+            return Ok((None, Duration::ZERO));
+        };
+
+        let queued_at = self.clock.wall();
+        match pool.acquire().await {
+            Ok(slot) => {
+                let waited = (self.clock.wall() - queued_at).to_std().unwrap_or_default();
+                Ok((Some(slot), self.latency_sim.pacer().unscaled(waited)))
+            }
+            Err(error) => {
+                self.state.increment_errors();
+                Err(error)
+            }
+        }
+    }
+
+    /// Latency simulator and profile of a request to `model`, following the
+    /// request's seed and the latency timeline phase in effect
+    fn latency_for(
+        &self,
+        model: &str,
+        model_config: &ModelConfig,
+        seed: Option<u64>,
+    ) -> (Cow<'_, LatencySimulator>, Option<String>) {
+        let config = self.config.read();
+        let active = config.latency.timeline.active_phase(self.uptime(), self.clock.wall());
+        self.log_phase_change(active);
+        let phase = active.map(|(_, phase)| phase);
+
+        let profile = phase.and_then(|p| p.profile_for(model))
+            .or(model_config.latency_profile.as_deref())
+            .map(str::to_string);
+        let factor = phase.map_or(1.0, |p| p.multiplier);
+        (self.latency_sim.for_request(seed, factor), profile)
+    }
+
+    /// Log the latency timeline phase taking or leaving effect, as first seen
+    /// by a request
+    fn log_phase_change(&self, active: Option<(usize, &TimelinePhase)>) {
+        let index = active.map(|(i, _)| i);
+        let mut current = self.timeline_phase.lock();
+        if *current == index {
+            return;
+        }
+        *current = index;
+
+        match active {
+            Some((i, phase)) => tracing::info!(
+                phase = %phase.name.clone().unwrap_or_else(|| format!("#{}", i)),
+                multiplier = phase.multiplier,
+                "Latency timeline phase started"
+            ),
+            None => tracing::info!("Latency timeline phase ended"),
+        }
+    }
+
+    /// Admit a request to its model's warm pool, delaying the first token of
+    /// a cold request by the cold-start penalty
+    fn cold_start(
+        &self,
+        model: &str,
+        latency: &LatencySimulator,
+        schedule: &mut LatencySchedule,
+    ) -> Option<Admission> {
+        if !latency.is_enabled() {
+            return None;
+        }
+        let admission = self.warm_pools.get(model)?.admit();
+        if admission.is_cold() {
+            schedule.ttft += latency.sample_delay(admission.penalty());
+            self.state.increment_cold_starts();
+        }
+        Some(admission)
+    }
+
+    /// Space the tokens of a request to a model with a token rate at that
+    /// rate, returning the shared ceilings a stream of them is held to
+    fn pace_throughput(
+        &self,
+        model: &str,
+        latency: &LatencySimulator,
+        schedule: &mut LatencySchedule,
+    ) -> Throttle {
+        match self.throughput.get(model) {
+            Some(pool) if latency.is_enabled() => {
+                schedule.set_token_interval(pool.config().token_interval().mul_f64(latency.multiplier()));
+                pool.throttle(None)
+            }
+            _ => Throttle::default(),
+        }
+    }
+
+    /// Instances of every model with cold starts configured
+    pub fn warm_pool_status(&self) -> HashMap<String, WarmPoolStatus> {
+        self.warm_pools.iter().map(|(model, pool)| (model.clone(), pool.status())).collect()
+    }
+
+    /// Load of every model with limited capacity, summed; None if all are unlimited
+    pub fn capacity_status(&self) -> Option<CapacityStatus> {
+        if self.capacity.is_empty() {
+            return None;
+        }
+
+        Some(self.capacity.values()
+            .map(|pool| pool.status())
+            .fold(CapacityStatus::default(), |total, s| CapacityStatus {
+                slots: total.slots + s.slots,
+                active: total.active + s.active,
+                queued: total.queued + s.queued,
+                max_queue: total.max_queue + s.max_queue,
+            }))
+    }
+
+    /// Session a chat request continues, if sessions are enabled
+    fn session_key(&self, request: &ChatCompletionRequest, session_id: Option<&str>) -> Option<String> {
+        if !self.config.read().sessions.enabled {
+            return None;
+        }
+        session_id
+            .or(request.user.as_deref())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+    }
+
+    /// The request with the session's earlier turns prepended, and the new
+    /// turns it adds to the session. Clients resending the whole
+    /// conversation are recognised and not duplicated.
+    fn with_history<'a>(
+        &self,
+        request: &'a ChatCompletionRequest,
+        session: Option<&str>,
+    ) -> (Cow<'a, ChatCompletionRequest>, Vec<Message>) {
+        let Some(session) = session else {
+            return (Cow::Borrowed(request), Vec::new());
+        };
+
+        let ttl = self.config.read().sessions.ttl();
+        let history = self.sessions.history(session, ttl);
+        let resent = history.len() <= request.messages.len()
+            && history.iter().zip(&request.messages)
+                .all(|(a, b)| a.role == b.role && a.text() == b.text());
+
+        if resent {
+            let turns = request.messages[history.len()..].to_vec();
+            return (Cow::Borrowed(request), turns);
+        }
+
+        let turns = request.messages.clone();
+        let mut messages = history;
+        messages.extend(turns.iter().cloned());
+        let request = ChatCompletionRequest { messages, ..request.clone() };
+        (Cow::Owned(request), turns)
+    }
+
+    /// Append a request's turns and the reply to its session
+    fn record_turn(&self, session: Option<&str>, mut turns: Vec<Message>, reply: &SourceResponse) {
+        let Some(session) = session else {
+            return;
+        };
+
+        turns.push(Message::assistant(reply.content.clone()));
+        let config = self.config.read();
+        self.sessions.record(session, turns, config.sessions.ttl(), config.sessions.max_sessions);
+    }
+
+    /// Live sessions, without their messages
+    pub fn sessions(&self) -> Vec<SessionInfo> {
+        self.sessions.list(self.config.read().sessions.ttl())
+    }
+
+    /// A live session with its messages
+    pub fn session(&self, session_id: &str) -> SimulatorResult<SessionInfo> {
+        self.sessions.get(session_id, self.config.read().sessions.ttl())
+            .ok_or_else(|| SimulationError::SessionNotFound(session_id.to_string()))
+    }
+
+    /// Forget a session
+    pub fn delete_session(&self, session_id: &str) -> SimulatorResult<()> {
+        if self.sessions.remove(session_id) {
+            Ok(())
+        } else {
+            Err(SimulationError::SessionNotFound(session_id.to_string()))
+        }
+    }
+
+    /// Whether a chat request is refused by the moderation rules
+    fn is_refused(&self, request: &ChatCompletionRequest) -> bool {
+        if !self.moderator.filters_completions() {
+            return false;
+        }
+        let input = request.messages.iter()
+            .map(|m| m.content.text())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.moderator.is_flagged(&input)
+    }
+
     /// List available models
     pub fn list_models(&self) -> ModelsResponse {
         let config = self.config.read();
-        let models: Vec<ModelObject> = config.models.iter()
-            .map(|(id, mc)| ModelObject::new(id, mc.provider.to_string()))
+        let created = self.clock.timestamp();
+        let mut models: Vec<ModelObject> = config.models.iter()
+            .map(|(id, mc)| ModelObject { created, ..ModelObject::new(id, mc.provider.to_string()) })
             .collect();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
         ModelsResponse::new(models)
     }
 
@@ -277,15 +959,23 @@ impl SimulationEngine {
     pub fn get_model(&self, model_id: &str) -> Option<ModelObject> {
         let config = self.config.read();
         config.models.get(model_id)
-            .map(|mc| ModelObject::new(model_id, mc.provider.to_string()))
+            .map(|mc| ModelObject {
+                created: self.clock.timestamp(),
+                ..ModelObject::new(model_id, mc.provider.to_string())
+            })
     }
 
     /// Get model configuration
     fn get_model_config(&self, model_id: &str) -> SimulatorResult<ModelConfig> {
         let config = self.config.read();
-        config.models.get(model_id)
+        let mut model = config.models.get(model_id)
             .cloned()
-            .ok_or_else(|| SimulationError::ModelNotFound(model_id.to_string()))
+            .ok_or_else(|| SimulationError::ModelNotFound(model_id.to_string()))?;
+
+        if model.generation.corpus.is_none() {
+            model.generation.corpus = config.corpus.clone();
+        }
+        Ok(model)
     }
 
     /// Get engine statistics
@@ -326,11 +1016,64 @@ pub struct StreamingResponse {
     pub id: String,
     pub model: String,
     pub tokens: Vec<String>,
+    pub tool_calls: Vec<ResponseToolCall>,
     pub schedule: LatencySchedule,
+    /// Paces the schedule's delays, compressed under virtual time
+    pub pacer: Pacer,
+    /// Shared throughput ceilings the tokens are held to
+    pub throttle: Throttle,
+    /// Fault the stream meets mid-flight, if any
+    pub fault: Option<StreamFault>,
+    /// Protocol fault the stream is broken by when sent
+    pub protocol_fault: Option<ProtocolFault>,
     pub usage: Usage,
+    pub finish_reason: FinishReason,
+    pub system_fingerprint: String,
+    pub created: DateTime<Utc>,
 }
 
 impl StreamingResponse {
+    /// Also hold the tokens to the per-key ceiling of API key `key`
+    pub fn for_api_key(mut self, key: &str) -> Self {
+        self.throttle = self.throttle.with_key(key);
+        self
+    }
+
+    /// Stream chunks as they are due, then the end of the stream; token
+    /// chunks are held to the throughput ceilings, and a stream fault
+    /// reshapes what arrives
+    pub fn paced_events(self) -> impl Stream<Item = StreamEvent> + Send {
+        let (pacer, throttle, fault) = (self.pacer, self.throttle.clone(), self.fault.clone());
+        let tokens = 1..=self.tokens.len();
+        let mut steps: Vec<StreamStep> = self.into_chunks().into_iter()
+            .enumerate()
+            .map(|(i, (delay, chunk))| StreamStep {
+                delay,
+                throttled: tokens.contains(&i),
+                ..StreamStep::now(StreamEvent::Chunk(chunk))
+            })
+            .collect();
+        steps.push(StreamStep::now(StreamEvent::Done));
+        if let Some(fault) = &fault {
+            apply_fault(&mut steps, fault);
+        }
+
+        stream::iter(steps).then(move |step| {
+            let throttle = step.throttled.then(|| throttle.clone());
+            async move {
+                match throttle {
+                    Some(throttle) => throttle.sleep(pacer, step.delay).await,
+                    None => pacer.sleep(step.delay).await,
+                }
+                // Stalls are meant to outlast real clients' timeouts
+                if !step.stall.is_zero() {
+                    tokio::time::sleep(step.stall).await;
+                }
+                step.event
+            }
+        })
+    }
+
     /// Convert to SSE stream chunks
     pub fn into_chunks(self) -> Vec<(Duration, ChatCompletionChunk)> {
         let mut chunks = Vec::with_capacity(self.tokens.len() + 2);
@@ -355,19 +1098,217 @@ impl StreamingResponse {
             chunks.push((delay, chunk));
         }
 
+        // Tool calls arrive whole, one chunk each
+        for (i, call) in self.tool_calls.iter().enumerate() {
+            let chunk = ChatCompletionChunk::new(
+                self.id.clone(),
+                self.model.clone(),
+                vec![ChunkChoice::tool_call_delta(i as u32, call, 0)],
+            );
+            chunks.push((Duration::ZERO, chunk));
+        }
+
         // Final chunk with finish reason
         let final_chunk = ChatCompletionChunk::finish(
             self.id.clone(),
             self.model.clone(),
-            FinishReason::Stop,
+            self.finish_reason,
             0,
         ).with_usage(self.usage);
         chunks.push((Duration::ZERO, final_chunk));
 
+        for (_, chunk) in &mut chunks {
+            chunk.created = self.created.timestamp();
+            chunk.system_fingerprint = Some(self.system_fingerprint.clone());
+        }
+
         chunks
     }
 }
 
+/// What a paced stream delivers
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    Chunk(ChatCompletionChunk),
+    /// An error reported in-band, ending the stream
+    Error(String),
+    /// The connection dropping mid-stream
+    Disconnect,
+    /// The end of the stream
+    Done,
+}
+
+/// One event of a paced stream and how long it waits
+struct StreamStep {
+    delay: Duration,
+    /// Whether the delay is held to the throughput ceilings
+    throttled: bool,
+    /// Real time the stream goes quiet before the event
+    stall: Duration,
+    event: StreamEvent,
+}
+
+impl StreamStep {
+    /// Step delivering `event` straight after the previous one
+    fn now(event: StreamEvent) -> Self {
+        Self { delay: Duration::ZERO, throttled: false, stall: Duration::ZERO, event }
+    }
+}
+
+/// Reshape the steps of a stream as `fault` dictates
+fn apply_fault(steps: &mut Vec<StreamStep>, fault: &StreamFault) {
+    // The step after the first `after_tokens` tokens, which follow the role
+    // chunk; at most the end of the stream
+    let at = (fault.after_tokens + 1).min(steps.len() - 1);
+    match fault.fault_type {
+        StreamFaultType::Disconnect => {
+            steps.truncate(at);
+            steps.push(StreamStep::now(StreamEvent::Disconnect));
+        }
+        StreamFaultType::ErrorEvent => {
+            steps.truncate(at);
+            steps.push(StreamStep::now(StreamEvent::Error(fault.message.clone())));
+        }
+        StreamFaultType::Stall => steps[at].stall = fault.stall,
+        StreamFaultType::MissingDone => {
+            steps.pop();
+        }
+        StreamFaultType::DuplicateChunk => {
+            let at = at.min(steps.len() - 2);
+            let event = steps[at].event.clone();
+            steps.insert(at + 1, StreamStep::now(event));
+        }
+        StreamFaultType::OutOfOrder => {
+            let at = at.min(steps.len() - 3);
+            let (first, rest) = steps.split_at_mut(at + 1);
+            std::mem::swap(&mut first[at].event, &mut rest[0].event);
+            // The chunk sent early claims the next index while the current
+            // one is still open
+            if let StreamEvent::Chunk(chunk) = &mut steps[at].event {
+                for choice in &mut chunk.choices {
+                    choice.index += 1;
+                    for call in choice.delta.tool_calls.iter_mut().flatten() {
+                        call.index += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Capacity pools of the models that limit concurrency
+fn capacity_pools(config: &SimulatorConfig) -> HashMap<String, Arc<CapacityPool>> {
+    config.models.iter()
+        .filter_map(|(id, model)| {
+            let capacity = model.capacity.clone()?;
+            Some((id.clone(), Arc::new(CapacityPool::new(capacity, model.provider))))
+        })
+        .collect()
+}
+
+/// Warm pools of the models with cold starts configured
+fn warm_pools(config: &SimulatorConfig) -> HashMap<String, Arc<WarmPool>> {
+    config.latency.cold_start.iter()
+        .map(|(model, cold_start)| (model.clone(), Arc::new(WarmPool::new(cold_start.clone()))))
+        .collect()
+}
+
+/// Throughput pools of the models with a token rate
+fn throughput_pools(config: &SimulatorConfig) -> HashMap<String, Arc<ThroughputPool>> {
+    config.models.iter()
+        .filter_map(|(id, model)| {
+            let throughput = model.throughput.clone()?;
+            Some((id.clone(), Arc::new(ThroughputPool::new(throughput))))
+        })
+        .collect()
+}
+
+/// Seeded IDs when deterministic IDs are configured
+fn id_generator(config: &SimulatorConfig) -> IdGenerator {
+    if config.deterministic.ids {
+        IdGenerator::seeded(config.seed.unwrap_or_default())
+    } else {
+        IdGenerator::random()
+    }
+}
+
+/// Fingerprint of a configuration: stable across restarts, changing with
+/// any setting that affects responses
+fn system_fingerprint(config: &SimulatorConfig) -> String {
+    // Values serialize maps with sorted keys, so the text is canonical
+    let canonical = serde_json::to_value(config)
+        .map(|v| v.to_string())
+        .unwrap_or_default();
+    let hash = StableHasher::new()
+        .str(env!("CARGO_PKG_VERSION"))
+        .str(&canonical)
+        .finish();
+    format!("fp_{:010x}", hash >> 24)
+}
+
+/// Train the corpora of every model using the Markov strategy.
+///
+/// Unreadable corpora are logged and those models fall back to templates.
+fn train_corpora(generator: &mut ResponseGenerator, config: &SimulatorConfig) {
+    for model in config.models.values() {
+        if !matches!(model.generation.strategy, GenerationStrategy::Markov) {
+            continue;
+        }
+        if let Some(corpus) = model.generation.corpus.as_ref().or(config.corpus.as_ref()) {
+            if let Err(e) = generator.train_corpus(corpus) {
+                tracing::warn!(model = %model.id, "{}", e);
+            }
+        }
+    }
+}
+
+/// Split a transcript into sentence segments timed evenly across the audio
+fn transcript_with_timings(text: &str, duration: f64, include_words: bool) -> VerboseTranscriptionResponse {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let seconds_per_word = duration / words.len().max(1) as f64;
+
+    let mut segments = Vec::new();
+    let mut timed_words = Vec::new();
+    let mut sentence: Vec<&str> = Vec::new();
+    let mut sentence_start = 0usize;
+
+    for (index, word) in words.iter().enumerate() {
+        timed_words.push(TranscriptionWord {
+            word: word.trim_matches(|c: char| c.is_ascii_punctuation()).to_string(),
+            start: index as f64 * seconds_per_word,
+            end: (index + 1) as f64 * seconds_per_word,
+        });
+
+        sentence.push(word);
+        let is_last = index + 1 == words.len();
+        if word.ends_with(['.', '!', '?']) || is_last {
+            segments.push(TranscriptionSegment {
+                id: segments.len() as u32,
+                seek: 0,
+                start: sentence_start as f64 * seconds_per_word,
+                end: (index + 1) as f64 * seconds_per_word,
+                text: format!(" {}", sentence.join(" ")),
+                tokens: Vec::new(),
+                temperature: 0.0,
+                avg_logprob: -0.25,
+                compression_ratio: 1.2,
+                no_speech_prob: 0.01,
+            });
+            sentence.clear();
+            sentence_start = index + 1;
+        }
+    }
+
+    VerboseTranscriptionResponse {
+        task: "transcribe".to_string(),
+        language: "english".to_string(),
+        duration,
+        text: text.to_string(),
+        segments,
+        words: include_words.then_some(timed_words),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,6 +1334,135 @@ mod tests {
         assert!(!response.choices.is_empty());
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_non_streaming_latency_includes_prefill_and_tokens() {
+        let mut config = SimulatorConfig::default();
+        config.latency.profiles.insert("test".to_string(), crate::config::LatencyProfile {
+            ttft: crate::config::LatencyDistribution::fixed(0.0),
+            itl: crate::config::LatencyDistribution::fixed(10.0),
+            overhead: Duration::ZERO,
+            prefill: Some(crate::config::PrefillConfig::linear(1000.0)),
+            ..Default::default()
+        });
+        config.models.get_mut("gpt-4").unwrap().latency_profile = Some("test".to_string());
+        let engine = SimulationEngine::new(config);
+
+        let timed = |prompt: String| {
+            let engine = &engine;
+            async move {
+                let mut request = ChatCompletionRequest::new("gpt-4", vec![Message::user(prompt)]);
+                request.max_tokens = Some(10);
+                let start = Instant::now();
+                let response = engine.chat_completion(&request).await.unwrap();
+                (start.elapsed(), response.usage.unwrap())
+            }
+        };
+
+        let (short, usage) = timed("Hi".to_string()).await;
+        let itl_total = Duration::from_millis(10 * usage.completion_tokens as u64);
+        assert!(short >= itl_total);
+
+        let (long, usage) = timed("word ".repeat(300)).await;
+        let prefill = Duration::from_millis(usage.prompt_tokens as u64);
+        assert!(usage.prompt_tokens >= 300);
+        assert!(long >= prefill + Duration::from_millis(10 * usage.completion_tokens as u64));
+        assert!(long > short);
+    }
+
+    /// Config answering gpt-4 after a fixed five seconds
+    fn five_second_config() -> SimulatorConfig {
+        let mut config = SimulatorConfig::default();
+        config.latency.profiles.insert("five-seconds".to_string(), crate::config::LatencyProfile {
+            ttft: crate::config::LatencyDistribution::fixed(5000.0),
+            itl: crate::config::LatencyDistribution::fixed(0.0),
+            ..Default::default()
+        });
+        config.models.get_mut("gpt-4").unwrap().latency_profile = Some("five-seconds".to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn test_virtual_time_reports_unslept_latency() {
+        let mut config = five_second_config();
+        config.latency.virtual_time.enabled = true;
+        let engine = SimulationEngine::new(config);
+
+        let start = std::time::Instant::now();
+        let before = engine.clock().timestamp();
+        let request = ChatCompletionRequest::new("gpt-4", vec![Message::user("Hello!")]);
+        let response = engine.chat_completion(&request).await.unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(response.timing.unwrap().total, Duration::from_secs(5));
+        assert!(response.created >= before + 5);
+        assert!(engine.stats().latency.mean_ms >= 5000.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_paused_tokio_time_drives_latency_and_clock() {
+        let engine = SimulationEngine::new(five_second_config());
+
+        let start = Instant::now();
+        let before = engine.clock().timestamp();
+        let request = ChatCompletionRequest::new("gpt-4", vec![Message::user("Hello!")]);
+        let response = engine.chat_completion(&request).await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_secs(5));
+        assert!(response.created >= before + 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeline_degrades_then_recovers() {
+        use crate::config::{LatencyDistribution, LatencyProfile, TimelinePhase};
+
+        let mut config = SimulatorConfig::default();
+        let fixed = |ttft_ms: f64| LatencyProfile {
+            ttft: LatencyDistribution::fixed(ttft_ms),
+            itl: LatencyDistribution::fixed(0.0),
+            ..Default::default()
+        };
+        config.latency.profiles.insert("steady".to_string(), fixed(100.0));
+        config.latency.profiles.insert("spiky".to_string(), fixed(1000.0));
+        config.models.get_mut("gpt-4").unwrap().latency_profile = Some("steady".to_string());
+        config.latency.timeline.phases = vec![
+            TimelinePhase { multiplier: 4.0, ..TimelinePhase::between(60.0, 120.0) },
+            TimelinePhase {
+                models: [("gpt-4".to_string(), "spiky".to_string())].into(),
+                ..TimelinePhase::between(120.0, 180.0)
+            },
+        ];
+        let engine = SimulationEngine::new(config);
+        let request = ChatCompletionRequest::new("gpt-4", vec![Message::user("Hello!")]);
+
+        let mut ttfts = Vec::new();
+        for _ in 0..4 {
+            ttfts.push(engine.chat_completion(&request).await.unwrap().timing.unwrap().ttft);
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+        assert_eq!(ttfts, [100, 400, 1000, 100].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cold_start_after_startup_and_idle() {
+        let mut config = SimulatorConfig::default();
+        config.latency.cold_start.insert("gpt-4".to_string(), crate::config::ColdStartConfig {
+            penalty: crate::config::LatencyDistribution::fixed(3000.0),
+            idle_timeout_secs: Some(60.0),
+            ..Default::default()
+        });
+        let engine = SimulationEngine::new(config);
+        let request = ChatCompletionRequest::new("gpt-4", vec![Message::user("Hello!")]);
+
+        let ttft = |response: ChatCompletionResponse| response.timing.unwrap().ttft;
+        assert!(ttft(engine.chat_completion(&request).await.unwrap()) >= Duration::from_secs(3));
+        assert!(ttft(engine.chat_completion(&request).await.unwrap()) < Duration::from_secs(3));
+
+        tokio::time::sleep(Duration::from_secs(120)).await;
+        assert!(ttft(engine.chat_completion(&request).await.unwrap()) >= Duration::from_secs(3));
+        assert_eq!(engine.stats().cold_starts, 2);
+        assert_eq!(engine.warm_pool_status()["gpt-4"].instances, 1);
+    }
+
     #[tokio::test]
     async fn test_model_not_found() {
         let engine = SimulationEngine::default_config();
@@ -447,6 +1517,61 @@ mod tests {
         assert!(chunks.len() >= 2); // At least role + finish
     }
 
+    /// Tokens of a seeded stream and the events it delivers meeting a fault
+    /// after two tokens
+    async fn faulted_events(fault_type: StreamFaultType) -> (Vec<String>, Vec<String>) {
+        let engine = SimulationEngine::default_config();
+        let request = ChatCompletionRequest {
+            seed: Some(7),
+            ..ChatCompletionRequest::new("gpt-4", vec![Message::user("Hello!")])
+        };
+        let mut stream = engine.chat_completion_stream(&request).await.unwrap();
+        stream.schedule = LatencySchedule::instant(stream.tokens.len());
+        stream.fault = Some(StreamFault {
+            fault_type,
+            after_tokens: 2,
+            stall: Duration::from_secs(60),
+            message: "boom".to_string(),
+        });
+
+        let tokens = stream.tokens.clone();
+        let events = stream.paced_events().map(|event| match event {
+            StreamEvent::Chunk(chunk) => match &chunk.choices[0] {
+                c if c.finish_reason.is_some() => "finish".to_string(),
+                c if c.index > 0 => format!("{}@{}", c.delta.content.as_deref().unwrap_or_default(), c.index),
+                c => c.delta.content.clone().unwrap_or_else(|| "role".to_string()),
+            },
+            StreamEvent::Error(message) => format!("error: {message}"),
+            StreamEvent::Disconnect => "disconnect".to_string(),
+            StreamEvent::Done => "done".to_string(),
+        }).collect().await;
+        (tokens, events)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stream_faults_reshape_events() {
+        let (t, events) = faulted_events(StreamFaultType::Disconnect).await;
+        assert_eq!(events, ["role", &t[0], &t[1], "disconnect"]);
+
+        let (t, events) = faulted_events(StreamFaultType::ErrorEvent).await;
+        assert_eq!(events, ["role", &t[0], &t[1], "error: boom"]);
+
+        let (t, events) = faulted_events(StreamFaultType::DuplicateChunk).await;
+        assert_eq!(events[..5], ["role", &t[0], &t[1], &t[2], &t[2]]);
+
+        let (t, events) = faulted_events(StreamFaultType::OutOfOrder).await;
+        assert_eq!(events[..5], ["role", &t[0], &t[1], &format!("{}@1", t[3]), &t[2]]);
+
+        let (_, events) = faulted_events(StreamFaultType::MissingDone).await;
+        assert_eq!(events.last().unwrap(), "finish");
+
+        // The stall holds the third token back in real time
+        let start = Instant::now();
+        let (t, events) = faulted_events(StreamFaultType::Stall).await;
+        assert_eq!(events.len(), t.len() + 3);
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+    }
+
     #[tokio::test]
     async fn test_stats() {
         let engine = SimulationEngine::default_config();
@@ -461,4 +1586,31 @@ mod tests {
         assert_eq!(stats.total_requests, 1);
         assert!(stats.total_input_tokens > 0);
     }
+
+    #[test]
+    fn test_fingerprint_follows_config() {
+        let engine = SimulationEngine::default_config();
+        assert_eq!(engine.system_fingerprint(), SimulationEngine::default_config().system_fingerprint());
+        assert!(engine.system_fingerprint().starts_with("fp_"));
+
+        let config = SimulatorConfig {
+            seed: Some(1),
+            ..Default::default()
+        };
+        assert_ne!(engine.system_fingerprint(), SimulationEngine::new(config).system_fingerprint());
+    }
+
+    #[test]
+    fn test_transcript_timings() {
+        let response = transcript_with_timings("Hello there. How are you today?", 6.0, true);
+
+        assert_eq!(response.segments.len(), 2);
+        assert_eq!(response.segments[0].text, " Hello there.");
+        assert_eq!(response.segments[1].start, 2.0);
+        assert_eq!(response.segments[1].end, 6.0);
+
+        let words = response.words.unwrap();
+        assert_eq!(words.len(), 6);
+        assert_eq!(words[1].word, "there");
+    }
 }
@@ -0,0 +1,160 @@
+//! Content moderation simulation
+//!
+//! Scores text against the configured keyword/regex rules. Categories
+//! without a matching rule get a small seeded noise score, so results are
+//! stable for identical inputs.
+
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use regex::Regex;
+
+use super::hash::StableHasher;
+use crate::config::{ModerationConfig, MODERATION_CATEGORIES};
+use crate::types::ModerationResult;
+
+/// Moderation rule with its patterns compiled
+struct CompiledRule {
+    category: String,
+    keywords: Vec<String>,
+    patterns: Vec<Regex>,
+    score: f64,
+}
+
+impl CompiledRule {
+    fn matches(&self, input: &str, lowercase: &str) -> bool {
+        self.keywords.iter().any(|k| lowercase.contains(k.as_str()))
+            || self.patterns.iter().any(|p| p.is_match(input))
+    }
+}
+
+/// Moderator applying configured category rules
+pub struct Moderator {
+    config: ModerationConfig,
+    rules: Vec<CompiledRule>,
+    seed: Option<u64>,
+}
+
+impl Moderator {
+    /// Create a moderator; rules are assumed to be validated
+    pub fn new(config: ModerationConfig, seed: Option<u64>) -> Self {
+        let rules = config.rules.iter()
+            .map(|rule| CompiledRule {
+                category: rule.category.clone(),
+                keywords: rule.keywords.iter().map(|k| k.to_lowercase()).collect(),
+                patterns: rule.patterns.iter().filter_map(|p| Regex::new(p).ok()).collect(),
+                score: rule.score,
+            })
+            .collect();
+
+        Self { config, rules, seed }
+    }
+
+    /// Whether chat requests should be checked against the rules
+    pub fn filters_completions(&self) -> bool {
+        self.config.filter_completions && !self.rules.is_empty()
+    }
+
+    /// Score an input across all categories
+    pub fn moderate(&self, input: &str) -> ModerationResult {
+        let lowercase = input.to_lowercase();
+
+        let mut category_scores: BTreeMap<String, f64> = MODERATION_CATEGORIES.iter()
+            .map(|category| (category.to_string(), self.baseline(input, category)))
+            .collect();
+
+        for rule in self.rules.iter().filter(|r| r.matches(input, &lowercase)) {
+            if let Some(score) = category_scores.get_mut(&rule.category) {
+                *score = score.max(rule.score);
+            }
+        }
+
+        let categories: BTreeMap<String, bool> = category_scores.iter()
+            .map(|(category, score)| (category.clone(), *score >= self.config.flag_threshold))
+            .collect();
+
+        ModerationResult {
+            flagged: categories.values().any(|flagged| *flagged),
+            categories,
+            category_scores,
+        }
+    }
+
+    /// Check whether an input would be flagged
+    pub fn is_flagged(&self, input: &str) -> bool {
+        self.moderate(input).flagged
+    }
+
+    /// Seeded noise score for a category
+    fn baseline(&self, input: &str, category: &str) -> f64 {
+        let seed = StableHasher::new().opt_u64(self.seed).str(input).str(category).finish();
+        let mut rng = StdRng::seed_from_u64(seed);
+        rng.gen::<f64>() * self.config.baseline_score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModerationRule;
+
+    fn moderator() -> Moderator {
+        let config = ModerationConfig {
+            filter_completions: true,
+            rules: vec![
+                ModerationRule {
+                    category: "violence".to_string(),
+                    keywords: vec!["Attack".to_string()],
+                    patterns: vec![],
+                    score: 0.9,
+                },
+                ModerationRule {
+                    category: "hate".to_string(),
+                    keywords: vec![],
+                    patterns: vec![r"\bslur\d+\b".to_string()],
+                    score: 0.8,
+                },
+            ],
+            ..Default::default()
+        };
+        Moderator::new(config, Some(42))
+    }
+
+    #[test]
+    fn test_keyword_rule_flags_category() {
+        let result = moderator().moderate("plan the ATTACK at dawn");
+
+        assert!(result.flagged);
+        assert!(result.categories["violence"]);
+        assert!(!result.categories["hate"]);
+        assert_eq!(result.category_scores["violence"], 0.9);
+    }
+
+    #[test]
+    fn test_pattern_rule_flags_category() {
+        let result = moderator().moderate("that was slur42");
+        assert!(result.categories["hate"]);
+    }
+
+    #[test]
+    fn test_baseline_is_low_and_stable() {
+        let moderator = moderator();
+        let first = moderator.moderate("a friendly greeting");
+        let second = moderator.moderate("a friendly greeting");
+
+        assert!(!first.flagged);
+        assert_eq!(first.category_scores.len(), MODERATION_CATEGORIES.len());
+        assert!(first.category_scores.values().all(|s| *s < 0.01));
+        assert_eq!(first.category_scores, second.category_scores);
+    }
+
+    #[test]
+    fn test_filtering_requires_rules() {
+        let moderator = Moderator::new(
+            ModerationConfig { filter_completions: true, ..Default::default() },
+            None,
+        );
+        assert!(!moderator.filters_completions());
+    }
+}
@@ -0,0 +1,165 @@
+//! Recall of facts stated earlier in a conversation
+//!
+//! Statements such as "my name is Ada", "call me Ada", "I live in Paris"
+//! or "my favorite color is teal" in user turns are remembered, and a
+//! final user question such as "what's my name?" or "where do I live?"
+//! is answered from them. This gives multi-turn tests something coherent
+//! to assert on without a real model.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{Message, Role};
+
+/// A sentence with its terminator
+static SENTENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^.!?\n]+[.!?]?").unwrap());
+
+/// "my <attribute> is <value>"
+static MY_IS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bmy ([a-z][a-z' ]{0,30}?) (?:is|are) ([^.,!?;\n]+)").unwrap()
+});
+static CALL_ME: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bcall me ([^.,!?;\n]+)").unwrap());
+static LIVE_IN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bi live in ([^.,!?;\n]+)").unwrap());
+static WORK_AT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bi work (?:at|for) ([^.,!?;\n]+)").unwrap());
+
+/// "what's my <attribute>?" and similar
+static ASK_MY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:what(?:'s| is| are)|do you (?:remember|know|recall)|tell me|remind me of) my ([a-z][a-z' ]{0,30}?)\s*(?:\?|\.|$)")
+        .unwrap()
+});
+static WHO_AM_I: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bwho am i\b").unwrap());
+static WHERE_LIVE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bwhere do i live\b").unwrap());
+static WHERE_WORK: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\bwhere do i work\b").unwrap());
+
+/// A conjunction starting a new clause, which ends a value
+static CLAUSE_BREAK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\s+(?:and|but|so|because)\s+(?:i|i'm|my|we|you)\b").unwrap()
+});
+
+/// Values longer than this are cut short
+const MAX_VALUE_WORDS: usize = 6;
+
+/// Facts stated in user turns, later statements overriding earlier ones
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Facts {
+    facts: Vec<(String, String)>,
+}
+
+impl Facts {
+    /// Collect facts from the user turns of a conversation
+    pub fn extract(messages: &[Message]) -> Self {
+        let mut facts = Self::default();
+
+        for message in messages.iter().filter(|m| m.role == Role::User) {
+            let text = message.text();
+            for sentence in SENTENCE.find_iter(&text).map(|m| m.as_str().trim()) {
+                // Questions state nothing
+                if sentence.ends_with('?') {
+                    continue;
+                }
+                if let Some(caps) = MY_IS.captures(sentence) {
+                    facts.insert(&caps[1], &caps[2]);
+                }
+                if let Some(caps) = CALL_ME.captures(sentence) {
+                    facts.insert("name", &caps[1]);
+                }
+                if let Some(caps) = LIVE_IN.captures(sentence) {
+                    facts.insert("home", &caps[1]);
+                }
+                if let Some(caps) = WORK_AT.captures(sentence) {
+                    facts.insert("workplace", &caps[1]);
+                }
+            }
+        }
+
+        facts
+    }
+
+    /// Remembered value of an attribute
+    pub fn get(&self, attribute: &str) -> Option<&str> {
+        self.facts.iter()
+            .find(|(a, _)| a == attribute)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.facts.is_empty()
+    }
+
+    fn insert(&mut self, attribute: &str, value: &str) {
+        let attribute = attribute.trim().to_lowercase();
+        let value = CLAUSE_BREAK.split(value).next().unwrap_or_default();
+        let value = value.split_whitespace().take(MAX_VALUE_WORDS).collect::<Vec<_>>().join(" ");
+        if value.is_empty() {
+            return;
+        }
+        self.facts.retain(|(a, _)| *a != attribute);
+        self.facts.push((attribute, value));
+    }
+}
+
+/// Answer to a recall question in the last user turn, if it asks one
+pub fn recall_answer(messages: &[Message]) -> Option<String> {
+    let question = messages.iter().rev().find(|m| m.role == Role::User)?.text();
+
+    let attribute = if WHO_AM_I.is_match(&question) {
+        "name".to_string()
+    } else if WHERE_LIVE.is_match(&question) {
+        "home".to_string()
+    } else if WHERE_WORK.is_match(&question) {
+        "workplace".to_string()
+    } else {
+        ASK_MY.captures(&question)?[1].trim().to_lowercase()
+    };
+
+    let facts = Facts::extract(messages);
+    let answer = match (attribute.as_str(), facts.get(&attribute)) {
+        ("home", Some(value)) => format!("You live in {}.", value),
+        ("workplace", Some(value)) => format!("You work at {}.", value),
+        ("home", None) => "You haven't told me where you live yet.".to_string(),
+        ("workplace", None) => "You haven't told me where you work yet.".to_string(),
+        (attribute, Some(value)) => format!("Your {} is {}.", attribute, value),
+        (attribute, None) => format!("You haven't told me your {} yet.", attribute),
+    };
+    Some(answer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recalls_name_and_facts() {
+        let messages = vec![
+            Message::user("Hi, my name is Ada Lovelace and I live in London."),
+            Message::assistant("Nice to meet you."),
+            Message::user("My favorite color is teal"),
+            Message::assistant("Noted."),
+        ];
+
+        let ask = |q: &str| {
+            let mut conversation = messages.clone();
+            conversation.push(Message::user(q));
+            recall_answer(&conversation)
+        };
+
+        assert_eq!(ask("What's my name?").as_deref(), Some("Your name is Ada Lovelace."));
+        assert_eq!(ask("who am I").as_deref(), Some("Your name is Ada Lovelace."));
+        assert_eq!(ask("Where do I live?").as_deref(), Some("You live in London."));
+        assert_eq!(ask("Do you remember my favorite color?").as_deref(), Some("Your favorite color is teal."));
+        assert_eq!(ask("What is my job?").as_deref(), Some("You haven't told me your job yet."));
+        assert_eq!(ask("Tell me a story"), None);
+    }
+
+    #[test]
+    fn test_later_statements_override() {
+        let facts = Facts::extract(&[
+            Message::user("My name is Ada."),
+            Message::user("Actually, call me Grace."),
+            Message::user("What is my name?"),
+        ]);
+
+        assert_eq!(facts.get("name"), Some("Grace"));
+        assert!(Facts::extract(&[Message::assistant("My name is Bot.")]).is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+//! Sampling parameters applied to generated text
+//!
+//! Word choices are drawn from a ranked vocabulary with Zipf-like base
+//! weights, reshaped the way a model's logits would be: logit bias and
+//! repetition penalties shift each candidate, temperature flattens or
+//! sharpens the distribution, and `top_p` cuts its tail.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::tokenizer::TokenizerKind;
+use crate::types::ChatCompletionRequest;
+
+/// Lowest effective temperature, so temperature 0 still varies words
+const MIN_TEMPERATURE: f64 = 0.2;
+
+/// Sampling parameters of a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingParams {
+    pub temperature: f32,
+    pub top_p: f32,
+    pub presence_penalty: f32,
+    pub frequency_penalty: f32,
+    /// Bias per token id
+    pub logit_bias: HashMap<u32, f32>,
+    /// Seed requested by the caller
+    pub seed: Option<u64>,
+}
+
+impl Default for SamplingParams {
+    fn default() -> Self {
+        Self {
+            temperature: 1.0,
+            top_p: 1.0,
+            presence_penalty: 0.0,
+            frequency_penalty: 0.0,
+            logit_bias: HashMap::new(),
+            seed: None,
+        }
+    }
+}
+
+impl SamplingParams {
+    /// Parameters of a chat request, defaulting unset ones
+    pub fn from_request(request: &ChatCompletionRequest) -> Self {
+        let defaults = Self::default();
+        Self {
+            temperature: request.temperature.unwrap_or(defaults.temperature),
+            top_p: request.top_p.unwrap_or(defaults.top_p),
+            presence_penalty: request.presence_penalty.unwrap_or_default(),
+            frequency_penalty: request.frequency_penalty.unwrap_or_default(),
+            logit_bias: request.logit_bias.iter()
+                .flatten()
+                .filter_map(|(token, bias)| Some((token.parse().ok()?, *bias)))
+                .collect(),
+            seed: request.seed,
+        }
+    }
+
+    /// Temperature 0: output depends only on the prompt
+    pub fn is_greedy(&self) -> bool {
+        self.temperature <= 0.0
+    }
+
+    /// Whether frequency or presence penalties apply, so repeats are tracked
+    pub fn penalizes_repeats(&self) -> bool {
+        self.frequency_penalty != 0.0 || self.presence_penalty != 0.0
+    }
+
+    /// Fraction of the configured length range to sample from: all of it
+    /// at the default temperature and above, narrowing below it
+    pub fn length_spread(&self) -> f64 {
+        (self.temperature as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// Chooses words under sampling parameters, tracking repetitions
+pub struct WordSampler<'a> {
+    params: &'a SamplingParams,
+    tokenizer: TokenizerKind,
+    counts: HashMap<String, u32>,
+    biases: HashMap<String, f64>,
+}
+
+impl<'a> WordSampler<'a> {
+    pub fn new(params: &'a SamplingParams, tokenizer: TokenizerKind) -> Self {
+        Self {
+            params,
+            tokenizer,
+            counts: HashMap::new(),
+            biases: HashMap::new(),
+        }
+    }
+
+    /// Choose from a vocabulary ordered from most to least likely
+    pub fn choose<'w, R: Rng>(&mut self, words: &[&'w str], rng: &mut R) -> &'w str {
+        let weights: Vec<f64> = (1..=words.len()).map(|rank| 1.0 / rank as f64).collect();
+        words[self.choose_weighted(words, &weights, rng)]
+    }
+
+    /// Choose an index into `words` given base weights
+    pub fn choose_weighted<R: Rng>(&mut self, words: &[&str], weights: &[f64], rng: &mut R) -> usize {
+        if words.len() <= 1 {
+            if let Some(word) = words.first() {
+                self.record(word);
+            }
+            return 0;
+        }
+
+        let temperature = (self.params.temperature as f64).max(MIN_TEMPERATURE);
+        let logits: Vec<f64> = words.iter()
+            .zip(weights)
+            .map(|(word, weight)| (weight.max(f64::MIN_POSITIVE).ln() + self.adjustment(word)) / temperature)
+            .collect();
+
+        // Softmax, then keep the smallest set of words covering top_p
+        let max = logits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let mut probs: Vec<(usize, f64)> = logits.iter()
+            .map(|l| (l - max).exp())
+            .enumerate()
+            .collect();
+        let total: f64 = probs.iter().map(|(_, p)| p).sum();
+        probs.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut nucleus = Vec::new();
+        let mut cumulative = 0.0;
+        for (index, p) in probs {
+            nucleus.push((index, p));
+            cumulative += p / total;
+            if cumulative >= self.params.top_p as f64 {
+                break;
+            }
+        }
+
+        let mass: f64 = nucleus.iter().map(|(_, p)| p).sum();
+        let mut pick = rng.gen_range(0.0..mass);
+        let mut chosen = nucleus[0].0;
+        for (index, p) in nucleus {
+            chosen = index;
+            if pick < p {
+                break;
+            }
+            pick -= p;
+        }
+
+        self.record(words[chosen]);
+        chosen
+    }
+
+    /// Count a word emitted outside `choose`
+    pub fn record(&mut self, word: &str) {
+        if !self.params.penalizes_repeats() {
+            return;
+        }
+        *self.counts.entry(word.to_string()).or_default() += 1;
+    }
+
+    /// Logit shift from bias and repetition penalties.
+    ///
+    /// Line breaks and indentation are never penalized, so penalties don't
+    /// flatten the layout of the text.
+    fn adjustment(&mut self, word: &str) -> f64 {
+        if !self.params.penalizes_repeats() || word.trim().is_empty() {
+            return self.bias(word);
+        }
+        let count = self.counts.get(word).copied().unwrap_or(0) as f64;
+        let penalty = self.params.frequency_penalty as f64 * count
+            + if count > 0.0 { self.params.presence_penalty as f64 } else { 0.0 };
+        self.bias(word) - penalty
+    }
+
+    /// Summed logit bias of the tokens of a word, as it appears mid-text
+    fn bias(&mut self, word: &str) -> f64 {
+        if self.params.logit_bias.is_empty() {
+            return 0.0;
+        }
+        if let Some(bias) = self.biases.get(word) {
+            return *bias;
+        }
+
+        let bias = self.tokenizer.encode(&format!(" {}", word.trim()))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|id| self.params.logit_bias.get(id))
+            .map(|b| *b as f64)
+            .sum();
+        self.biases.insert(word.to_string(), bias);
+        bias
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const WORDS: &[&str] = &["alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta"];
+
+    fn distinct(params: &SamplingParams, draws: usize) -> usize {
+        let mut sampler = WordSampler::new(params, TokenizerKind::Cl100kBase);
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut seen: Vec<&str> = (0..draws).map(|_| sampler.choose(WORDS, &mut rng)).collect();
+        seen.sort();
+        seen.dedup();
+        seen.len()
+    }
+
+    #[test]
+    fn test_temperature_increases_variety() {
+        let cold = SamplingParams { temperature: 0.0, ..Default::default() };
+        let hot = SamplingParams { temperature: 2.0, ..Default::default() };
+        assert!(distinct(&hot, 40) > distinct(&cold, 40));
+    }
+
+    #[test]
+    fn test_top_p_limits_candidates() {
+        let params = SamplingParams { top_p: 0.01, ..Default::default() };
+        assert_eq!(distinct(&params, 40), 1);
+    }
+
+    #[test]
+    fn test_frequency_penalty_avoids_repeats() {
+        let plain = SamplingParams { temperature: 0.0, ..Default::default() };
+        let penalized = SamplingParams { frequency_penalty: 2.0, ..plain.clone() };
+        assert!(distinct(&penalized, 8) > distinct(&plain, 8));
+    }
+
+    #[test]
+    fn test_logit_bias_bans_word() {
+        let alpha = TokenizerKind::Cl100kBase.encode(" alpha").unwrap();
+        let params = SamplingParams {
+            logit_bias: alpha.into_iter().map(|id| (id, -100.0)).collect(),
+            ..Default::default()
+        };
+        let mut sampler = WordSampler::new(&params, TokenizerKind::Cl100kBase);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!((0..100).all(|_| sampler.choose(WORDS, &mut rng) != "alpha"));
+    }
+
+    #[test]
+    fn test_length_spread() {
+        let spread = |temperature| SamplingParams { temperature, ..Default::default() }.length_spread();
+        assert_eq!(SamplingParams::default().length_spread(), 1.0);
+        assert_eq!(spread(2.0), 1.0);
+        assert_eq!(spread(0.5), 0.5);
+        assert_eq!(spread(0.0), 0.0);
+    }
+}
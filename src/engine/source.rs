@@ -0,0 +1,149 @@
+//! Pluggable response sources
+//!
+//! Library users embedding the simulator can replace the built-in
+//! generator with their own logic, globally or per model. Sources receive
+//! the normalized (OpenAI-shaped) request, so one implementation serves
+//! every provider API.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::ModelConfig;
+use crate::error::SimulatorResult;
+use crate::types::{ChatCompletionRequest, FinishReason, ResponseFunctionCall, ResponseToolCall};
+
+/// Produces chat responses in place of the built-in generator
+#[async_trait]
+pub trait ResponseSource: Send + Sync {
+    /// Respond to a normalized chat request for `model`.
+    ///
+    /// Content longer than the request's token limit is truncated by the
+    /// engine, which then reports `FinishReason::Length`.
+    async fn respond(
+        &self,
+        request: &ChatCompletionRequest,
+        model: &ModelConfig,
+    ) -> SimulatorResult<SourceResponse>;
+}
+
+/// Response produced by a source
+#[derive(Debug, Clone)]
+pub struct SourceResponse {
+    pub content: String,
+    pub tool_calls: Vec<ResponseToolCall>,
+    pub finish_reason: FinishReason,
+}
+
+impl SourceResponse {
+    /// Plain text response
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            tool_calls: Vec::new(),
+            finish_reason: FinishReason::Stop,
+        }
+    }
+
+    /// Response that only calls tools
+    pub fn tool_calls(tool_calls: Vec<ResponseToolCall>) -> Self {
+        Self {
+            content: String::new(),
+            tool_calls,
+            finish_reason: FinishReason::ToolCalls,
+        }
+    }
+
+    /// Add a function tool call, switching the finish reason to `tool_calls`
+    pub fn with_tool_call(mut self, name: impl Into<String>, arguments: impl Into<String>) -> Self {
+        self.tool_calls.push(function_call(name, arguments));
+        self.finish_reason = FinishReason::ToolCalls;
+        self
+    }
+
+    /// Override the finish reason
+    pub fn with_finish_reason(mut self, finish_reason: FinishReason) -> Self {
+        self.finish_reason = finish_reason;
+        self
+    }
+}
+
+/// Build a function tool call. Its `call_` id is left empty for the engine
+/// to assign from its ID generator.
+pub fn function_call(name: impl Into<String>, arguments: impl Into<String>) -> ResponseToolCall {
+    ResponseToolCall {
+        id: String::new(),
+        call_type: "function".to_string(),
+        function: ResponseFunctionCall {
+            name: name.into(),
+            arguments: arguments.into(),
+        },
+    }
+}
+
+/// Registered sources, looked up per model before the global one
+#[derive(Default, Clone)]
+pub struct ResponseSources {
+    global: Option<Arc<dyn ResponseSource>>,
+    by_model: HashMap<String, Arc<dyn ResponseSource>>,
+}
+
+impl ResponseSources {
+    /// Use `source` for every model without its own
+    pub fn set_global(&mut self, source: Arc<dyn ResponseSource>) {
+        self.global = Some(source);
+    }
+
+    /// Use `source` for one model
+    pub fn register(&mut self, model: impl Into<String>, source: Arc<dyn ResponseSource>) {
+        self.by_model.insert(model.into(), source);
+    }
+
+    /// Source for `model`, if any is registered
+    pub fn get(&self, model: &str) -> Option<Arc<dyn ResponseSource>> {
+        self.by_model.get(model).or(self.global.as_ref()).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str);
+
+    #[async_trait]
+    impl ResponseSource for Fixed {
+        async fn respond(&self, _: &ChatCompletionRequest, _: &ModelConfig) -> SimulatorResult<SourceResponse> {
+            Ok(SourceResponse::text(self.0))
+        }
+    }
+
+    #[test]
+    fn test_model_source_overrides_global() {
+        let mut sources = ResponseSources::default();
+        assert!(sources.get("gpt-4").is_none());
+
+        sources.set_global(Arc::new(Fixed("global")));
+        sources.register("gpt-4", Arc::new(Fixed("model")));
+
+        let request = ChatCompletionRequest::new("gpt-4", vec![]);
+        let model = ModelConfig::gpt4();
+        let respond = |name: &str| {
+            let source = sources.get(name).unwrap();
+            futures::executor::block_on(source.respond(&request, &model)).unwrap().content
+        };
+
+        assert_eq!(respond("gpt-4"), "model");
+        assert_eq!(respond("claude-3-haiku-20240307"), "global");
+    }
+
+    #[test]
+    fn test_tool_call_response() {
+        let response = SourceResponse::text("").with_tool_call("get_weather", r#"{"city":"Paris"}"#);
+
+        assert_eq!(response.finish_reason, FinishReason::ToolCalls);
+        assert_eq!(response.tool_calls[0].function.name, "get_weather");
+        assert!(response.tool_calls[0].id.is_empty());
+    }
+}
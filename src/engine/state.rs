@@ -1,5 +1,6 @@
 //! Engine state and statistics tracking
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use parking_lot::RwLock;
@@ -11,6 +12,7 @@ pub struct EngineState {
     total_errors: AtomicU64,
     total_input_tokens: AtomicU64,
     total_output_tokens: AtomicU64,
+    cold_starts: AtomicU64,
     latencies: RwLock<LatencyTracker>,
 }
 
@@ -21,6 +23,7 @@ impl EngineState {
             total_errors: AtomicU64::new(0),
             total_input_tokens: AtomicU64::new(0),
             total_output_tokens: AtomicU64::new(0),
+            cold_starts: AtomicU64::new(0),
             latencies: RwLock::new(LatencyTracker::new()),
         }
     }
@@ -41,6 +44,11 @@ impl EngineState {
         self.total_output_tokens.fetch_add(output, Ordering::Relaxed);
     }
 
+    /// Count a request served cold
+    pub fn increment_cold_starts(&self) {
+        self.cold_starts.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Record a latency measurement
     pub fn record_latency(&self, latency: Duration) {
         self.latencies.write().record(latency);
@@ -55,6 +63,7 @@ impl EngineState {
             total_errors: self.total_errors.load(Ordering::Relaxed),
             total_input_tokens: self.total_input_tokens.load(Ordering::Relaxed),
             total_output_tokens: self.total_output_tokens.load(Ordering::Relaxed),
+            cold_starts: self.cold_starts.load(Ordering::Relaxed),
             latency: latency_stats,
         }
     }
@@ -65,6 +74,7 @@ impl EngineState {
         self.total_errors.store(0, Ordering::Relaxed);
         self.total_input_tokens.store(0, Ordering::Relaxed);
         self.total_output_tokens.store(0, Ordering::Relaxed);
+        self.cold_starts.store(0, Ordering::Relaxed);
         *self.latencies.write() = LatencyTracker::new();
     }
 }
@@ -82,6 +92,9 @@ pub struct EngineStats {
     pub total_errors: u64,
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
+    /// Requests that paid a cold-start penalty
+    #[serde(default)]
+    pub cold_starts: u64,
     pub latency: LatencyStats,
 }
 
@@ -112,6 +125,7 @@ impl Default for EngineStats {
             total_errors: 0,
             total_input_tokens: 0,
             total_output_tokens: 0,
+            cold_starts: 0,
             latency: LatencyStats::default(),
         }
     }
@@ -253,6 +267,122 @@ impl SessionState {
     pub fn is_expired(&self, max_idle: Duration) -> bool {
         self.last_activity.elapsed() > max_idle
     }
+
+    /// Serializable snapshot of the session
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            session_id: self.session_id.clone(),
+            message_count: self.messages.len(),
+            total_tokens: self.total_tokens,
+            age_secs: self.created_at.elapsed().as_secs(),
+            idle_secs: self.last_activity.elapsed().as_secs(),
+            messages: self.messages.clone(),
+        }
+    }
+}
+
+/// Session snapshot returned by the admin API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub message_count: usize,
+    pub total_tokens: usize,
+    pub age_secs: u64,
+    pub idle_secs: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub messages: Vec<crate::types::Message>,
+}
+
+/// Conversation sessions by ID, expiring after a period of inactivity
+pub struct SessionStore {
+    sessions: RwLock<HashMap<String, SessionState>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self {
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Earlier turns of a live session, dropping it if expired
+    pub fn history(&self, session_id: &str, ttl: Duration) -> Vec<crate::types::Message> {
+        let mut sessions = self.sessions.write();
+        match sessions.get(session_id) {
+            Some(session) if session.is_expired(ttl) => {
+                sessions.remove(session_id);
+                Vec::new()
+            }
+            Some(session) => session.messages.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Append turns to a session, creating it and evicting the least
+    /// recently active sessions beyond `max_sessions`
+    pub fn record(
+        &self,
+        session_id: &str,
+        messages: impl IntoIterator<Item = crate::types::Message>,
+        ttl: Duration,
+        max_sessions: usize,
+    ) {
+        let mut sessions = self.sessions.write();
+        if !sessions.contains_key(session_id) {
+            sessions.retain(|_, s| !s.is_expired(ttl));
+            while sessions.len() >= max_sessions.max(1) {
+                let oldest = sessions.values()
+                    .min_by_key(|s| s.last_activity)
+                    .map(|s| s.session_id.clone());
+                match oldest {
+                    Some(id) => sessions.remove(&id),
+                    None => break,
+                };
+            }
+        }
+
+        let session = sessions.entry(session_id.to_string())
+            .or_insert_with(|| SessionState::new(session_id.to_string()));
+        for message in messages {
+            session.add_message(message);
+        }
+    }
+
+    /// A live session
+    pub fn get(&self, session_id: &str, ttl: Duration) -> Option<SessionInfo> {
+        self.sessions.read()
+            .get(session_id)
+            .filter(|s| !s.is_expired(ttl))
+            .map(SessionState::info)
+    }
+
+    /// Summaries of all live sessions, without their messages
+    pub fn list(&self, ttl: Duration) -> Vec<SessionInfo> {
+        let mut sessions = self.sessions.write();
+        sessions.retain(|_, s| !s.is_expired(ttl));
+
+        let mut infos: Vec<SessionInfo> = sessions.values()
+            .map(|s| SessionInfo { messages: Vec::new(), ..s.info() })
+            .collect();
+        infos.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        infos
+    }
+
+    /// Remove a session, returning whether it existed
+    pub fn remove(&self, session_id: &str) -> bool {
+        self.sessions.write().remove(session_id).is_some()
+    }
+
+    /// Remove all sessions
+    pub fn clear(&self) {
+        self.sessions.write().clear();
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -326,4 +456,36 @@ mod tests {
         assert!(session.total_tokens > 0);
         assert!(!session.is_expired(Duration::from_secs(60)));
     }
+
+    #[test]
+    fn test_session_store() {
+        let store = SessionStore::new();
+        let ttl = Duration::from_secs(60);
+
+        store.record("a", [crate::types::Message::user("Hello")], ttl, 2);
+        store.record("a", [crate::types::Message::assistant("Hi there!")], ttl, 2);
+        assert_eq!(store.history("a", ttl).len(), 2);
+        assert_eq!(store.get("a", ttl).unwrap().message_count, 2);
+
+        // A third session evicts the least recently active
+        store.record("b", [crate::types::Message::user("Hey")], ttl, 2);
+        store.record("c", [crate::types::Message::user("Yo")], ttl, 2);
+        let ids: Vec<String> = store.list(ttl).into_iter().map(|s| s.session_id).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+
+        assert!(store.remove("b"));
+        assert!(!store.remove("b"));
+        assert!(store.history("b", ttl).is_empty());
+    }
+
+    #[test]
+    fn test_session_expiry() {
+        let store = SessionStore::new();
+        store.record("a", [crate::types::Message::user("Hello")], Duration::from_secs(60), 10);
+
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(store.get("a", Duration::ZERO).is_none());
+        assert!(store.history("a", Duration::ZERO).is_empty());
+        assert!(store.get("a", Duration::from_secs(60)).is_none());
+    }
 }
@@ -0,0 +1,163 @@
+//! Per-model throughput ceilings
+//!
+//! Streams of a model with aggregate token rates reserve every token from
+//! shared limiters before sending it. A limiter hands out one token per
+//! interval, so concurrent streams interleave and each gets a share of the
+//! rate rather than its own nominal speed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+use crate::config::ThroughputConfig;
+use crate::latency::Pacer;
+
+/// Aggregate token rates of one model
+#[derive(Debug)]
+pub struct ThroughputPool {
+    config: ThroughputConfig,
+    global: Option<Arc<RateLimiter>>,
+    keys: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl ThroughputPool {
+    pub fn new(config: ThroughputConfig) -> Self {
+        Self {
+            global: config.global_tokens_per_second.map(|rate| Arc::new(RateLimiter::new(rate))),
+            keys: Mutex::new(HashMap::new()),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ThroughputConfig {
+        &self.config
+    }
+
+    /// Ceilings on a stream of the API key `key`, if any
+    pub fn throttle(self: &Arc<Self>, key: Option<&str>) -> Throttle {
+        let key = key.zip(self.config.per_key_tokens_per_second).map(|(key, rate)| {
+            self.keys.lock()
+                .entry(key.to_string())
+                .or_insert_with(|| Arc::new(RateLimiter::new(rate)))
+                .clone()
+        });
+        Throttle {
+            pool: Some(self.clone()),
+            limiters: self.global.iter().cloned().chain(key).collect(),
+        }
+    }
+}
+
+/// Shared ceilings a stream's tokens are held to
+#[derive(Debug, Clone, Default)]
+pub struct Throttle {
+    pool: Option<Arc<ThroughputPool>>,
+    limiters: Vec<Arc<RateLimiter>>,
+}
+
+impl Throttle {
+    /// The same ceilings plus those of the API key `key`
+    pub fn with_key(self, key: &str) -> Self {
+        match &self.pool {
+            Some(pool) => pool.throttle(Some(key)),
+            None => self,
+        }
+    }
+
+    /// Real time to wait for a token due `delay` from now, reserving its place
+    /// under every ceiling; ceilings are compressed like the delay under
+    /// virtual time
+    pub fn reserve(&self, pacer: Pacer, delay: Duration) -> Duration {
+        let now = Instant::now();
+        let due = now + pacer.scaled(delay);
+        let ready = self.limiters.iter()
+            .fold(due, |ready, limiter| limiter.reserve(ready, pacer));
+        ready - now
+    }
+
+    /// Sleep until a token due `delay` from now may go out
+    pub async fn sleep(&self, pacer: Pacer, delay: Duration) {
+        let wait = self.reserve(pacer, delay);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limiters.is_empty()
+    }
+}
+
+/// Hands out one token per interval, never ahead of the previous one
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    fn new(tokens_per_second: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / tokens_per_second),
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Earliest time from `due` a token may go out, which it then takes
+    fn reserve(&self, due: Instant, pacer: Pacer) -> Instant {
+        let mut next = self.next.lock();
+        let at = next.map_or(due, |next| next.max(due));
+        *next = Some(at + pacer.scaled(self.interval));
+        at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(global: Option<f64>, per_key: Option<f64>) -> Arc<ThroughputPool> {
+        Arc::new(ThroughputPool::new(ThroughputConfig {
+            tokens_per_second: 100.0,
+            global_tokens_per_second: global,
+            per_key_tokens_per_second: per_key,
+        }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_streams_share_global_rate() {
+        let pool = pool(Some(10.0), None);
+        let (a, b) = (pool.throttle(None), pool.throttle(Some("key")));
+        let pacer = Pacer::real_time();
+
+        // Interleaved tokens of two streams go out 100ms apart
+        let waits: Vec<Duration> = (0..4)
+            .map(|i| if i % 2 == 0 { &a } else { &b }.reserve(pacer, Duration::ZERO))
+            .collect();
+        assert_eq!(waits, [0, 100, 200, 300].map(Duration::from_millis));
+
+        // Nominal delays longer than the ceiling's spacing are kept
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(a.reserve(pacer, Duration::from_millis(250)), Duration::from_millis(250));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_per_key_rates_are_separate() {
+        let pool = pool(None, Some(5.0));
+        let pacer = Pacer::real_time();
+
+        assert_eq!(pool.throttle(Some("a")).reserve(pacer, Duration::ZERO), Duration::ZERO);
+        assert_eq!(pool.throttle(None).with_key("a").reserve(pacer, Duration::ZERO), Duration::from_millis(200));
+        assert_eq!(pool.throttle(Some("b")).reserve(pacer, Duration::ZERO), Duration::ZERO);
+
+        // Without a key or under zero-scale virtual time nothing is held back
+        assert!(pool.throttle(None).is_empty());
+        let compressed = pool.throttle(Some("c"));
+        for _ in 0..3 {
+            assert_eq!(compressed.reserve(Pacer::new(0.0), Duration::from_secs(1)), Duration::ZERO);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Per-model warm pools
+//!
+//! A model with cold starts configured runs on simulated instances. Requests
+//! beyond the instances already up scale up a new one, idle instances are
+//! released back down to the warm pool, and the first requests on a new
+//! instance are served cold.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::config::{ColdStartConfig, LatencyDistribution};
+
+/// Instances of one model and how warm they are
+#[derive(Debug)]
+pub struct WarmPool {
+    config: ColdStartConfig,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    instances: usize,
+    in_flight: usize,
+    /// Requests still to be served cold by recently started instances
+    cold_remaining: usize,
+    last_active: Option<Instant>,
+    cold_starts: u64,
+}
+
+impl WarmPool {
+    pub fn new(config: ColdStartConfig) -> Self {
+        Self {
+            state: Mutex::new(PoolState {
+                instances: config.warm_pool,
+                in_flight: 0,
+                cold_remaining: 0,
+                last_active: None,
+                cold_starts: 0,
+            }),
+            config,
+        }
+    }
+
+    /// Admit a request, scaling up if every instance is busy
+    pub fn admit(self: &Arc<Self>) -> Admission {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+
+        let idle = state.in_flight == 0 && match (state.last_active, self.config.idle_timeout()) {
+            (Some(last), Some(timeout)) => now.duration_since(last) > timeout,
+            _ => false,
+        };
+        if idle && state.instances > self.config.warm_pool {
+            state.instances = self.config.warm_pool;
+            state.cold_remaining = 0;
+        }
+
+        state.in_flight += 1;
+        state.last_active = Some(now);
+        if state.in_flight > state.instances {
+            state.instances = state.in_flight;
+            state.cold_remaining += self.config.warmup_requests;
+        }
+
+        let cold = state.cold_remaining > 0;
+        if cold {
+            state.cold_remaining -= 1;
+            state.cold_starts += 1;
+        }
+
+        Admission { pool: self.clone(), cold }
+    }
+
+    pub fn config(&self) -> &ColdStartConfig {
+        &self.config
+    }
+
+    /// Snapshot of the pool
+    pub fn status(&self) -> WarmPoolStatus {
+        let state = self.state.lock();
+        WarmPoolStatus {
+            instances: state.instances,
+            in_flight: state.in_flight,
+            cold_starts: state.cold_starts,
+        }
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock();
+        state.in_flight -= 1;
+        state.last_active = Some(Instant::now());
+    }
+}
+
+/// A request running on an instance of a warm pool, released on drop
+#[derive(Debug)]
+pub struct Admission {
+    pool: Arc<WarmPool>,
+    cold: bool,
+}
+
+impl Admission {
+    /// Whether the request landed on an instance still starting up
+    pub fn is_cold(&self) -> bool {
+        self.cold
+    }
+
+    /// Extra TTFT of a cold request
+    pub fn penalty(&self) -> &LatencyDistribution {
+        &self.pool.config.penalty
+    }
+
+    /// Keep the instance busy while the response is produced
+    pub fn hold_for(self, duration: Duration) {
+        if duration.is_zero() {
+            return;
+        }
+        tokio::spawn(async move {
+            tokio::time::sleep(duration).await;
+            drop(self);
+        });
+    }
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        self.pool.release();
+    }
+}
+
+/// Instances of a warm pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WarmPoolStatus {
+    pub instances: usize,
+    pub in_flight: usize,
+    pub cold_starts: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(warm_pool: usize, warmup_requests: usize) -> Arc<WarmPool> {
+        Arc::new(WarmPool::new(ColdStartConfig {
+            warm_pool,
+            warmup_requests,
+            idle_timeout_secs: Some(60.0),
+            ..Default::default()
+        }))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_first_requests_and_idle_models_start_cold() {
+        let pool = pool(0, 2);
+
+        assert!(pool.admit().is_cold());
+        assert!(pool.admit().is_cold());
+        assert!(!pool.admit().is_cold());
+
+        // Idle past the timeout, the instance is released
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(pool.admit().is_cold());
+        assert_eq!(pool.status(), WarmPoolStatus { instances: 1, in_flight: 0, cold_starts: 3 });
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_warm_pool_absorbs_load_then_scales_up() {
+        let pool = pool(2, 1);
+
+        let first = pool.admit();
+        let second = pool.admit();
+        assert!(!first.is_cold() && !second.is_cold());
+
+        // A third concurrent request needs a new instance
+        let third = pool.admit();
+        assert!(third.is_cold());
+        assert_eq!(pool.status().instances, 3);
+        drop((first, second, third));
+
+        // Scaling down keeps the warm pool
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(!pool.admit().is_cold());
+        assert_eq!(pool.status().instances, 2);
+    }
+}
@@ -34,6 +34,9 @@ pub enum SimulationError {
     #[error("Rate limit exceeded")]
     RateLimitExceeded { retry_after: Duration },
 
+    #[error("Overloaded")]
+    Overloaded { retry_after: Duration },
+
     // Timeout
     #[error("Request timeout after {0:?}")]
     Timeout(Duration),
@@ -84,6 +87,37 @@ pub enum InjectedErrorType {
     AuthenticationError,
     InvalidRequest,
     ContextLengthExceeded,
+    // Protocol faults: the chat response itself is malformed
+    /// Completion or first chunk's JSON cut off halfway
+    TruncatedJson,
+    /// Completion or stream labeled as HTML
+    WrongContentType,
+    /// Generated text opening with bytes that are not valid UTF-8
+    InvalidUtf8,
+    /// Event stream whose lines lack the `data:` prefix
+    MissingDataPrefix,
+    /// Plain body declared as gzip-encoded
+    MislabeledGzip,
+    /// Headers too large for common clients and proxies
+    OversizedHeaders,
+    /// Error body under a success status
+    ErrorPayload,
+}
+
+impl InjectedErrorType {
+    /// Whether the error corrupts the response rather than reporting an error
+    pub fn is_protocol_fault(&self) -> bool {
+        matches!(
+            self,
+            Self::TruncatedJson
+                | Self::WrongContentType
+                | Self::InvalidUtf8
+                | Self::MissingDataPrefix
+                | Self::MislabeledGzip
+                | Self::OversizedHeaders
+                | Self::ErrorPayload
+        )
+    }
 }
 
 impl std::fmt::Display for InjectedErrorType {
@@ -97,10 +131,27 @@ impl std::fmt::Display for InjectedErrorType {
             Self::AuthenticationError => write!(f, "authentication_error"),
             Self::InvalidRequest => write!(f, "invalid_request_error"),
             Self::ContextLengthExceeded => write!(f, "context_length_exceeded"),
+            Self::TruncatedJson => write!(f, "truncated_json"),
+            Self::WrongContentType => write!(f, "wrong_content_type"),
+            Self::InvalidUtf8 => write!(f, "invalid_utf8"),
+            Self::MissingDataPrefix => write!(f, "missing_data_prefix"),
+            Self::MislabeledGzip => write!(f, "mislabeled_gzip"),
+            Self::OversizedHeaders => write!(f, "oversized_headers"),
+            Self::ErrorPayload => write!(f, "error_payload"),
         }
     }
 }
 
+/// A protocol fault a chat response is broken by on its way out
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolFault {
+    pub fault_type: InjectedErrorType,
+    /// Status the broken response claims
+    pub status_code: u16,
+    /// Message of an error payload
+    pub message: String,
+}
+
 /// OpenAI-compatible error response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
@@ -148,6 +199,7 @@ impl SimulationError {
             Self::Validation { .. } => StatusCode::BAD_REQUEST,
             Self::ProviderNotFound(_) | Self::ModelNotFound(_) => StatusCode::NOT_FOUND,
             Self::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::Overloaded { .. } => StatusCode::from_u16(529).unwrap(),
             Self::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
             Self::AuthenticationFailed(_) => StatusCode::UNAUTHORIZED,
             Self::PermissionDenied(_) => StatusCode::FORBIDDEN,
@@ -168,6 +220,7 @@ impl SimulationError {
             Self::Validation { .. } => "invalid_request_error",
             Self::ProviderNotFound(_) | Self::ModelNotFound(_) => "not_found_error",
             Self::RateLimitExceeded { .. } => "rate_limit_error",
+            Self::Overloaded { .. } => "overloaded_error",
             Self::Timeout(_) => "timeout_error",
             Self::AuthenticationFailed(_) => "authentication_error",
             Self::PermissionDenied(_) => "permission_error",
@@ -208,8 +261,8 @@ impl IntoResponse for SimulationError {
 
         let mut response = (status, body).into_response();
 
-        // Add retry-after header for rate limits
-        if let SimulationError::RateLimitExceeded { retry_after } = &self {
+        // Add retry-after header for rate limits and overload
+        if let SimulationError::RateLimitExceeded { retry_after } | SimulationError::Overloaded { retry_after } = &self {
             response.headers_mut().insert(
                 "retry-after",
                 retry_after.as_secs().to_string().parse().unwrap(),
@@ -0,0 +1,577 @@
+//! Latency profile calibration
+//!
+//! Fits TTFT and ITL distributions to recorded traffic so that simulated
+//! profiles reproduce production. Traces are JSONL observations, one request
+//! per line, or summaries exported from latency-lens. Every provider/model
+//! gets the candidate distribution closest to what was observed, and a
+//! percentile table when no candidate comes close.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::DistributionSampler;
+use crate::adapters::latency_lens::{ConsumedLatencyProfile, LatencyDistributionData};
+use crate::config::{gamma_fn, LatencyDistribution, LatencyProfile};
+use crate::error::{SimulationError, SimulatorResult};
+
+/// Draws from each candidate when comparing it with the observations
+const SIMULATED_DRAWS: usize = 20_000;
+/// Seed of those draws, so calibration is repeatable
+const CALIBRATION_SEED: u64 = 0x5eed;
+/// Fit error above which a percentile table is tried as well
+const FALLBACK_ERROR: f64 = 0.1;
+
+/// One recorded request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyObservation {
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Time to first token (ms)
+    #[serde(default)]
+    pub ttft_ms: Option<f64>,
+    /// Inter-token latencies (ms), one per token or a single average
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub itl_ms: Vec<f64>,
+    /// Whole request (ms)
+    #[serde(default)]
+    pub total_ms: Option<f64>,
+    /// Tokens generated; with `total_ms` gives an average ITL when `itl_ms` is missing
+    #[serde(default)]
+    pub output_tokens: Option<u64>,
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(f64),
+        Many(Vec<f64>),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(value)) => vec![value],
+        Some(OneOrMany::Many(values)) => values,
+        None => Vec::new(),
+    })
+}
+
+/// Recorded latency data to calibrate from
+#[derive(Debug, Clone)]
+pub enum LatencyTraces {
+    /// Per-request measurements
+    Observations(Vec<LatencyObservation>),
+    /// Latency-lens summaries, one per provider/model
+    Summaries(Vec<ConsumedLatencyProfile>),
+}
+
+/// Exported TTFT and ITL summaries without a profile around them
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SummaryPair {
+    ttft: Option<LatencyDistributionData>,
+    itl: Option<LatencyDistributionData>,
+}
+
+impl LatencyTraces {
+    /// Parse latency-lens JSON (a `ConsumedLatencyProfile`, a list of them,
+    /// or `{ttft, itl}` of `LatencyDistributionData`), else JSONL observations
+    pub fn parse(input: &str) -> SimulatorResult<Self> {
+        if let Ok(profiles) = serde_json::from_str::<Vec<ConsumedLatencyProfile>>(input) {
+            return Ok(Self::Summaries(profiles));
+        }
+        if let Ok(profile) = serde_json::from_str::<ConsumedLatencyProfile>(input) {
+            return Ok(Self::Summaries(vec![profile]));
+        }
+        if let Ok(SummaryPair { ttft, itl }) = serde_json::from_str::<SummaryPair>(input) {
+            return Ok(Self::Summaries(vec![ConsumedLatencyProfile {
+                id: String::new(),
+                provider: String::new(),
+                model: String::new(),
+                ttft: ttft.unwrap_or_default(),
+                itl: itl.unwrap_or_default(),
+                total_latency: LatencyDistributionData::default(),
+                captured_at: 0,
+            }]));
+        }
+
+        input.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| SimulationError::Config(format!("Trace line {}: {}", i + 1, e)))
+            })
+            .collect::<SimulatorResult<Vec<_>>>()
+            .map(Self::Observations)
+    }
+}
+
+/// How a fit was scored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMetric {
+    /// Kolmogorov-Smirnov distance from the observed samples
+    KolmogorovSmirnov,
+    /// Largest relative error at the reported percentiles
+    PercentileError,
+}
+
+impl FitMetric {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::KolmogorovSmirnov => "ks",
+            Self::PercentileError => "percentile error",
+        }
+    }
+}
+
+/// Observed and fitted latency at one percentile
+#[derive(Debug, Clone, Serialize)]
+pub struct PercentileCheck {
+    pub percentile: String,
+    pub observed_ms: f64,
+    pub fitted_ms: f64,
+}
+
+/// Distribution fitted to one latency series
+#[derive(Debug, Clone, Serialize)]
+pub struct DistributionFit {
+    pub distribution: LatencyDistribution,
+    pub metric: FitMetric,
+    /// Error of `distribution` under `metric`; lower is better
+    pub error: f64,
+    /// Observations behind the fit
+    pub samples: u64,
+    pub percentiles: Vec<PercentileCheck>,
+    /// Every candidate tried and its error, best first
+    pub candidates: Vec<(String, f64)>,
+}
+
+/// Fitted latency of one provider/model
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCalibration {
+    /// `provider/model`, or whichever of the two is known
+    pub name: String,
+    pub ttft: Option<DistributionFit>,
+    pub itl: Option<DistributionFit>,
+}
+
+impl ModelCalibration {
+    /// Latency profile using the fitted distributions
+    pub fn profile(&self) -> LatencyProfile {
+        let fitted = |fit: &Option<DistributionFit>| fit.as_ref().map(|f| f.distribution.clone());
+        LatencyProfile {
+            ttft: fitted(&self.ttft).unwrap_or_default(),
+            itl: fitted(&self.itl).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Calibrated latency of every provider/model in a trace
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Calibration {
+    pub models: Vec<ModelCalibration>,
+}
+
+impl Calibration {
+    /// Profiles by provider/model, ready for `latency.profiles`
+    pub fn profiles(&self) -> BTreeMap<String, LatencyProfile> {
+        self.models.iter().map(|m| (m.name.clone(), m.profile())).collect()
+    }
+
+    /// Goodness-of-fit report
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        for model in &self.models {
+            let _ = writeln!(report, "{}", model.name);
+            for (series, fit) in [("ttft", &model.ttft), ("itl", &model.itl)] {
+                let Some(fit) = fit else {
+                    let _ = writeln!(report, "  {:<5} no observations", series);
+                    continue;
+                };
+                let _ = writeln!(
+                    report,
+                    "  {:<5} {:<12} {} {:.3} over {} samples",
+                    series, fit.distribution.kind(), fit.metric.label(), fit.error, fit.samples,
+                );
+                let checks: Vec<String> = fit.percentiles.iter()
+                    .map(|c| format!("{} {:.1}/{:.1} ms", c.percentile, c.observed_ms, c.fitted_ms))
+                    .collect();
+                let _ = writeln!(report, "        observed/fitted: {}", checks.join(", "));
+                let candidates: Vec<String> = fit.candidates.iter()
+                    .map(|(name, error)| format!("{} {:.3}", name, error))
+                    .collect();
+                let _ = writeln!(report, "        candidates: {}", candidates.join(", "));
+            }
+        }
+        report
+    }
+}
+
+/// Fit TTFT and ITL distributions for every provider/model in `traces`
+pub fn calibrate(traces: &LatencyTraces) -> Calibration {
+    let models = match traces {
+        LatencyTraces::Observations(observations) => {
+            let mut series: BTreeMap<String, (Vec<f64>, Vec<f64>)> = BTreeMap::new();
+            for observation in observations {
+                let name = model_name(observation.provider.as_deref(), observation.model.as_deref());
+                let (ttft, itl) = series.entry(name).or_default();
+                ttft.extend(observation.ttft_ms);
+                itl.extend(observed_itl(observation));
+            }
+            series.into_iter()
+                .map(|(name, (ttft, itl))| ModelCalibration {
+                    name,
+                    ttft: fit_samples(&ttft),
+                    itl: fit_samples(&itl),
+                })
+                .collect()
+        }
+        LatencyTraces::Summaries(profiles) => profiles.iter()
+            .map(|profile| ModelCalibration {
+                name: model_name(Some(&profile.provider), Some(&profile.model)),
+                ttft: fit_summary(&profile.ttft),
+                itl: fit_summary(&profile.itl),
+            })
+            .collect(),
+    };
+    Calibration { models }
+}
+
+fn model_name(provider: Option<&str>, model: Option<&str>) -> String {
+    match (provider.filter(|p| !p.is_empty()), model.filter(|m| !m.is_empty())) {
+        (Some(provider), Some(model)) => format!("{}/{}", provider, model),
+        (None, Some(name)) | (Some(name), None) => name.to_string(),
+        (None, None) => "default".to_string(),
+    }
+}
+
+/// ITLs of an observation, or its average ITL derived from the total
+fn observed_itl(observation: &LatencyObservation) -> Vec<f64> {
+    if !observation.itl_ms.is_empty() {
+        return observation.itl_ms.clone();
+    }
+    match (observation.total_ms, observation.ttft_ms, observation.output_tokens) {
+        (Some(total), Some(ttft), Some(tokens)) if tokens > 1 && total >= ttft => {
+            vec![(total - ttft) / (tokens - 1) as f64]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Fit raw samples, scoring candidates by Kolmogorov-Smirnov distance
+fn fit_samples(values: &[f64]) -> Option<DistributionFit> {
+    let mut observed: Vec<f64> = values.iter().copied().filter(|v| v.is_finite() && *v >= 0.0).collect();
+    if observed.is_empty() {
+        return None;
+    }
+    observed.sort_by(f64::total_cmp);
+
+    let n = observed.len() as f64;
+    let mean = observed.iter().sum::<f64>() / n;
+    let std_dev = (observed.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+    let mut candidates = Vec::new();
+    if std_dev > 0.0 {
+        candidates.extend(moment_candidates(mean, std_dev));
+        candidates.push(log_normal_mle(&observed).unwrap_or(LatencyDistribution::log_normal(mean, std_dev)));
+        candidates.extend(pareto_mle(&observed));
+    } else {
+        candidates.push(LatencyDistribution::fixed(mean));
+    }
+
+    let mut table = vec![("p0", 0.0), ("p50", 0.5), ("p90", 0.9), ("p99", 0.99)];
+    if observed.len() >= 1000 {
+        table.push(("p999", 0.999));
+    }
+    table.push(("p100", 1.0));
+    let fallback = (observed.len() >= 20).then(|| {
+        let points: Vec<(&str, f64)> = table.iter().map(|(p, q)| (*p, quantile(&observed, *q))).collect();
+        LatencyDistribution::percentiles(&points)
+    });
+
+    let (distribution, error, candidates) = choose(candidates, fallback, |d| ks_distance(&observed, &simulate(d)));
+    let fitted = simulate(&distribution);
+    let percentiles = [("p50", 0.5), ("p90", 0.9), ("p99", 0.99)].iter()
+        .map(|(p, q)| PercentileCheck {
+            percentile: p.to_string(),
+            observed_ms: quantile(&observed, *q),
+            fitted_ms: quantile(&fitted, *q),
+        })
+        .collect();
+
+    Some(DistributionFit {
+        distribution,
+        metric: FitMetric::KolmogorovSmirnov,
+        error,
+        samples: observed.len() as u64,
+        percentiles,
+        candidates,
+    })
+}
+
+/// Fit summary statistics, scoring candidates by their worst percentile error
+fn fit_summary(data: &LatencyDistributionData) -> Option<DistributionFit> {
+    if data.mean_ms <= 0.0 && data.p50_ms <= 0.0 {
+        return None;
+    }
+
+    let observed = [("p50", 0.5, data.p50_ms), ("p95", 0.95, data.p95_ms), ("p99", 0.99, data.p99_ms)];
+    let candidates = if data.std_dev_ms > 0.0 {
+        let mut candidates = moment_candidates(data.mean_ms, data.std_dev_ms);
+        candidates.push(LatencyDistribution::log_normal(data.mean_ms, data.std_dev_ms));
+        candidates
+    } else {
+        vec![LatencyDistribution::fixed(data.mean_ms)]
+    };
+
+    let mut table = Vec::new();
+    if data.min_ms <= data.p50_ms {
+        table.push(("p0", data.min_ms));
+    }
+    table.extend(observed.iter().map(|(p, _, ms)| (*p, *ms)));
+    if data.max_ms >= data.p99_ms {
+        table.push(("p100", data.max_ms));
+    }
+    let fallback = Some(LatencyDistribution::percentiles(&table));
+
+    let error = |distribution: &LatencyDistribution| {
+        let fitted = simulate(distribution);
+        observed.iter()
+            .filter(|(_, _, ms)| *ms > 0.0)
+            .map(|(_, q, ms)| (quantile(&fitted, *q) - ms).abs() / ms)
+            .fold(0.0, f64::max)
+    };
+    let (distribution, fit_error, candidates) = choose(candidates, fallback, error);
+    let fitted = simulate(&distribution);
+    let percentiles = observed.iter()
+        .map(|(p, q, ms)| PercentileCheck {
+            percentile: p.to_string(),
+            observed_ms: *ms,
+            fitted_ms: quantile(&fitted, *q),
+        })
+        .collect();
+
+    Some(DistributionFit {
+        distribution,
+        metric: FitMetric::PercentileError,
+        error: fit_error,
+        samples: data.sample_count,
+        percentiles,
+        candidates,
+    })
+}
+
+/// Distributions matching a mean and standard deviation
+fn moment_candidates(mean: f64, std_dev: f64) -> Vec<LatencyDistribution> {
+    let variance = std_dev * std_dev;
+    let mut candidates = vec![LatencyDistribution::normal(mean, std_dev)];
+    if mean > 0.0 {
+        candidates.push(LatencyDistribution::exponential(mean));
+        candidates.push(LatencyDistribution::gamma(mean * mean / variance, variance / mean));
+
+        // Shape from the coefficient of variation (Justus' approximation)
+        let shape = (std_dev / mean).powf(-1.086);
+        candidates.push(LatencyDistribution::weibull(mean / gamma_fn(1.0 + 1.0 / shape), shape));
+    }
+    candidates
+}
+
+/// Log-normal fitted to the logs of the samples, as mean and standard deviation
+fn log_normal_mle(sorted: &[f64]) -> Option<LatencyDistribution> {
+    if sorted.first().map_or(true, |min| *min <= 0.0) {
+        return None;
+    }
+    let n = sorted.len() as f64;
+    let mu = sorted.iter().map(|v| v.ln()).sum::<f64>() / n;
+    let sigma2 = sorted.iter().map(|v| (v.ln() - mu).powi(2)).sum::<f64>() / n;
+
+    let mean = (mu + sigma2 / 2.0).exp();
+    Some(LatencyDistribution::log_normal(mean, mean * (sigma2.exp() - 1.0).sqrt()))
+}
+
+/// Pareto with the smallest sample as scale and the maximum-likelihood shape
+fn pareto_mle(sorted: &[f64]) -> Option<LatencyDistribution> {
+    let scale = *sorted.first()?;
+    if scale <= 0.0 {
+        return None;
+    }
+    let log_sum: f64 = sorted.iter().map(|v| (v / scale).ln()).sum();
+    (log_sum > 0.0).then(|| LatencyDistribution::pareto(scale, sorted.len() as f64 / log_sum))
+}
+
+/// Score the valid candidates, adding `fallback` when none fits well; best first
+fn choose(
+    candidates: Vec<LatencyDistribution>,
+    fallback: Option<LatencyDistribution>,
+    error: impl Fn(&LatencyDistribution) -> f64,
+) -> (LatencyDistribution, f64, Vec<(String, f64)>) {
+    let score = |candidates: Vec<LatencyDistribution>| {
+        candidates.into_iter()
+            .filter(|d| d.validate().is_ok())
+            .map(|d| {
+                let e = error(&d);
+                (d, e)
+            })
+            .filter(|(_, e)| e.is_finite())
+            .collect::<Vec<_>>()
+    };
+
+    let mut scored = score(candidates);
+    scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+    if scored.first().map_or(true, |(_, e)| *e > FALLBACK_ERROR) {
+        scored.extend(score(fallback.into_iter().collect()));
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+    }
+
+    let ranking = scored.iter().map(|(d, e)| (d.kind().to_string(), *e)).collect();
+    match scored.into_iter().next() {
+        Some((best, e)) => (best, e, ranking),
+        None => (LatencyDistribution::default(), f64::INFINITY, ranking),
+    }
+}
+
+/// Sorted draws from `distribution`
+fn simulate(distribution: &LatencyDistribution) -> Vec<f64> {
+    let mut draws = DistributionSampler::with_seed(CALIBRATION_SEED).sample_n(distribution, SIMULATED_DRAWS);
+    draws.sort_by(f64::total_cmp);
+    draws
+}
+
+/// Quantile `q` of sorted values, interpolating between neighbours
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (position - low as f64)
+}
+
+/// Largest gap between the empirical CDFs of two sorted samples
+fn ks_distance(a: &[f64], b: &[f64]) -> f64 {
+    let (mut i, mut j, mut distance) = (0, 0, 0.0f64);
+    while i < a.len() && j < b.len() {
+        let x = a[i].min(b[j]);
+        while i < a.len() && a[i] <= x {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= x {
+            j += 1;
+        }
+        distance = distance.max((i as f64 / a.len() as f64 - j as f64 / b.len() as f64).abs());
+    }
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(distribution: &LatencyDistribution, n: usize, seed: u64) -> Vec<f64> {
+        DistributionSampler::with_seed(seed).sample_n(distribution, n)
+    }
+
+    #[test]
+    fn test_recovers_log_normal_from_observations() {
+        let ttft = draws(&LatencyDistribution::log_normal(300.0, 120.0), 2000, 1);
+        let itl = draws(&LatencyDistribution::gamma(4.0, 8.0), 2000, 2);
+        let jsonl: String = ttft.iter().zip(&itl)
+            .map(|(t, i)| format!("{{\"provider\":\"openai\",\"model\":\"gpt-4\",\"ttft_ms\":{},\"itl_ms\":{}}}\n", t, i))
+            .collect();
+
+        let calibration = calibrate(&LatencyTraces::parse(&jsonl).unwrap());
+        let model = &calibration.models[0];
+        assert_eq!(model.name, "openai/gpt-4");
+
+        let ttft = model.ttft.as_ref().unwrap();
+        assert_eq!(ttft.distribution.kind(), "log_normal");
+        assert!(ttft.error < 0.05, "{}", ttft.error);
+        assert!((ttft.distribution.mean() - 300.0).abs() < 15.0);
+
+        let itl = model.itl.as_ref().unwrap();
+        assert!(matches!(itl.distribution.kind(), "gamma" | "weibull" | "log_normal"), "{:?}", itl.distribution);
+        assert!((itl.distribution.mean() - 32.0).abs() < 2.0);
+
+        let yaml = serde_yaml::to_string(&calibration.profiles()).unwrap();
+        let profiles: BTreeMap<String, LatencyProfile> = serde_yaml::from_str(&yaml).unwrap();
+        assert!(profiles["openai/gpt-4"].validate().is_ok());
+        assert!(calibration.report().contains("openai/gpt-4"));
+    }
+
+    #[test]
+    fn test_bimodal_falls_back_to_percentiles() {
+        let mut values = draws(&LatencyDistribution::normal(100.0, 5.0), 900, 3);
+        values.extend(draws(&LatencyDistribution::normal(2000.0, 50.0), 100, 4));
+        let observations = values.iter()
+            .map(|v| LatencyObservation { ttft_ms: Some(*v), ..Default::default() })
+            .collect();
+
+        let calibration = calibrate(&LatencyTraces::Observations(observations));
+        let ttft = calibration.models[0].ttft.as_ref().unwrap();
+        assert_eq!(calibration.models[0].name, "default");
+        assert_eq!(ttft.distribution.kind(), "empirical");
+        assert!(ttft.candidates.len() > 1);
+        assert!(calibration.models[0].itl.is_none());
+    }
+
+    #[test]
+    fn test_itl_derived_from_total() {
+        let line = r#"{"model": "claude", "ttft_ms": 200, "total_ms": 1190, "output_tokens": 100}"#;
+        let LatencyTraces::Observations(observations) = LatencyTraces::parse(line).unwrap() else {
+            panic!("expected observations");
+        };
+        assert_eq!(observed_itl(&observations[0]), vec![10.0]);
+    }
+
+    #[test]
+    fn test_latency_lens_summaries() {
+        let summary = |mean: f64, std_dev: f64, p50: f64, p95: f64, p99: f64| LatencyDistributionData {
+            min_ms: p50 / 3.0,
+            max_ms: p99 * 2.0,
+            mean_ms: mean,
+            std_dev_ms: std_dev,
+            p50_ms: p50,
+            p95_ms: p95,
+            p99_ms: p99,
+            sample_count: 10_000,
+        };
+        let profile = ConsumedLatencyProfile {
+            id: "lens-1".to_string(),
+            provider: "anthropic".to_string(),
+            model: "claude-3".to_string(),
+            ttft: summary(250.0, 100.0, 232.0, 440.0, 590.0),
+            itl: summary(30.0, 0.0, 30.0, 30.0, 30.0),
+            total_latency: LatencyDistributionData::default(),
+            captured_at: 0,
+        };
+        let json = serde_json::to_string(&vec![profile]).unwrap();
+
+        let calibration = calibrate(&LatencyTraces::parse(&json).unwrap());
+        let model = &calibration.models[0];
+        assert_eq!(model.name, "anthropic/claude-3");
+
+        let ttft = model.ttft.as_ref().unwrap();
+        assert_eq!(ttft.metric, FitMetric::PercentileError);
+        assert!(ttft.error < 0.1, "{}", calibration.report());
+        assert_eq!(model.itl.as_ref().unwrap().distribution.kind(), "fixed");
+
+        let pair = r#"{"ttft": {"min_ms": 50, "max_ms": 900, "mean_ms": 200, "std_dev_ms": 80,
+            "p50_ms": 185, "p95_ms": 350, "p99_ms": 460, "sample_count": 500}}"#;
+        let calibration = calibrate(&LatencyTraces::parse(pair).unwrap());
+        assert!(calibration.models[0].ttft.is_some());
+        assert!(calibration.models[0].itl.is_none());
+    }
+
+    #[test]
+    fn test_ks_distance() {
+        assert_eq!(ks_distance(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]), 0.0);
+        assert_eq!(ks_distance(&[1.0, 2.0], &[3.0, 4.0]), 1.0);
+        assert!((quantile(&[0.0, 10.0], 0.25) - 2.5).abs() < 1e-12);
+    }
+}
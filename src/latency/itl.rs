@@ -0,0 +1,136 @@
+//! Inter-token latency sequences
+//!
+//! Production streams don't space tokens independently: slow stretches
+//! persist, serving load moves between regimes, and now and then a stream
+//! stalls. A profile's ITLs are therefore generated as one sequence.
+
+use rand::prelude::*;
+
+use super::DistributionSampler;
+use crate::config::{ItlCorrelation, LatencyProfile};
+
+/// ITLs in milliseconds for `count` tokens of `profile`, before the multiplier
+pub fn itl_sequence(
+    profile: &LatencyProfile,
+    count: usize,
+    sampler: &DistributionSampler,
+    rng: &mut StdRng,
+) -> Vec<f64> {
+    let mean = profile.itl.mean();
+    let mut delays = Vec::with_capacity(count);
+    let mut previous: Option<f64> = None;
+    let mut slow = false;
+
+    for _ in 0..count {
+        let draw = sampler.sample_with_rng(&profile.itl, rng);
+
+        let mut delay = match &profile.itl_correlation {
+            ItlCorrelation::Independent => draw,
+            // Scaling the innovation by sqrt(1 - phi^2) keeps the ITL variance
+            ItlCorrelation::Ar1 { phi } => match previous {
+                Some(previous) => {
+                    let innovation = (1.0 - phi * phi).sqrt() * (draw - mean);
+                    (mean + phi * (previous - mean) + innovation).max(0.0)
+                }
+                None => draw,
+            },
+            ItlCorrelation::Regimes { slow_multiplier, enter_slow, exit_slow } => {
+                let switch = if slow { exit_slow } else { enter_slow };
+                if rng.gen_bool(switch.clamp(0.0, 1.0)) {
+                    slow = !slow;
+                }
+                if slow { draw * slow_multiplier } else { draw }
+            }
+        };
+        previous = Some(delay);
+
+        if let Some(stalls) = &profile.stalls {
+            if rng.gen_bool(stalls.probability.clamp(0.0, 1.0)) {
+                delay += sampler.sample_with_rng(&stalls.duration, rng);
+            }
+        }
+        delays.push(delay);
+    }
+
+    delays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LatencyDistribution, StallConfig};
+
+    fn sequence(profile: &LatencyProfile, count: usize) -> Vec<f64> {
+        itl_sequence(profile, count, &DistributionSampler::with_seed(7), &mut StdRng::seed_from_u64(7))
+    }
+
+    fn profile(itl_correlation: ItlCorrelation) -> LatencyProfile {
+        LatencyProfile {
+            itl: LatencyDistribution::normal(30.0, 10.0),
+            itl_correlation,
+            ..Default::default()
+        }
+    }
+
+    /// Correlation of each ITL with the next
+    fn lag1_correlation(values: &[f64]) -> f64 {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+        let covariance: f64 = values.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum();
+        covariance / variance
+    }
+
+    #[test]
+    fn test_ar1_correlates_neighbours() {
+        let independent = sequence(&profile(ItlCorrelation::Independent), 5000);
+        let correlated = sequence(&profile(ItlCorrelation::Ar1 { phi: 0.9 }), 5000);
+
+        assert!(lag1_correlation(&independent).abs() < 0.1);
+        assert!(lag1_correlation(&correlated) > 0.8);
+
+        // Correlation leaves the average ITL alone
+        let mean = correlated.iter().sum::<f64>() / correlated.len() as f64;
+        assert!((mean - 30.0).abs() < 3.0, "{}", mean);
+    }
+
+    #[test]
+    fn test_regimes_produce_slow_runs() {
+        let regimes = ItlCorrelation::Regimes { slow_multiplier: 10.0, enter_slow: 0.01, exit_slow: 0.1 };
+        let delays = sequence(&profile(regimes), 5000);
+
+        let slow: Vec<bool> = delays.iter().map(|d| *d > 150.0).collect();
+        let slow_count = slow.iter().filter(|s| **s).count();
+        let runs = slow.windows(2).filter(|w| w[1] && !w[0]).count();
+
+        // About 1/11 of tokens are slow, arriving in runs of about ten
+        assert!((250..750).contains(&slow_count), "{}", slow_count);
+        assert!(slow_count / runs.max(1) >= 5);
+    }
+
+    #[test]
+    fn test_stalls_follow_probability() {
+        let profile = LatencyProfile {
+            itl: LatencyDistribution::fixed(20.0),
+            stalls: Some(StallConfig { probability: 0.01, duration: LatencyDistribution::fixed(2000.0) }),
+            ..Default::default()
+        };
+        let delays = sequence(&profile, 10_000);
+
+        let stalls = delays.iter().filter(|d| **d > 1000.0).count();
+        assert!((50..150).contains(&stalls), "{}", stalls);
+        assert!(delays.iter().all(|d| *d == 20.0 || *d == 2020.0));
+    }
+
+    #[test]
+    fn test_correlation_validation() {
+        assert!(ItlCorrelation::Ar1 { phi: 1.0 }.validate().is_err());
+        assert!(ItlCorrelation::Regimes { slow_multiplier: 2.0, enter_slow: 1.5, exit_slow: 0.1 }.validate().is_err());
+
+        let heavy_tail = LatencyProfile {
+            itl: LatencyDistribution::pareto(10.0, 1.0),
+            itl_correlation: ItlCorrelation::Ar1 { phi: 0.5 },
+            ..Default::default()
+        };
+        assert!(heavy_tail.validate().is_err());
+    }
+}
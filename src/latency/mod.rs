@@ -3,18 +3,28 @@
 //! Provides realistic latency simulation using statistical distributions
 //! for Time-To-First-Token (TTFT) and Inter-Token-Latency (ITL).
 
+mod calibrate;
+mod itl;
 mod sampler;
 
+pub use calibrate::*;
+pub use itl::*;
 pub use sampler::*;
 
+use rand::prelude::*;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use crate::config::{LatencyConfig, LatencyProfile};
+use crate::config::{LatencyConfig, LatencyDistribution, LatencyProfile};
 
 /// Latency simulator that generates realistic timing
 #[derive(Debug, Clone)]
 pub struct LatencySimulator {
     config: LatencyConfig,
     rng_seed: Option<u64>,
+    /// Draw streams handed out, so each seeded sample or schedule differs
+    stream_counter: Arc<AtomicU64>,
 }
 
 impl LatencySimulator {
@@ -23,17 +33,42 @@ impl LatencySimulator {
         Self {
             config,
             rng_seed: None,
+            stream_counter: Arc::default(),
         }
     }
 
     /// Create a latency simulator with a fixed seed for deterministic behavior
+    ///
+    /// Samples and schedules draw from successive streams derived from the
+    /// seed: a run repeats exactly, while its latencies still vary.
     pub fn with_seed(config: LatencyConfig, seed: u64) -> Self {
         Self {
             config,
             rng_seed: Some(seed),
+            stream_counter: Arc::default(),
         }
     }
 
+    /// Simulator sampling from `seed` when given, such as a per-request seed
+    pub fn seeded(&self, seed: Option<u64>) -> Cow<'_, Self> {
+        self.for_request(seed, 1.0)
+    }
+
+    /// Simulator sampling from `seed` when given, with its multiplier scaled
+    /// by `factor`, such as that of a timeline phase
+    pub fn for_request(&self, seed: Option<u64>, factor: f64) -> Cow<'_, Self> {
+        if seed.is_none() && factor == 1.0 {
+            return Cow::Borrowed(self);
+        }
+
+        let mut simulator = match seed {
+            Some(seed) => Self::with_seed(self.config.clone(), seed),
+            None => self.clone(),
+        };
+        simulator.config.multiplier *= factor;
+        Cow::Owned(simulator)
+    }
+
     /// Check if latency simulation is enabled
     pub fn is_enabled(&self) -> bool {
         self.config.enabled
@@ -44,6 +79,11 @@ impl LatencySimulator {
         self.config.multiplier
     }
 
+    /// Pacer sleeping out simulated delays, compressed under virtual time
+    pub fn pacer(&self) -> Pacer {
+        Pacer::new(self.config.sleep_scale())
+    }
+
     /// Generate a TTFT (Time to First Token) duration
     pub fn sample_ttft(&self, profile_name: Option<&str>) -> Duration {
         if !self.config.enabled {
@@ -54,11 +94,25 @@ impl LatencySimulator {
             .and_then(|name| self.config.get_profile(name))
             .unwrap_or_else(|| self.config.default_profile());
 
-        let sampler = self.create_sampler();
-        let base_ms = sampler.sample(&profile.ttft);
-        let adjusted_ms = base_ms * self.config.multiplier;
+        self.draw(&profile.ttft, &self.create_sampler())
+    }
 
-        Duration::from_micros((adjusted_ms * 1000.0).max(0.0) as u64)
+    /// Generate a TTFT including prefill of a prompt of `input_tokens`
+    pub fn sample_ttft_for(&self, profile_name: Option<&str>, input_tokens: usize) -> Duration {
+        self.sample_ttft(profile_name) + self.prefill(profile_name, input_tokens)
+    }
+
+    /// Prompt processing time for `input_tokens`
+    pub fn prefill(&self, profile_name: Option<&str>, input_tokens: usize) -> Duration {
+        if !self.config.enabled {
+            return Duration::ZERO;
+        }
+
+        let profile = profile_name
+            .and_then(|name| self.config.get_profile(name))
+            .unwrap_or_else(|| self.config.default_profile());
+
+        millis(profile.prefill_ms(input_tokens) * self.config.multiplier)
     }
 
     /// Generate an ITL (Inter-Token Latency) duration
@@ -71,11 +125,21 @@ impl LatencySimulator {
             .and_then(|name| self.config.get_profile(name))
             .unwrap_or_else(|| self.config.default_profile());
 
-        let sampler = self.create_sampler();
-        let base_ms = sampler.sample(&profile.itl);
-        let adjusted_ms = base_ms * self.config.multiplier;
+        self.draw(&profile.itl, &self.create_sampler())
+    }
+
+    /// Generate an extra delay from `dist`, such as a cold-start penalty
+    pub fn sample_delay(&self, dist: &LatencyDistribution) -> Duration {
+        if !self.config.enabled {
+            return Duration::ZERO;
+        }
+
+        self.draw(dist, &self.create_sampler())
+    }
 
-        Duration::from_micros((adjusted_ms * 1000.0).max(0.0) as u64)
+    /// One multiplied draw from `dist`
+    fn draw(&self, dist: &LatencyDistribution, sampler: &DistributionSampler) -> Duration {
+        millis(sampler.sample(dist) * self.config.multiplier)
     }
 
     /// Get the fixed overhead for a profile
@@ -91,28 +155,75 @@ impl LatencySimulator {
         Duration::from_micros((profile.overhead.as_micros() as f64 * self.config.multiplier) as u64)
     }
 
+    /// Get the overhead for a profile and a response of `output_tokens`
+    pub fn overhead_for(&self, profile_name: Option<&str>, output_tokens: usize) -> Duration {
+        if !self.config.enabled {
+            return Duration::ZERO;
+        }
+
+        let profile = profile_name
+            .and_then(|name| self.config.get_profile(name))
+            .unwrap_or_else(|| self.config.default_profile());
+
+        millis(profile.overhead_ms(output_tokens) * self.config.multiplier)
+    }
+
     /// Generate a complete latency schedule for a streaming response
     pub fn generate_schedule(&self, token_count: usize, profile_name: Option<&str>) -> LatencySchedule {
-        let ttft = self.sample_ttft(profile_name);
-        let overhead = self.overhead(profile_name);
+        self.generate_schedule_for(0, token_count, profile_name)
+    }
 
-        let mut token_delays = Vec::with_capacity(token_count);
-        for _ in 0..token_count {
-            token_delays.push(self.sample_itl(profile_name));
+    /// Generate a latency schedule for a response of `token_count` tokens
+    /// to a prompt of `input_tokens`
+    pub fn generate_schedule_for(
+        &self,
+        input_tokens: usize,
+        token_count: usize,
+        profile_name: Option<&str>,
+    ) -> LatencySchedule {
+        if !self.config.enabled {
+            return LatencySchedule::instant(token_count);
         }
 
+        let profile = profile_name
+            .and_then(|name| self.config.get_profile(name))
+            .unwrap_or_else(|| self.config.default_profile());
+
+        // One stream for the whole schedule, so its ITLs are independent draws
+        let sampler = self.create_sampler();
+        let ttft = self.draw(&profile.ttft, &sampler) + self.prefill(profile_name, input_tokens);
+        let overhead = self.overhead_for(profile_name, token_count);
+
+        let token_delays = if profile.itl_is_independent() {
+            (0..token_count).map(|_| self.draw(&profile.itl, &sampler)).collect()
+        } else {
+            itl_sequence(profile, token_count, &sampler, &mut self.create_rng())
+                .into_iter()
+                .map(|ms| millis(ms * self.config.multiplier))
+                .collect()
+        };
+
         LatencySchedule {
             ttft,
             overhead,
             token_delays,
+            queued: Duration::ZERO,
         }
     }
 
-    /// Create a distribution sampler
+    /// Create a distribution sampler on the next draw stream
     fn create_sampler(&self) -> DistributionSampler {
+        DistributionSampler::from_rng(self.create_rng())
+    }
+
+    /// Create a random number generator on the next draw stream
+    fn create_rng(&self) -> StdRng {
         match self.rng_seed {
-            Some(seed) => DistributionSampler::with_seed(seed),
-            None => DistributionSampler::new(),
+            Some(seed) => {
+                let stream = self.stream_counter.fetch_add(1, Ordering::Relaxed);
+                StdRng::seed_from_u64(stream_seed(seed, stream))
+            }
+            None => StdRng::from_entropy(),
         }
     }
 
@@ -127,6 +238,11 @@ impl LatencySimulator {
     }
 }
 
+/// Duration of a non-negative number of milliseconds
+fn millis(ms: f64) -> Duration {
+    Duration::from_micros((ms * 1000.0).max(0.0) as u64)
+}
+
 impl Default for LatencySimulator {
     fn default() -> Self {
         Self::new(LatencyConfig::default())
@@ -142,6 +258,9 @@ pub struct LatencySchedule {
     pub overhead: Duration,
     /// Delay for each token
     pub token_delays: Vec<Duration>,
+    /// Time already spent waiting for a serving slot, reported as part of
+    /// the TTFT but not slept again
+    pub queued: Duration,
 }
 
 impl LatencySchedule {
@@ -160,16 +279,123 @@ impl LatencySchedule {
         }
     }
 
+    /// Stretch every inter-token delay by `factor`
+    pub fn scale_token_delays(&mut self, factor: f64) {
+        for delay in &mut self.token_delays {
+            *delay = delay.mul_f64(factor.max(0.0));
+        }
+    }
+
+    /// Space every token `interval` apart
+    pub fn set_token_interval(&mut self, interval: Duration) {
+        self.token_delays.fill(interval);
+    }
+
     /// Create an instant schedule (no delays)
     pub fn instant(token_count: usize) -> Self {
         Self {
             ttft: Duration::ZERO,
             overhead: Duration::ZERO,
             token_delays: vec![Duration::ZERO; token_count],
+            queued: Duration::ZERO,
+        }
+    }
+
+    /// Simulated timing of the response this schedule paces
+    pub fn server_timing(&self) -> ServerTiming {
+        ServerTiming {
+            ttft: self.queued + self.delay_for_token(0),
+            total: self.queued + self.total_duration(),
         }
     }
 }
 
+/// Sleeps for simulated delays
+///
+/// Under virtual time only `scale` of each delay is slept, so latency-heavy
+/// scenarios run quickly while reporting the latency they simulate. Sleeps
+/// go through `tokio::time`, so a paused tokio clock skips them as well.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pacer {
+    scale: f64,
+}
+
+impl Pacer {
+    /// Pacer sleeping for `scale` of every delay
+    pub fn new(scale: f64) -> Self {
+        Self { scale: scale.max(0.0) }
+    }
+
+    /// Pacer sleeping for every delay in full
+    pub fn real_time() -> Self {
+        Self::new(1.0)
+    }
+
+    /// Real time slept per simulated second
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Real time slept for a simulated `delay`
+    pub fn scaled(&self, delay: Duration) -> Duration {
+        if self.scale == 1.0 {
+            delay
+        } else {
+            delay.mul_f64(self.scale)
+        }
+    }
+
+    /// Simulated time a `real` wait stands for; none when nothing is slept
+    pub fn unscaled(&self, real: Duration) -> Duration {
+        if self.scale == 1.0 {
+            real
+        } else if self.scale == 0.0 {
+            Duration::ZERO
+        } else {
+            real.div_f64(self.scale)
+        }
+    }
+
+    /// Part of a simulated `delay` that is not slept
+    pub fn skipped(&self, delay: Duration) -> Duration {
+        delay.saturating_sub(self.scaled(delay))
+    }
+
+    /// Sleep for a simulated `delay`
+    pub async fn sleep(&self, delay: Duration) {
+        let delay = self.scaled(delay);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+impl Default for Pacer {
+    fn default() -> Self {
+        Self::real_time()
+    }
+}
+
+/// Simulated timing of a response, reported as a `Server-Timing` header
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ServerTiming {
+    /// Time to the first token
+    pub ttft: Duration,
+    /// Time to the whole response
+    pub total: Duration,
+}
+
+impl ServerTiming {
+    /// Header value in milliseconds, e.g. `ttft;dur=210.5, total;dur=1432.0`
+    pub fn header_value(&self) -> String {
+        format!(
+            "ttft;dur={:.1}, total;dur={:.1}",
+            self.ttft.as_secs_f64() * 1000.0,
+            self.total.as_secs_f64() * 1000.0,
+        )
+    }
+}
+
 /// Statistics from latency sampling
 #[derive(Debug, Clone, Default)]
 pub struct LatencyStats {
@@ -219,6 +445,7 @@ impl LatencyStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{LatencyDistribution, PrefillConfig, StallConfig, VirtualTimeConfig};
 
     #[test]
     fn test_simulator_creation() {
@@ -248,6 +475,40 @@ mod tests {
         assert_eq!(ttft1, ttft2);
     }
 
+    #[test]
+    fn test_request_seed_overrides_global() {
+        let sim = LatencySimulator::with_seed(LatencyConfig::default(), 42);
+
+        assert_eq!(
+            sim.seeded(Some(7)).sample_ttft(Some("standard")),
+            LatencySimulator::with_seed(LatencyConfig::default(), 7).sample_ttft(Some("standard"))
+        );
+        assert_eq!(
+            sim.seeded(None).sample_ttft(Some("standard")),
+            LatencySimulator::with_seed(LatencyConfig::default(), 42).sample_ttft(Some("standard"))
+        );
+    }
+
+    #[test]
+    fn test_seeded_draws_follow_distribution() {
+        let sim = LatencySimulator::with_seed(LatencyConfig::default(), 42);
+        let replay = LatencySimulator::with_seed(LatencyConfig::default(), 42);
+
+        // Successive samples and the ITLs within a schedule all differ
+        let ttfts: Vec<Duration> = (0..5).map(|_| sim.sample_ttft(Some("standard"))).collect();
+        assert!(ttfts.windows(2).all(|w| w[0] != w[1]));
+        let schedule = sim.generate_schedule(50, Some("standard"));
+        let stats = LatencyStats::from_samples(
+            &schedule.token_delays.iter().map(|d| d.as_secs_f64() * 1000.0).collect::<Vec<_>>(),
+        );
+        assert!(stats.std_dev_ms > 1.0, "{:?}", stats);
+
+        // ...and the run replays exactly under the same seed
+        let replayed: Vec<Duration> = (0..5).map(|_| replay.sample_ttft(Some("standard"))).collect();
+        assert_eq!(ttfts, replayed);
+        assert_eq!(schedule.token_delays, replay.generate_schedule(50, Some("standard")).token_delays);
+    }
+
     #[test]
     fn test_latency_schedule() {
         let sim = LatencySimulator::default();
@@ -257,6 +518,43 @@ mod tests {
         assert!(schedule.total_duration() > Duration::ZERO);
     }
 
+    #[test]
+    fn test_prefill_scales_ttft() {
+        let mut config = LatencyConfig::default();
+        config.profiles.insert("prefill".to_string(), LatencyProfile {
+            ttft: LatencyDistribution::fixed(100.0),
+            itl: LatencyDistribution::fixed(10.0),
+            overhead: Duration::from_millis(5),
+            prefill: Some(PrefillConfig::linear(50.0)),
+            overhead_per_output_token_ms: 1.0,
+            ..Default::default()
+        });
+        let sim = LatencySimulator::new(config);
+
+        assert_eq!(sim.sample_ttft_for(Some("prefill"), 10), Duration::from_micros(100_500));
+        assert_eq!(sim.sample_ttft_for(Some("prefill"), 100_000), Duration::from_millis(5_100));
+
+        let schedule = sim.generate_schedule_for(2_000, 20, Some("prefill"));
+        assert_eq!(schedule.ttft, Duration::from_millis(200));
+        assert_eq!(schedule.overhead, Duration::from_millis(25));
+        assert_eq!(schedule.total_duration(), Duration::from_millis(425));
+    }
+
+    #[test]
+    fn test_schedule_stalls() {
+        let mut config = LatencyConfig { multiplier: 2.0, ..Default::default() };
+        config.profiles.insert("stalling".to_string(), LatencyProfile {
+            itl: LatencyDistribution::fixed(10.0),
+            stalls: Some(StallConfig { probability: 0.5, duration: LatencyDistribution::fixed(1000.0) }),
+            ..Default::default()
+        });
+        let schedule = LatencySimulator::with_seed(config, 3).generate_schedule(100, Some("stalling"));
+
+        let stalled = schedule.token_delays.iter().filter(|d| **d == Duration::from_millis(2020)).count();
+        assert!((25..75).contains(&stalled), "{}", stalled);
+        assert!(schedule.token_delays.iter().all(|d| *d == Duration::from_millis(20) || *d == Duration::from_millis(2020)));
+    }
+
     #[test]
     fn test_instant_schedule() {
         let schedule = LatencySchedule::instant(5);
@@ -264,6 +562,30 @@ mod tests {
         assert_eq!(schedule.token_delays.len(), 5);
     }
 
+    #[test]
+    fn test_virtual_time_pacer() {
+        let mut config = LatencyConfig::default();
+        assert_eq!(LatencySimulator::new(config.clone()).pacer(), Pacer::real_time());
+
+        config.virtual_time = VirtualTimeConfig { enabled: true, time_scale: 0.1 };
+        let pacer = LatencySimulator::new(config).pacer();
+        assert_eq!(pacer.scaled(Duration::from_secs(2)), Duration::from_millis(200));
+        assert_eq!(pacer.skipped(Duration::from_secs(2)), Duration::from_millis(1800));
+
+        let schedule = LatencySchedule {
+            ttft: Duration::from_millis(200),
+            overhead: Duration::from_millis(10),
+            token_delays: vec![Duration::from_millis(25); 4],
+            queued: Duration::ZERO,
+        };
+        assert_eq!(schedule.server_timing().header_value(), "ttft;dur=210.0, total;dur=310.0");
+        assert_eq!(pacer.unscaled(Duration::from_millis(50)), Duration::from_millis(500));
+
+        // A wait for a slot counts toward the TTFT
+        let queued = LatencySchedule { queued: Duration::from_millis(500), ..schedule };
+        assert_eq!(queued.server_timing().header_value(), "ttft;dur=710.0, total;dur=810.0");
+    }
+
     #[test]
     fn test_multiplier() {
         let mut config = LatencyConfig::default();
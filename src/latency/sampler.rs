@@ -1,34 +1,39 @@
 //! Distribution sampling implementations
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
 use rand::prelude::*;
-use rand_distr::{Distribution, Exp, LogNormal, Normal, Pareto, Uniform};
-use crate::config::LatencyDistribution;
+use rand_distr::{Distribution, Exp, Gamma, LogNormal, Normal, Pareto, Uniform, Weibull};
+use crate::config::{interpolate_percentile, LatencyDistribution};
 
 /// Sampler for latency distributions
 pub struct DistributionSampler {
-    rng: StdRng,
+    rng: Mutex<StdRng>,
 }
 
 impl DistributionSampler {
     /// Create a new sampler with random seed
     pub fn new() -> Self {
-        Self {
-            rng: StdRng::from_entropy(),
-        }
+        Self::from_rng(StdRng::from_entropy())
     }
 
     /// Create a sampler with a fixed seed for reproducibility
     pub fn with_seed(seed: u64) -> Self {
-        Self {
-            rng: StdRng::seed_from_u64(seed),
-        }
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    /// Create a sampler drawing from `rng`
+    pub fn from_rng(rng: StdRng) -> Self {
+        Self { rng: Mutex::new(rng) }
     }
 
     /// Sample a value from the distribution (in milliseconds)
+    ///
+    /// Each call advances the sampler, so successive samples are independent.
     pub fn sample(&self, dist: &LatencyDistribution) -> f64 {
-        // We need interior mutability for the RNG, so we use a local clone
-        let mut rng = self.rng.clone();
-        self.sample_with_rng(dist, &mut rng)
+        self.sample_with_rng(dist, &mut self.rng.lock())
     }
 
     /// Sample a value with an explicit RNG
@@ -81,12 +86,48 @@ impl DistributionSampler {
                 let pareto = Pareto::new(*scale_ms, *shape).unwrap();
                 pareto.sample(rng)
             }
+
+            LatencyDistribution::Gamma { shape, scale_ms } => {
+                if *shape <= 0.0 || *scale_ms <= 0.0 {
+                    return 0.0;
+                }
+                let gamma = Gamma::new(*shape, *scale_ms).unwrap();
+                gamma.sample(rng)
+            }
+
+            LatencyDistribution::Weibull { scale_ms, shape } => {
+                if *scale_ms <= 0.0 || *shape <= 0.0 {
+                    return scale_ms.max(0.0);
+                }
+                let weibull = Weibull::new(*scale_ms, *shape).unwrap();
+                weibull.sample(rng)
+            }
+
+            LatencyDistribution::Empirical { samples, percentiles } => {
+                if let Some(sample) = samples.choose(rng) {
+                    return *sample;
+                }
+                interpolate_percentile(percentiles.points(), rng.gen())
+            }
+
+            LatencyDistribution::Mixture { components } => {
+                let total: f64 = components.iter().map(|c| c.weight.max(0.0)).sum();
+                let mut pick = rng.gen::<f64>() * total;
+                for component in components {
+                    pick -= component.weight.max(0.0);
+                    if pick < 0.0 {
+                        return self.sample_with_rng(&component.distribution, rng);
+                    }
+                }
+                components.last()
+                    .map_or(0.0, |c| self.sample_with_rng(&c.distribution, rng))
+            }
         }
     }
 
     /// Sample multiple values and return statistics
     pub fn sample_n(&self, dist: &LatencyDistribution, n: usize) -> Vec<f64> {
-        let mut rng = self.rng.clone();
+        let mut rng = self.rng.lock();
         (0..n).map(|_| self.sample_with_rng(dist, &mut rng)).collect()
     }
 }
@@ -101,33 +142,28 @@ impl Default for DistributionSampler {
 #[derive(Clone)]
 pub struct ThreadSafeSampler {
     seed: Option<u64>,
+    sample_counter: Arc<AtomicU64>,
 }
 
 impl ThreadSafeSampler {
     /// Create a new thread-safe sampler
     pub fn new() -> Self {
-        Self { seed: None }
+        Self { seed: None, sample_counter: Arc::default() }
     }
 
     /// Create with a base seed (each sample will derive from this)
     pub fn with_seed(seed: u64) -> Self {
-        Self { seed: Some(seed) }
+        Self { seed: Some(seed), sample_counter: Arc::default() }
     }
 
-    /// Sample a value from the distribution
+    /// Sample a value from the distribution, seeded by the base seed and a
+    /// count of the samples drawn so far
     pub fn sample(&self, dist: &LatencyDistribution) -> f64 {
         let sampler = match self.seed {
-            Some(seed) => DistributionSampler::with_seed(seed),
-            None => DistributionSampler::new(),
-        };
-        sampler.sample(dist)
-    }
-
-    /// Sample TTFT with request-specific seed
-    pub fn sample_with_request_seed(&self, dist: &LatencyDistribution, request_id: u64) -> f64 {
-        let seed = self.seed.map(|s| s.wrapping_add(request_id));
-        let sampler = match seed {
-            Some(s) => DistributionSampler::with_seed(s),
+            Some(seed) => {
+                let n = self.sample_counter.fetch_add(1, Ordering::Relaxed);
+                DistributionSampler::with_seed(stream_seed(seed, n))
+            }
             None => DistributionSampler::new(),
         };
         sampler.sample(dist)
@@ -140,6 +176,12 @@ impl Default for ThreadSafeSampler {
     }
 }
 
+/// Seed of the `stream`th draw stream under `seed`
+pub fn stream_seed(seed: u64, stream: u64) -> u64 {
+    // Spread consecutive streams so nearby seeds don't share streams
+    seed ^ stream.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
 /// Jitter generator for adding variation
 pub struct JitterGenerator {
     base_jitter_ms: f64,
@@ -247,6 +289,22 @@ mod tests {
         assert_eq!(samples1, samples2);
     }
 
+    #[test]
+    fn test_seeded_samples_advance() {
+        let dist = LatencyDistribution::normal(100.0, 20.0);
+
+        let sampler = DistributionSampler::with_seed(42);
+        let first = sampler.sample(&dist);
+        assert_ne!(first, sampler.sample(&dist));
+        assert_eq!(first, DistributionSampler::with_seed(42).sample(&dist));
+
+        let shared = ThreadSafeSampler::with_seed(42);
+        let samples: Vec<f64> = (0..10).map(|_| shared.sample(&dist)).collect();
+        let replay = ThreadSafeSampler::with_seed(42);
+        assert!(samples.windows(2).all(|w| w[0] != w[1]));
+        assert_eq!(samples, (0..10).map(|_| replay.sample(&dist)).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_thread_safe_sampler() {
         let sampler = ThreadSafeSampler::with_seed(42);
@@ -298,4 +356,48 @@ mod tests {
             assert!(*sample >= 10.0);
         }
     }
+
+    fn sample_mean(dist: &LatencyDistribution) -> f64 {
+        let samples = DistributionSampler::with_seed(42).sample_n(dist, 20_000);
+        samples.iter().sum::<f64>() / samples.len() as f64
+    }
+
+    #[test]
+    fn test_gamma_and_weibull_match_their_means() {
+        for dist in [LatencyDistribution::gamma(2.0, 50.0), LatencyDistribution::weibull(100.0, 1.5)] {
+            let expected = dist.mean();
+            assert!((sample_mean(&dist) - expected).abs() < expected * 0.03, "{:?}", dist);
+        }
+        assert!((LatencyDistribution::weibull(100.0, 1.0).mean() - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_percentile_table_reproduces_percentiles() {
+        let dist = LatencyDistribution::percentiles(&[("p50", 120.0), ("p90", 300.0), ("p99", 900.0), ("p999", 2500.0)]);
+        let mut samples = DistributionSampler::with_seed(42).sample_n(&dist, 100_000);
+        samples.sort_by(f64::total_cmp);
+
+        let percentile = |q: f64| samples[(q * samples.len() as f64) as usize];
+        assert!((percentile(0.5) - 120.0).abs() < 5.0);
+        assert!((percentile(0.9) - 300.0).abs() < 15.0);
+        assert!((percentile(0.99) - 900.0).abs() < 60.0);
+        assert!((sample_mean(&dist) - dist.mean()).abs() < dist.mean() * 0.03);
+    }
+
+    #[test]
+    fn test_empirical_samples_and_mixture() {
+        let observed = LatencyDistribution::empirical(vec![10.0, 20.0, 60.0]);
+        let samples = DistributionSampler::with_seed(1).sample_n(&observed, 100);
+        assert!(samples.iter().all(|s| [10.0, 20.0, 60.0].contains(s)));
+        assert_eq!(observed.mean(), 30.0);
+
+        let mixture = LatencyDistribution::mixture(vec![
+            (0.95, LatencyDistribution::fixed(100.0)),
+            (0.05, LatencyDistribution::fixed(2000.0)),
+        ]);
+        let samples = DistributionSampler::with_seed(1).sample_n(&mixture, 10_000);
+        let slow = samples.iter().filter(|s| **s == 2000.0).count();
+        assert!((400..600).contains(&slow), "{}", slow);
+        assert!((mixture.mean() - 195.0).abs() < 1e-9);
+    }
 }
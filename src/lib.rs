@@ -39,12 +39,13 @@ pub mod sdk;
 pub mod security;
 pub mod server;
 pub mod telemetry;
+pub mod tokenizer;
 pub mod types;
 
 pub use config::SimulatorConfig;
 pub use engine::SimulationEngine;
 pub use error::{SimulationError, SimulatorResult};
-pub use server::run_server;
+pub use server::{run_server, run_server_with_engine};
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -64,4 +64,21 @@ mod tests {
 
         assert!(matches!(cli.command, Commands::Health(_)));
     }
+
+    #[test]
+    fn test_cli_calibrate() {
+        let cli = Cli::try_parse_from([
+            "llm-simulator",
+            "calibrate",
+            "traces.jsonl",
+            "--output", "profiles.yaml",
+        ]).unwrap();
+
+        if let Commands::Calibrate(cmd) = cli.command {
+            assert_eq!(cmd.input.to_str(), Some("traces.jsonl"));
+            assert_eq!(cmd.format, "yaml");
+        } else {
+            panic!("Expected Calibrate command");
+        }
+    }
 }
@@ -98,12 +98,54 @@ pub mod anthropic_utils {
         (system_prompt, anthropic_messages)
     }
 
+    /// Convert Anthropic content, including image blocks, to internal format
+    pub fn to_message_content(content: &AnthropicContent) -> MessageContent {
+        let blocks = match content {
+            AnthropicContent::Text(text) => return MessageContent::Text(text.clone()),
+            AnthropicContent::Blocks(blocks) => blocks,
+        };
+
+        let parts: Vec<ContentPart> = blocks.iter()
+            .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => Some(ContentPart::Text {
+                    text: block.get("text")?.as_str()?.to_string(),
+                }),
+                Some("image") => {
+                    let source = block.get("source")?;
+                    let image_url = match source.get("type").and_then(|t| t.as_str()) {
+                        Some("url") => ImageUrl {
+                            url: source.get("url")?.as_str()?.to_string(),
+                            detail: None,
+                        },
+                        _ => ImageUrl::inline(
+                            source.get("media_type")?.as_str()?,
+                            source.get("data")?.as_str()?,
+                        ),
+                    };
+                    Some(ContentPart::ImageUrl { image_url })
+                }
+                _ => None,
+            })
+            .collect();
+
+        if parts.iter().all(|p| matches!(p, ContentPart::Text { .. })) {
+            MessageContent::Text(content.text())
+        } else {
+            MessageContent::Parts(parts)
+        }
+    }
+
     /// Generate Anthropic-style message ID
     pub fn generate_message_id() -> String {
         let uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
         format!("msg_{}", &uuid[..24])
     }
 
+    /// Anthropic message ID for a chat completion ID
+    pub fn message_id(completion_id: &str) -> String {
+        format!("msg_{}", completion_id.strip_prefix("chatcmpl-").unwrap_or(completion_id))
+    }
+
     /// Convert OpenAI-style request to Anthropic format
     pub fn from_openai_request(request: &ChatCompletionRequest) -> AnthropicMessagesRequest {
         let (system, messages) = convert_messages(&request.messages);
@@ -122,6 +164,31 @@ pub mod anthropic_utils {
         }
     }
 
+    /// Map an OpenAI finish reason to an Anthropic stop reason
+    pub fn stop_reason(reason: FinishReason) -> &'static str {
+        match reason {
+            FinishReason::Stop => "end_turn",
+            FinishReason::Length => "max_tokens",
+            FinishReason::ContentFilter => "refusal",
+            FinishReason::ToolCalls | FinishReason::FunctionCall => "tool_use",
+        }
+    }
+
+    /// Anthropic `toolu_` id of an OpenAI `call_` id
+    pub fn tool_use_id(call_id: &str) -> String {
+        call_id.replacen("call_", "toolu_", 1)
+    }
+
+    /// Convert an OpenAI tool call to a `tool_use` content block
+    pub fn tool_use_block(call: &ResponseToolCall) -> AnthropicContentBlock {
+        AnthropicContentBlock::ToolUse {
+            id: tool_use_id(&call.id),
+            name: call.function.name.clone(),
+            input: serde_json::from_str(&call.function.arguments)
+                .unwrap_or_else(|_| serde_json::json!({})),
+        }
+    }
+
     /// Convert Anthropic response to OpenAI format
     pub fn to_openai_response(
         response: AnthropicMessagesResponse,
@@ -173,6 +240,33 @@ mod tests {
         assert!(id.starts_with("msg_"));
     }
 
+    #[test]
+    fn test_message_id_from_completion_id() {
+        assert_eq!(anthropic_utils::message_id("chatcmpl-abc123"), "msg_abc123");
+    }
+
+    #[test]
+    fn test_stop_reason_mapping() {
+        use crate::types::FinishReason;
+        assert_eq!(anthropic_utils::stop_reason(FinishReason::Stop), "end_turn");
+        assert_eq!(anthropic_utils::stop_reason(FinishReason::ContentFilter), "refusal");
+    }
+
+    #[test]
+    fn test_image_block_conversion() {
+        use crate::types::{AnthropicContent, MessageContent};
+
+        let content: AnthropicContent = serde_json::from_value(serde_json::json!([
+            {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAAA"}},
+            {"type": "text", "text": "Describe this"}
+        ])).unwrap();
+
+        let converted = anthropic_utils::to_message_content(&content);
+        assert!(matches!(converted, MessageContent::Parts(_)));
+        assert_eq!(converted.text(), "Describe this");
+        assert_eq!(converted.images().next().unwrap().url, "data:image/png;base64,AAAA");
+    }
+
     #[test]
     fn test_message_conversion() {
         use crate::types::Message;
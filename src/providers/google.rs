@@ -72,6 +72,7 @@ impl ProviderHandler for GoogleHandler {
 
 /// Google/Gemini-specific utilities
 pub mod google_utils {
+    use crate::engine::IdGenerator;
     use crate::types::*;
 
     /// Convert internal messages to Gemini format
@@ -117,16 +118,42 @@ pub mod google_utils {
         }
     }
 
+    /// Convert Gemini parts, including inline images, to internal format
+    pub fn parts_to_content(parts: &[GeminiPart]) -> MessageContent {
+        let images: Vec<ContentPart> = parts.iter()
+            .filter_map(|p| {
+                let data = p.inline_data.as_ref()?;
+                let mime_type = data.get("mime_type").or_else(|| data.get("mimeType"))?.as_str()?;
+                let image_url = ImageUrl::inline(mime_type, data.get("data")?.as_str()?);
+                Some(ContentPart::ImageUrl { image_url })
+            })
+            .collect();
+
+        let text = parts.iter()
+            .filter_map(|p| p.text.clone())
+            .collect::<Vec<_>>()
+            .join("");
+
+        if images.is_empty() {
+            MessageContent::Text(text)
+        } else {
+            let mut content = vec![ContentPart::Text { text }];
+            content.extend(images);
+            MessageContent::Parts(content)
+        }
+    }
+
     /// Convert Gemini response to OpenAI format
     pub fn to_openai_response(
         response: GeminiResponse,
         request_model: &str,
+        ids: &IdGenerator,
     ) -> ChatCompletionResponse {
         let content = response.candidates
             .first()
             .map(|c| {
                 c.content.parts.iter()
-                    .map(|p| p.text.clone())
+                    .filter_map(|p| p.text.clone())
                     .collect::<Vec<_>>()
                     .join("")
             })
@@ -136,7 +163,7 @@ pub mod google_utils {
             .map(|u| (u.prompt_token_count, u.candidates_token_count))
             .unwrap_or((0, 0));
 
-        let id = format!("chatcmpl-gemini-{}", uuid::Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
+        let id = ids.id("chatcmpl-gemini-", 12);
 
         ChatCompletionResponse::simple(
             id,
@@ -146,6 +173,19 @@ pub mod google_utils {
         )
     }
 
+    /// Convert an OpenAI function name and JSON arguments to a
+    /// `functionCall` part
+    pub fn function_call_part(name: &str, arguments: &str) -> GeminiResponsePart {
+        GeminiResponsePart {
+            function_call: Some(GeminiFunctionCall {
+                name: name.to_string(),
+                args: serde_json::from_str(arguments)
+                    .unwrap_or_else(|_| serde_json::json!({})),
+            }),
+            ..Default::default()
+        }
+    }
+
     /// Map Gemini finish reason to OpenAI format
     pub fn map_finish_reason(reason: Option<&str>) -> FinishReason {
         match reason {
@@ -156,6 +196,15 @@ pub mod google_utils {
             _ => FinishReason::Stop,
         }
     }
+
+    /// Map an OpenAI finish reason to Gemini format
+    pub fn to_gemini_finish_reason(reason: FinishReason) -> &'static str {
+        match reason {
+            FinishReason::Length => "MAX_TOKENS",
+            FinishReason::ContentFilter => "SAFETY",
+            _ => "STOP",
+        }
+    }
 }
 
 #[cfg(test)]
@@ -202,5 +251,19 @@ mod tests {
         assert_eq!(google_utils::map_finish_reason(Some("STOP")), FinishReason::Stop);
         assert_eq!(google_utils::map_finish_reason(Some("MAX_TOKENS")), FinishReason::Length);
         assert_eq!(google_utils::map_finish_reason(Some("SAFETY")), FinishReason::ContentFilter);
+        assert_eq!(google_utils::to_gemini_finish_reason(FinishReason::ContentFilter), "SAFETY");
+    }
+
+    #[test]
+    fn test_response_ids_follow_generator() {
+        use crate::engine::IdGenerator;
+        use crate::types::GeminiResponse;
+
+        let convert = |ids: &IdGenerator| {
+            google_utils::to_openai_response(GeminiResponse::new("Hi".into(), 1, 1), "gemini-pro", ids).id
+        };
+        let id = convert(&IdGenerator::seeded(7));
+        assert!(id.starts_with("chatcmpl-gemini-"));
+        assert_eq!(id, convert(&IdGenerator::seeded(7)));
     }
 }
@@ -6,10 +6,12 @@
 mod openai;
 mod anthropic;
 mod google;
+mod ollama;
 
 pub use openai::*;
 pub use anthropic::*;
 pub use google::*;
+pub use ollama::*;
 
 use async_trait::async_trait;
 use crate::types::Provider;
@@ -39,6 +41,7 @@ impl ProviderRegistry {
                 Box::new(OpenAIHandler::new()),
                 Box::new(AnthropicHandler::new()),
                 Box::new(GoogleHandler::new()),
+                Box::new(OllamaHandler::new()),
             ],
         }
     }
@@ -123,6 +126,7 @@ mod tests {
         assert_eq!(registry.find_provider("gpt-4"), Some(Provider::OpenAI));
         assert_eq!(registry.find_provider("claude-3-opus-20240229"), Some(Provider::Anthropic));
         assert_eq!(registry.find_provider("gemini-1.5-pro"), Some(Provider::Google));
+        assert_eq!(registry.find_provider("llama3.2:latest"), Some(Provider::Ollama));
     }
 
     #[test]
@@ -0,0 +1,199 @@
+//! Ollama provider implementation
+
+use async_trait::async_trait;
+use crate::types::Provider;
+use super::ProviderHandler;
+
+/// Ollama API handler
+pub struct OllamaHandler {
+    models: Vec<String>,
+}
+
+impl OllamaHandler {
+    pub fn new() -> Self {
+        Self {
+            models: vec![
+                // Llama family
+                "llama3.2".to_string(),
+                "llama3.1".to_string(),
+                "llama3".to_string(),
+                // Other popular local models
+                "mistral".to_string(),
+                "mixtral".to_string(),
+                "phi3".to_string(),
+                "qwen2.5".to_string(),
+                "gemma2".to_string(),
+                // Embedding models
+                "nomic-embed-text".to_string(),
+                "mxbai-embed-large".to_string(),
+            ],
+        }
+    }
+
+    /// Check if model ID matches a common Ollama model family
+    pub fn matches_pattern(model: &str) -> bool {
+        let lower = model.to_lowercase();
+        lower.starts_with("llama")
+            || lower.starts_with("mistral")
+            || lower.starts_with("mixtral")
+            || lower.starts_with("phi")
+            || lower.starts_with("qwen")
+            || lower.starts_with("gemma")
+            || lower.starts_with("nomic-embed")
+            || lower.starts_with("mxbai-embed")
+    }
+}
+
+impl Default for OllamaHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProviderHandler for OllamaHandler {
+    fn provider(&self) -> Provider {
+        Provider::Ollama
+    }
+
+    fn supports_model(&self, model: &str) -> bool {
+        let name = ollama_utils::strip_tag(model);
+        self.models.iter().any(|m| m == name)
+            || Self::matches_pattern(name)
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.models.clone()
+    }
+}
+
+/// Ollama-specific utilities
+pub mod ollama_utils {
+    use crate::types::*;
+
+    /// Strip the default `:latest` tag from an Ollama model name
+    pub fn strip_tag(model: &str) -> &str {
+        model.strip_suffix(":latest").unwrap_or(model)
+    }
+
+    /// Extract the model family used in `/api/tags` details
+    /// e.g., "llama3.2:1b" -> "llama"
+    pub fn model_family(model: &str) -> String {
+        strip_tag(model)
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect()
+    }
+
+    /// Convert an Ollama role string to the internal role
+    pub fn convert_role(role: &str) -> Role {
+        match role {
+            "system" => Role::System,
+            "assistant" => Role::Assistant,
+            "tool" => Role::Tool,
+            _ => Role::User,
+        }
+    }
+
+    /// Convert an OpenAI tool call to Ollama format
+    pub fn tool_call(call: &ResponseToolCall) -> OllamaToolCall {
+        OllamaToolCall {
+            function: OllamaFunctionCall {
+                name: call.function.name.clone(),
+                arguments: serde_json::from_str(&call.function.arguments)
+                    .unwrap_or_else(|_| serde_json::json!({})),
+            },
+        }
+    }
+
+    /// Convert Ollama chat request to OpenAI format
+    pub fn chat_to_openai_request(request: &OllamaChatRequest, model: &str) -> ChatCompletionRequest {
+        let messages = request.messages.iter()
+            .map(|m| Message {
+                role: convert_role(&m.role),
+                content: MessageContent::Text(m.content.clone()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                function_call: None,
+            })
+            .collect();
+
+        with_options(ChatCompletionRequest::new(model, messages), request.options.as_ref(), request.is_streaming())
+    }
+
+    /// Convert Ollama generate request to OpenAI format
+    pub fn generate_to_openai_request(request: &OllamaGenerateRequest, model: &str) -> ChatCompletionRequest {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(Message::system(system.clone()));
+        }
+        messages.push(Message::user(request.prompt.clone()));
+
+        with_options(ChatCompletionRequest::new(model, messages), request.options.as_ref(), request.is_streaming())
+    }
+
+    fn with_options(
+        mut request: ChatCompletionRequest,
+        options: Option<&OllamaOptions>,
+        stream: bool,
+    ) -> ChatCompletionRequest {
+        request.stream = stream;
+        if let Some(options) = options {
+            request.temperature = options.temperature;
+            request.top_p = options.top_p;
+            request.max_tokens = options.max_tokens();
+            request.seed = options.seed;
+            request.stop = options.stop.clone().map(StopSequence::Multiple);
+        }
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ollama_handler() {
+        let handler = OllamaHandler::new();
+
+        assert!(handler.supports_model("llama3.2"));
+        assert!(handler.supports_model("llama3.2:latest"));
+        assert!(handler.supports_model("mistral:7b"));
+        assert!(!handler.supports_model("gpt-4"));
+    }
+
+    #[test]
+    fn test_model_family() {
+        assert_eq!(ollama_utils::model_family("llama3.2:1b"), "llama");
+        assert_eq!(ollama_utils::model_family("nomic-embed-text"), "nomic");
+        assert_eq!(ollama_utils::strip_tag("mistral:latest"), "mistral");
+    }
+
+    #[test]
+    fn test_generate_conversion() {
+        use crate::types::*;
+
+        let request = OllamaGenerateRequest {
+            model: "llama3.2".to_string(),
+            prompt: "Why is the sky blue?".to_string(),
+            system: Some("Be brief".to_string()),
+            stream: None,
+            format: None,
+            raw: None,
+            options: Some(OllamaOptions {
+                num_predict: Some(64),
+                ..Default::default()
+            }),
+            keep_alive: None,
+        };
+
+        let converted = ollama_utils::generate_to_openai_request(&request, "llama3.2");
+
+        assert_eq!(converted.messages.len(), 2);
+        assert_eq!(converted.messages[0].role, Role::System);
+        assert_eq!(converted.max_tokens, Some(64));
+        assert!(converted.stream);
+    }
+}
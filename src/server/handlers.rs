@@ -1,19 +1,28 @@
 //! HTTP request handlers
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Multipart, Path, State},
+    Extension,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response, Sse},
     Json,
 };
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use serde::Serialize;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use super::protocol_faults::with_protocol_fault;
 use super::state::AppState;
-use super::streaming::create_sse_stream;
+use super::streaming::{
+    create_ollama_chat_stream, create_ollama_generate_stream, create_sse_stream, ollama_metrics,
+};
 use crate::config::SimulatorConfig;
-use crate::engine::EngineStats;
+use crate::engine::{image, EngineStats, SessionInfo, StreamingResponse};
 use crate::error::SimulationError;
+use crate::latency::ServerTiming;
+use crate::providers::{anthropic_utils, google_utils, ollama_utils};
+use crate::security::ApiKeyInfo;
 use crate::types::*;
 
 // ============== OpenAI Handlers ==============
@@ -21,24 +30,54 @@ use crate::types::*;
 /// POST /v1/chat/completions
 pub async fn openai_chat_completions(
     State(state): State<AppState>,
+    key: Option<Extension<ApiKeyInfo>>,
+    headers: HeaderMap,
     Json(request): Json<ChatCompletionRequest>,
 ) -> Result<Response, SimulationError> {
+    let session = session_id(&state, &headers);
+
     if request.stream {
         // Streaming response
-        let stream_response = state.engine.chat_completion_stream(&request).await?;
+        let stream_response = state.engine.chat_completion_stream_in_session(&request, session).await?;
+        let stream_response = for_caller(stream_response, key);
+        let (timing, fault) = (stream_response.schedule.server_timing(), stream_response.protocol_fault.clone());
         let stream = create_sse_stream(stream_response);
-        Ok(Sse::new(stream)
+        let response = Sse::new(stream)
             .keep_alive(
                 axum::response::sse::KeepAlive::new()
                     .interval(Duration::from_secs(15))
                     .text("keep-alive"),
             )
-            .into_response())
+            .into_response();
+        Ok(with_protocol_fault(with_server_timing(response, Some(timing)), fault))
     } else {
         // Non-streaming response
-        let response = state.engine.chat_completion(&request).await?;
-        Ok(Json(response).into_response())
+        let response = state.engine.chat_completion_in_session(&request, session).await?;
+        let (timing, fault) = (response.timing, response.protocol_fault.clone());
+        Ok(with_protocol_fault(with_server_timing(Json(response).into_response(), timing), fault))
+    }
+}
+
+/// Hold a stream to the per-key throughput ceiling of the caller's API key
+fn for_caller(response: StreamingResponse, key: Option<Extension<ApiKeyInfo>>) -> StreamingResponse {
+    match key {
+        Some(Extension(key)) => response.for_api_key(&key.id),
+        None => response,
+    }
+}
+
+/// Report the simulated latency of a response in a `Server-Timing` header
+fn with_server_timing(mut response: Response, timing: Option<ServerTiming>) -> Response {
+    if let Some(value) = timing.and_then(|t| HeaderValue::from_str(&t.header_value()).ok()) {
+        response.headers_mut().insert("server-timing", value);
     }
+    response
+}
+
+/// Session named by the configured session header
+fn session_id<'a>(state: &AppState, headers: &'a HeaderMap) -> Option<&'a str> {
+    headers.get(state.config.sessions.header.as_str())
+        .and_then(|h| h.to_str().ok())
 }
 
 /// POST /v1/embeddings
@@ -50,6 +89,15 @@ pub async fn openai_embeddings(
     Ok(Json(response))
 }
 
+/// POST /v1/moderations
+pub async fn openai_moderations(
+    State(state): State<AppState>,
+    Json(request): Json<ModerationRequest>,
+) -> Result<Json<ModerationResponse>, SimulationError> {
+    let response = state.engine.moderations(&request).await?;
+    Ok(Json(response))
+}
+
 /// GET /v1/models
 pub async fn list_models(
     State(state): State<AppState>,
@@ -67,11 +115,119 @@ pub async fn get_model(
         .ok_or_else(|| SimulationError::ModelNotFound(model_id))
 }
 
+/// POST /v1/images/generations
+pub async fn openai_image_generations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ImageGenerationRequest>,
+) -> Result<Json<ImageGenerationResponse>, SimulationError> {
+    let images = state.engine.image_generation(&request).await?;
+
+    // Placeholder URLs point back at this server
+    let host = headers.get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}:{}", state.config.server.host, state.config.server.port));
+    let inline = request.response_format.as_deref() == Some("b64_json");
+    let revised_prompt = (request.model == "dall-e-3").then(|| request.prompt.clone());
+
+    let data = images.iter()
+        .map(|image| ImageData {
+            url: (!inline).then(|| format!("http://{}{}", host, image.path())),
+            b64_json: inline.then(|| BASE64.encode(image.render())),
+            revised_prompt: revised_prompt.clone(),
+        })
+        .collect();
+
+    Ok(Json(ImageGenerationResponse {
+        created: state.engine.clock().timestamp(),
+        ..ImageGenerationResponse::new(data)
+    }))
+}
+
+/// GET /v1/images/placeholder/:seed/:file
+pub async fn openai_image_placeholder(
+    Path((seed, file)): Path<(u64, String)>,
+) -> Result<Response, SimulationError> {
+    let (width, height) = image::parse_placeholder_size(&file)
+        .ok_or_else(|| SimulationError::Validation {
+            message: format!("Invalid placeholder image: {}", file),
+            param: None,
+        })?;
+
+    let png = image::PlaceholderImage::new(seed, width, height).render();
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+/// POST /v1/audio/speech
+pub async fn openai_audio_speech(
+    State(state): State<AppState>,
+    Json(request): Json<SpeechRequest>,
+) -> Result<Response, SimulationError> {
+    let audio = state.engine.speech(&request).await?;
+    let content_type = match request.format() {
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        _ => "audio/pcm",
+    };
+
+    Ok(([(header::CONTENT_TYPE, content_type)], audio).into_response())
+}
+
+/// POST /v1/audio/transcriptions
+pub async fn openai_audio_transcriptions(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Response, SimulationError> {
+    let invalid = |e: axum::extract::multipart::MultipartError| SimulationError::Validation {
+        message: format!("Invalid multipart body: {}", e),
+        param: None,
+    };
+
+    let mut request = TranscriptionRequest::default();
+    while let Some(field) = multipart.next_field().await.map_err(invalid)? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            request.filename = field.file_name().map(str::to_string);
+            request.file = field.bytes().await.map_err(invalid)?.to_vec();
+            continue;
+        }
+
+        let value = field.text().await.map_err(invalid)?;
+        match name.as_str() {
+            "model" => request.model = value,
+            "language" => request.language = Some(value),
+            "prompt" => request.prompt = Some(value),
+            "response_format" => request.response_format = Some(value),
+            "temperature" => request.temperature = value.parse().ok(),
+            "timestamp_granularities[]" | "timestamp_granularities" => {
+                request.timestamp_granularities.push(value)
+            }
+            _ => {}
+        }
+    }
+
+    let transcript = state.engine.transcription(&request).await?;
+    let text_response = |body: String| {
+        ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], body).into_response()
+    };
+
+    Ok(match request.format() {
+        "text" => text_response(transcript.text),
+        "srt" => text_response(transcript.to_srt()),
+        "vtt" => text_response(transcript.to_vtt()),
+        "verbose_json" => Json(transcript).into_response(),
+        _ => Json(TranscriptionResponse { text: transcript.text }).into_response(),
+    })
+}
+
 // ============== Anthropic Handlers ==============
 
 /// POST /v1/messages
 pub async fn anthropic_messages(
     State(state): State<AppState>,
+    key: Option<Extension<ApiKeyInfo>>,
+    headers: HeaderMap,
     Json(request): Json<AnthropicMessagesRequest>,
 ) -> Result<Response, SimulationError> {
     // Convert to internal format
@@ -84,7 +240,7 @@ pub async fn anthropic_messages(
             };
             Message {
                 role,
-                content: MessageContent::Text(m.content.text()),
+                content: anthropic_utils::to_message_content(&m.content),
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
@@ -111,32 +267,56 @@ pub async fn anthropic_messages(
     };
 
     if request.stream {
-        let stream_response = state.engine.chat_completion_stream(&chat_request).await?;
+        let stream_response = state.engine
+            .chat_completion_stream_in_session(&chat_request, session_id(&state, &headers))
+            .await?;
+        let stream_response = for_caller(stream_response, key);
+        let (timing, fault) = (stream_response.schedule.server_timing(), stream_response.protocol_fault.clone());
         let stream = super::streaming::create_anthropic_sse_stream(stream_response, &request.model);
-        Ok(Sse::new(stream)
+        let response = Sse::new(stream)
             .keep_alive(
                 axum::response::sse::KeepAlive::new()
                     .interval(Duration::from_secs(15))
                     .text("ping"),
             )
-            .into_response())
+            .into_response();
+        Ok(with_protocol_fault(with_server_timing(response, Some(timing)), fault))
     } else {
-        let response = state.engine.chat_completion(&chat_request).await?;
+        let response = state.engine
+            .chat_completion_in_session(&chat_request, session_id(&state, &headers))
+            .await?;
+        let fault = response.protocol_fault.clone();
 
         // Convert to Anthropic format
         let content = response.choices.first()
             .and_then(|c| c.message.content.clone())
             .unwrap_or_default();
 
-        let anthropic_response = AnthropicMessagesResponse::new(
-            response.id,
+        let finish_reason = response.choices.first()
+            .and_then(|c| c.finish_reason)
+            .unwrap_or_default();
+
+        let mut anthropic_response = AnthropicMessagesResponse::new(
+            anthropic_utils::message_id(&response.id),
             request.model,
             content,
             response.usage.as_ref().map(|u| u.prompt_tokens).unwrap_or(0),
             response.usage.as_ref().map(|u| u.completion_tokens).unwrap_or(0),
         );
+        anthropic_response.stop_reason = Some(anthropic_utils::stop_reason(finish_reason).to_string());
 
-        Ok(Json(anthropic_response).into_response())
+        let tool_calls = response.choices.first()
+            .and_then(|c| c.message.tool_calls.clone())
+            .unwrap_or_default();
+        if !tool_calls.is_empty() {
+            anthropic_response.content.retain(|block| {
+                !matches!(block, AnthropicContentBlock::Text { text } if text.is_empty())
+            });
+            anthropic_response.content.extend(tool_calls.iter().map(anthropic_utils::tool_use_block));
+        }
+
+        let response = with_server_timing(Json(anthropic_response).into_response(), response.timing);
+        Ok(with_protocol_fault(response, fault))
     }
 }
 
@@ -147,7 +327,7 @@ pub async fn gemini_generate_content(
     State(state): State<AppState>,
     Path(model_id): Path<String>,
     Json(request): Json<GeminiRequest>,
-) -> Result<Json<GeminiResponse>, SimulationError> {
+) -> Result<Response, SimulationError> {
     // Convert to internal format
     let messages = request.contents.iter()
         .map(|c| {
@@ -156,13 +336,9 @@ pub async fn gemini_generate_content(
                 "model" => Role::Assistant,
                 _ => Role::User,
             };
-            let content = c.parts.iter()
-                .filter_map(|p| p.text.clone())
-                .collect::<Vec<_>>()
-                .join("");
             Message {
                 role,
-                content: MessageContent::Text(content),
+                content: google_utils::parts_to_content(&c.parts),
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
@@ -187,23 +363,43 @@ pub async fn gemini_generate_content(
     };
 
     let response = state.engine.chat_completion(&chat_request).await?;
+    let fault = response.protocol_fault.clone();
 
     let content = response.choices.first()
         .and_then(|c| c.message.content.clone())
         .unwrap_or_default();
 
-    let gemini_response = GeminiResponse::new(
+    let finish_reason = response.choices.first()
+        .and_then(|c| c.finish_reason)
+        .unwrap_or_default();
+
+    let mut gemini_response = GeminiResponse::new(
         content,
         response.usage.as_ref().map(|u| u.prompt_tokens).unwrap_or(0),
         response.usage.as_ref().map(|u| u.completion_tokens).unwrap_or(0),
     );
+    gemini_response.candidates[0].finish_reason =
+        Some(google_utils::to_gemini_finish_reason(finish_reason).to_string());
 
-    Ok(Json(gemini_response))
+    let tool_calls = response.choices.first()
+        .and_then(|c| c.message.tool_calls.clone())
+        .unwrap_or_default();
+    if !tool_calls.is_empty() {
+        let parts = &mut gemini_response.candidates[0].content.parts;
+        parts.retain(|part| !matches!(&part.text, Some(text) if text.is_empty()));
+        parts.extend(tool_calls.iter().map(|call| {
+            google_utils::function_call_part(&call.function.name, &call.function.arguments)
+        }));
+    }
+
+    let response = with_server_timing(Json(gemini_response).into_response(), response.timing);
+    Ok(with_protocol_fault(response, fault))
 }
 
 /// POST /v1/models/:model_id:streamGenerateContent
 pub async fn gemini_stream_generate_content(
     State(state): State<AppState>,
+    key: Option<Extension<ApiKeyInfo>>,
     Path(model_id): Path<String>,
     Json(request): Json<GeminiRequest>,
 ) -> Result<Response, SimulationError> {
@@ -215,13 +411,9 @@ pub async fn gemini_stream_generate_content(
                 "model" => Role::Assistant,
                 _ => Role::User,
             };
-            let content = c.parts.iter()
-                .filter_map(|p| p.text.clone())
-                .collect::<Vec<_>>()
-                .join("");
             Message {
                 role,
-                content: MessageContent::Text(content),
+                content: google_utils::parts_to_content(&c.parts),
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
@@ -245,9 +437,160 @@ pub async fn gemini_stream_generate_content(
     };
 
     let stream_response = state.engine.chat_completion_stream(&chat_request).await?;
+    let stream_response = for_caller(stream_response, key);
+    let (timing, fault) = (stream_response.schedule.server_timing(), stream_response.protocol_fault.clone());
     let stream = super::streaming::create_gemini_sse_stream(stream_response);
 
-    Ok(Sse::new(stream).into_response())
+    Ok(with_protocol_fault(with_server_timing(Sse::new(stream).into_response(), Some(timing)), fault))
+}
+
+// ============== Ollama Handlers ==============
+
+/// POST /api/chat
+pub async fn ollama_chat(
+    State(state): State<AppState>,
+    key: Option<Extension<ApiKeyInfo>>,
+    headers: HeaderMap,
+    Json(request): Json<OllamaChatRequest>,
+) -> Result<Response, SimulationError> {
+    let model = resolve_ollama_model(&state, &request.model);
+    let chat_request = ollama_utils::chat_to_openai_request(&request, &model);
+    let stream_response = state.engine
+        .chat_completion_stream_in_session(&chat_request, session_id(&state, &headers))
+        .await?;
+    let stream_response = for_caller(stream_response, key);
+    let fault = stream_response.protocol_fault.clone();
+
+    if request.is_streaming() {
+        let timing = stream_response.schedule.server_timing();
+        let stream = create_ollama_chat_stream(stream_response, &request.model);
+        let response = with_server_timing(ndjson_response(Body::from_stream(stream)), Some(timing));
+        Ok(with_protocol_fault(response, fault))
+    } else {
+        // Non-streaming Ollama responses arrive once generation has finished
+        let metrics = ollama_metrics(&stream_response);
+        let timing = stream_response.schedule.server_timing();
+        stream_response.pacer.sleep(timing.total).await;
+
+        let mut line = OllamaChatResponse {
+            created_at: stream_response.created,
+            ..OllamaChatResponse::new(request.model, stream_response.tokens.concat(), true, metrics)
+        };
+        if !stream_response.tool_calls.is_empty() {
+            line.message.tool_calls = Some(stream_response.tool_calls.iter().map(ollama_utils::tool_call).collect());
+        }
+        let response = Json(line).into_response();
+        Ok(with_protocol_fault(with_server_timing(response, Some(timing)), fault))
+    }
+}
+
+/// POST /api/generate
+pub async fn ollama_generate(
+    State(state): State<AppState>,
+    key: Option<Extension<ApiKeyInfo>>,
+    Json(request): Json<OllamaGenerateRequest>,
+) -> Result<Response, SimulationError> {
+    let model = resolve_ollama_model(&state, &request.model);
+    let chat_request = ollama_utils::generate_to_openai_request(&request, &model);
+    let stream_response = state.engine.chat_completion_stream(&chat_request).await?;
+    let stream_response = for_caller(stream_response, key);
+    let fault = stream_response.protocol_fault.clone();
+
+    if request.is_streaming() {
+        let timing = stream_response.schedule.server_timing();
+        let stream = create_ollama_generate_stream(stream_response, &request.model);
+        let response = with_server_timing(ndjson_response(Body::from_stream(stream)), Some(timing));
+        Ok(with_protocol_fault(response, fault))
+    } else {
+        let metrics = ollama_metrics(&stream_response);
+        let timing = stream_response.schedule.server_timing();
+        stream_response.pacer.sleep(timing.total).await;
+
+        let response = Json(OllamaGenerateResponse {
+            created_at: stream_response.created,
+            ..OllamaGenerateResponse::new(request.model, stream_response.tokens.concat(), true, metrics)
+        }).into_response();
+        Ok(with_protocol_fault(with_server_timing(response, Some(timing)), fault))
+    }
+}
+
+/// POST /api/embeddings
+pub async fn ollama_embeddings(
+    State(state): State<AppState>,
+    Json(request): Json<OllamaEmbeddingsRequest>,
+) -> Result<Json<OllamaEmbeddingsResponse>, SimulationError> {
+    let embeddings_request = EmbeddingsRequest {
+        model: resolve_ollama_model(&state, &request.model),
+        input: EmbeddingInput::Single(request.prompt),
+        encoding_format: None,
+        dimensions: None,
+        user: None,
+    };
+
+    let response = state.engine.embeddings(&embeddings_request).await?;
+    let embedding = response.data.into_iter()
+        .next()
+        .map(|e| e.embedding)
+        .unwrap_or_default();
+
+    Ok(Json(OllamaEmbeddingsResponse { embedding }))
+}
+
+/// POST /api/embed
+pub async fn ollama_embed(
+    State(state): State<AppState>,
+    Json(request): Json<OllamaEmbedRequest>,
+) -> Result<Json<OllamaEmbedResponse>, SimulationError> {
+    let start = Instant::now();
+    let embeddings_request = EmbeddingsRequest {
+        model: resolve_ollama_model(&state, &request.model),
+        input: request.input,
+        encoding_format: None,
+        dimensions: None,
+        user: None,
+    };
+
+    let response = state.engine.embeddings(&embeddings_request).await?;
+
+    Ok(Json(OllamaEmbedResponse {
+        model: request.model,
+        prompt_eval_count: response.usage.prompt_tokens,
+        embeddings: response.data.into_iter().map(|e| e.embedding).collect(),
+        total_duration: start.elapsed().as_nanos() as u64,
+        load_duration: 0,
+    }))
+}
+
+/// GET /api/tags
+pub async fn ollama_tags(
+    State(state): State<AppState>,
+) -> Json<OllamaTagsResponse> {
+    let mut names: Vec<&String> = state.config.models.keys().collect();
+    names.sort();
+
+    let modified_at = state.engine.clock().now();
+    let models = names.into_iter()
+        .map(|name| OllamaModelTag {
+            modified_at,
+            ..OllamaModelTag::new(name.as_str(), ollama_utils::model_family(name))
+        })
+        .collect();
+
+    Json(OllamaTagsResponse { models })
+}
+
+/// Map an Ollama model name to a configured model, accepting the implicit `:latest` tag
+fn resolve_ollama_model(state: &AppState, model: &str) -> String {
+    if state.engine.model_exists(model) {
+        model.to_string()
+    } else {
+        ollama_utils::strip_tag(model).to_string()
+    }
+}
+
+/// Wrap a body as a newline-delimited JSON response
+fn ndjson_response(body: Body) -> Response {
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], body).into_response()
 }
 
 // ============== Admin Handlers ==============
@@ -267,6 +610,30 @@ pub async fn reset_stats(
     StatusCode::NO_CONTENT
 }
 
+/// GET /admin/sessions
+pub async fn list_sessions(
+    State(state): State<AppState>,
+) -> Json<Vec<SessionInfo>> {
+    Json(state.engine.sessions())
+}
+
+/// GET /admin/sessions/:session_id
+pub async fn get_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionInfo>, SimulationError> {
+    state.engine.session(&session_id).map(Json)
+}
+
+/// DELETE /admin/sessions/:session_id
+pub async fn delete_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, SimulationError> {
+    state.engine.delete_session(&session_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// GET /admin/config
 pub async fn get_config(
     State(state): State<AppState>,
@@ -422,8 +789,14 @@ pub async fn ready_check(
 pub async fn metrics(
     State(state): State<AppState>,
 ) -> String {
-    // Update queue metrics before export
-    state.metrics.set_queue_depth(state.shutdown.in_flight_count());
+    // Update queue metrics before export, from the capacity model if any
+    match state.engine.capacity_status() {
+        Some(capacity) => {
+            state.metrics.set_queue_depth(capacity.queued as u64);
+            state.metrics.set_queue_capacity(capacity.max_queue as u64);
+        }
+        None => state.metrics.set_queue_depth(state.shutdown.in_flight_count()),
+    }
     state.metrics.export()
 }
 
@@ -555,8 +928,14 @@ pub async fn root() -> Json<RootResponse> {
         endpoints: vec![
             "/v1/chat/completions".to_string(),
             "/v1/embeddings".to_string(),
+            "/v1/moderations".to_string(),
+            "/v1/images/generations".to_string(),
+            "/v1/audio/speech".to_string(),
+            "/v1/audio/transcriptions".to_string(),
             "/v1/models".to_string(),
             "/v1/messages".to_string(),
+            "/api/chat".to_string(),
+            "/api/generate".to_string(),
             "/health".to_string(),
             "/metrics".to_string(),
         ],
@@ -6,17 +6,20 @@
 #![allow(dead_code)]
 
 use axum::{
-    extract::Request,
+    extract::{Request, State},
     middleware::Next,
     response::Response,
     http::StatusCode,
 };
 use std::time::Instant;
 use tracing::{info, warn};
-use uuid::Uuid;
 
-/// Request ID middleware
+use super::state::AppState;
+
+/// Request ID middleware, minting IDs from the engine's ID generator so
+/// seeded runs repeat them
 pub async fn request_id_middleware(
+    State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Response {
@@ -25,7 +28,7 @@ pub async fn request_id_middleware(
         .get("x-request-id")
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string())
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
+        .unwrap_or_else(|| state.engine.ids().id("req_", 32));
 
     request.extensions_mut().insert(RequestId(request_id.clone()));
 
@@ -1,19 +1,23 @@
 //! HTTP server implementation
 //!
 //! Provides the Axum-based HTTP server with all API endpoints
-//! for OpenAI, Anthropic, and Google API compatibility.
+//! for OpenAI, Anthropic, Google, and Ollama API compatibility.
 
 mod routes;
 mod middleware;
 mod handlers;
 mod state;
 mod streaming;
+mod realtime;
+mod protocol_faults;
 pub mod shutdown;
 
 pub use routes::*;
 pub use handlers::*;
 pub use state::*;
 pub use streaming::*;
+pub use realtime::*;
+pub use protocol_faults::*;
 pub use shutdown::*;
 
 use std::net::SocketAddr;
@@ -35,11 +39,18 @@ use crate::telemetry::{init_telemetry, SimulatorMetrics};
 
 /// Run the simulator server
 pub async fn run_server(config: SimulatorConfig) -> anyhow::Result<()> {
+    let engine = Arc::new(SimulationEngine::new(config.clone()));
+    run_server_with_engine(config, engine).await
+}
+
+/// Run the simulator server around a prepared engine
+pub async fn run_server_with_engine(
+    config: SimulatorConfig,
+    engine: Arc<SimulationEngine>,
+) -> anyhow::Result<()> {
     // Initialize telemetry
     init_telemetry(&config.telemetry)?;
 
-    // Create the simulation engine
-    let engine = Arc::new(SimulationEngine::new(config.clone()));
     let metrics = Arc::new(SimulatorMetrics::new());
 
     // Create security state
@@ -105,6 +116,8 @@ pub fn create_router(state: AppState, security: SecurityState) -> Router {
         .merge(routes::anthropic_routes())
         // Google compatible routes
         .merge(routes::google_routes())
+        // Ollama compatible routes
+        .merge(routes::ollama_routes())
         // Admin/utility routes
         .merge(routes::admin_routes())
         // Health and metrics
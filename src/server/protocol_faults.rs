@@ -0,0 +1,199 @@
+//! Protocol faults on real responses
+//!
+//! A protocol fault lets the request run as usual and breaks the response on
+//! its way out: the completion body or the stream's chunks are cut short,
+//! made invalid UTF-8 or stripped of their `data:` prefixes, or the headers
+//! mislabel them.
+
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::Response;
+use futures::StreamExt;
+
+use crate::error::{InjectedErrorType, ProtocolFault, SimulationError};
+
+/// Size of the header padding an oversized-headers fault adds
+const OVERSIZED_HEADER_BYTES: usize = 64 * 1024;
+
+/// Keys opening the generated text in every provider's bodies and chunks
+const TEXT_KEYS: [&[u8]; 3] = [b"\"content\":\"", b"\"text\":\"", b"\"response\":\""];
+
+/// Break `response` as `fault` dictates; untouched without one
+pub fn with_protocol_fault(response: Response, fault: Option<ProtocolFault>) -> Response {
+    let Some(fault) = fault else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    if let Ok(status) = StatusCode::from_u16(fault.status_code) {
+        parts.status = status;
+    }
+    let event_stream = parts.headers.get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"text/event-stream"));
+
+    let body = match fault.fault_type {
+        InjectedErrorType::TruncatedJson => rewrite_frames(body, truncate_json),
+        InjectedErrorType::InvalidUtf8 => rewrite_frames(body, break_utf8),
+        InjectedErrorType::MissingDataPrefix => rewrite_frames(body, strip_data_prefix),
+        InjectedErrorType::WrongContentType => {
+            parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+            body
+        }
+        InjectedErrorType::MislabeledGzip => {
+            parts.headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            body
+        }
+        InjectedErrorType::OversizedHeaders => {
+            if let Ok(padding) = HeaderValue::from_str(&"x".repeat(OVERSIZED_HEADER_BYTES)) {
+                parts.headers.insert("x-simulator-padding", padding);
+            }
+            body
+        }
+        InjectedErrorType::ErrorPayload => error_payload(fault, event_stream),
+        InjectedErrorType::RateLimit
+        | InjectedErrorType::Timeout
+        | InjectedErrorType::ServerError
+        | InjectedErrorType::BadGateway
+        | InjectedErrorType::ServiceUnavailable
+        | InjectedErrorType::AuthenticationError
+        | InjectedErrorType::InvalidRequest
+        | InjectedErrorType::ContextLengthExceeded => body,
+    };
+
+    // Rewritten bodies no longer match any length set for the original
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, body)
+}
+
+/// Pass every frame of `body` through `rewrite` until it reports a change
+/// that ends the fault
+fn rewrite_frames(body: Body, mut rewrite: impl FnMut(&mut Vec<u8>) -> bool + Send + 'static) -> Body {
+    let mut done = false;
+    Body::from_stream(body.into_data_stream().map(move |frame| {
+        frame.map(|bytes| {
+            if done {
+                return bytes;
+            }
+            let mut frame = bytes.to_vec();
+            done = rewrite(&mut frame);
+            Bytes::from(frame)
+        })
+    }))
+}
+
+/// Cut the first JSON value in `frame` off halfway, keeping the line breaks
+/// that end an event or NDJSON line
+fn truncate_json(frame: &mut Vec<u8>) -> bool {
+    let Some(start) = frame.iter().position(|&b| b == b'{') else {
+        return false;
+    };
+    let end = frame.len() - frame.iter().rev().take_while(|&&b| b == b'\n').count();
+    frame.drain(start + (end - start) / 2..end);
+    true
+}
+
+/// Open the first generated text in `frame` with bytes no UTF-8 decoder accepts
+fn break_utf8(frame: &mut Vec<u8>) -> bool {
+    let at = TEXT_KEYS.iter()
+        .filter_map(|key| frame.windows(key.len()).position(|w| w == *key).map(|i| i + key.len()))
+        .min();
+    match at {
+        Some(at) => {
+            frame.splice(at..at, [0xFF, 0xFE]);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Drop the `data:` prefix of every line in `frame`; never done, so every
+/// event of a stream loses it
+fn strip_data_prefix(frame: &mut Vec<u8>) -> bool {
+    *frame = frame.split_inclusive(|&b| b == b'\n')
+        .flat_map(|line| match line.strip_prefix(b"data:".as_slice()) {
+            Some(rest) => rest.strip_prefix(b" ".as_slice()).unwrap_or(rest),
+            None => line,
+        })
+        .copied()
+        .collect();
+    false
+}
+
+/// The fault's error in place of the response, as an event on streams
+fn error_payload(fault: ProtocolFault, event_stream: bool) -> Body {
+    let error = SimulationError::Injected {
+        error_type: fault.fault_type,
+        message: fault.message,
+        status_code: fault.status_code,
+    };
+    let json = serde_json::to_string(&error.to_error_response()).unwrap_or_default();
+    if event_stream {
+        Body::from(format!("data: {}\n\n", json))
+    } else {
+        Body::from(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::{IntoResponse, Json};
+    use crate::error::ErrorResponse;
+    use crate::types::{ChatCompletionResponse, Usage};
+
+    /// A completion broken by `fault_type`, with its status, headers and body
+    async fn render(fault_type: InjectedErrorType) -> (StatusCode, axum::http::HeaderMap, Bytes) {
+        let completion = ChatCompletionResponse::simple(
+            "chatcmpl-1".to_string(),
+            "gpt-4".to_string(),
+            "Hello there".to_string(),
+            Usage::new(1, 2),
+        );
+        let fault = ProtocolFault { fault_type, status_code: 200, message: "Injected".to_string() };
+        let response = with_protocol_fault(Json(completion).into_response(), Some(fault));
+        let (status, headers) = (response.status(), response.headers().clone());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, headers, body)
+    }
+
+    #[tokio::test]
+    async fn test_protocol_faults_break_the_completion() {
+        let (status, headers, body) = render(InjectedErrorType::TruncatedJson).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        assert!(body.starts_with(b"{\"id\":\"chatcmpl-1\""));
+        assert!(serde_json::from_slice::<serde_json::Value>(&body).is_err());
+
+        let (_, _, body) = render(InjectedErrorType::InvalidUtf8).await;
+        assert!(std::str::from_utf8(&body).is_err());
+        assert!(String::from_utf8_lossy(&body).contains("\"content\":\"\u{FFFD}\u{FFFD}Hello there\""));
+
+        let (_, headers, body) = render(InjectedErrorType::MislabeledGzip).await;
+        assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+        let completion: ChatCompletionResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(completion.id, "chatcmpl-1");
+
+        let (_, headers, _) = render(InjectedErrorType::OversizedHeaders).await;
+        assert_eq!(headers["x-simulator-padding"].len(), OVERSIZED_HEADER_BYTES);
+
+        let (status, _, body) = render(InjectedErrorType::ErrorPayload).await;
+        assert_eq!(status, StatusCode::OK);
+        let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error.error_type, "api_error");
+    }
+
+    #[test]
+    fn test_stream_frames_are_rewritten() {
+        let mut event = b"data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\"}\n\n".to_vec();
+        assert!(!strip_data_prefix(&mut event));
+        assert_eq!(event, b"{\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\"}\n\n");
+
+        // Truncation keeps the event's framing
+        assert!(truncate_json(&mut event));
+        assert_eq!(event, b"{\"id\":\"chatcmpl-1\",\"object\n\n");
+
+        let mut keep_alive = b": keep-alive\n\n".to_vec();
+        assert!(!truncate_json(&mut keep_alive));
+        assert!(!break_utf8(&mut keep_alive));
+    }
+}
@@ -0,0 +1,444 @@
+//! OpenAI Realtime API over WebSocket
+//!
+//! Each connection holds one session and its conversation. Responses are
+//! generated by the simulation engine and paced by the request's
+//! `LatencySchedule`, so chaos and latency settings apply as they do for
+//! the HTTP endpoints.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use futures::{stream::SplitSink, SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use super::state::AppState;
+use crate::engine::audio::{ToneSynthesizer, AUDIO_PER_TOKEN};
+use crate::error::SimulationError;
+use crate::types::*;
+
+/// Model used when the client does not pass `?model=`
+pub const DEFAULT_REALTIME_MODEL: &str = "gpt-4o-realtime-preview";
+
+/// Query parameters of the realtime endpoint
+#[derive(Debug, Deserialize)]
+pub struct RealtimeParams {
+    pub model: Option<String>,
+}
+
+/// GET /v1/realtime (WebSocket upgrade)
+pub async fn openai_realtime(
+    State(state): State<AppState>,
+    Query(params): Query<RealtimeParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, SimulationError> {
+    let model = params.model.unwrap_or_else(|| DEFAULT_REALTIME_MODEL.to_string());
+    if !state.engine.model_exists(&model) {
+        return Err(SimulationError::ModelNotFound(model));
+    }
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        RealtimeConnection::new(socket, state, model).run().await;
+    }))
+}
+
+/// Error returned when the client has gone away
+struct Disconnected;
+
+/// Outcome of waiting for the next scheduled delta
+enum Interrupt {
+    Elapsed,
+    Cancelled,
+    Disconnected,
+}
+
+/// State of a single realtime WebSocket connection
+struct RealtimeConnection {
+    state: AppState,
+    session: RealtimeSession,
+    conversation: Vec<RealtimeItem>,
+    audio_buffer: Vec<u8>,
+    sender: SplitSink<WebSocket, WsMessage>,
+    incoming: mpsc::UnboundedReceiver<String>,
+    /// Client events received while a response was streaming
+    pending: VecDeque<String>,
+    reader: tokio::task::JoinHandle<()>,
+}
+
+impl RealtimeConnection {
+    fn new(socket: WebSocket, state: AppState, model: String) -> Self {
+        let (sender, mut receiver) = socket.split();
+        let (tx, incoming) = mpsc::unbounded_channel();
+
+        // Read the socket on a separate task so `response.cancel` can
+        // interrupt a response that is still streaming.
+        let reader = tokio::spawn(async move {
+            while let Some(Ok(message)) = receiver.next().await {
+                let text = match message {
+                    WsMessage::Text(text) => text,
+                    WsMessage::Close(_) => break,
+                    _ => continue,
+                };
+                if tx.send(text).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let session = RealtimeSession {
+            id: state.engine.ids().id("sess_", 20),
+            ..RealtimeSession::new(model)
+        };
+
+        Self {
+            state,
+            session,
+            conversation: Vec::new(),
+            audio_buffer: Vec::new(),
+            sender,
+            incoming,
+            pending: VecDeque::new(),
+            reader,
+        }
+    }
+
+    async fn run(mut self) {
+        let created = RealtimeServerEvent::SessionCreated { session: self.session.clone() };
+        if self.send(created).await.is_ok() {
+            while let Some(text) = self.next_event().await {
+                if self.handle(&text).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        debug!(session_id = %self.session.id, "Realtime session closed");
+        self.reader.abort();
+    }
+
+    async fn next_event(&mut self) -> Option<String> {
+        match self.pending.pop_front() {
+            Some(text) => Some(text),
+            None => self.incoming.recv().await,
+        }
+    }
+
+    async fn handle(&mut self, text: &str) -> Result<(), Disconnected> {
+        let event = match serde_json::from_str::<RealtimeClientEvent>(text) {
+            Ok(event) => event,
+            Err(e) => {
+                return self.send_error("invalid_request_error", Some("invalid_event"), e.to_string()).await;
+            }
+        };
+
+        match event {
+            RealtimeClientEvent::SessionUpdate { session, .. } => {
+                self.session.apply(session);
+                let updated = RealtimeServerEvent::SessionUpdated { session: self.session.clone() };
+                self.send(updated).await
+            }
+            RealtimeClientEvent::InputAudioBufferAppend { audio, .. } => {
+                match BASE64.decode(audio.as_bytes()) {
+                    Ok(bytes) => {
+                        self.audio_buffer.extend(bytes);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        self.send_error("invalid_request_error", Some("invalid_audio"), e.to_string()).await
+                    }
+                }
+            }
+            RealtimeClientEvent::InputAudioBufferCommit { .. } => self.commit_audio().await,
+            RealtimeClientEvent::InputAudioBufferClear { .. } => {
+                self.audio_buffer.clear();
+                self.send(RealtimeServerEvent::InputAudioBufferCleared).await
+            }
+            RealtimeClientEvent::ConversationItemCreate { item, .. } => self.add_item(item).await,
+            RealtimeClientEvent::ResponseCreate { response, .. } => {
+                self.create_response(response.unwrap_or_default()).await
+            }
+            RealtimeClientEvent::ResponseCancel { .. } => {
+                self.send_error(
+                    "invalid_request_error",
+                    Some("response_cancel_not_active"),
+                    "There is no active response to cancel".to_string(),
+                ).await
+            }
+        }
+    }
+
+    async fn commit_audio(&mut self) -> Result<(), Disconnected> {
+        if self.audio_buffer.is_empty() {
+            return self.send_error(
+                "invalid_request_error",
+                Some("input_audio_buffer_commit_empty"),
+                "Cannot commit an empty audio buffer".to_string(),
+            ).await;
+        }
+
+        self.audio_buffer.clear();
+        let mut item = RealtimeItem::message(
+            "user",
+            "completed",
+            vec![RealtimeContentPart::InputAudio { audio: None, transcript: None }],
+        );
+        item.id = self.new_id("item_");
+
+        let committed = RealtimeServerEvent::InputAudioBufferCommitted {
+            previous_item_id: self.last_item_id(),
+            item_id: item.id.clone(),
+        };
+        self.send(committed).await?;
+        self.add_item(item).await
+    }
+
+    async fn add_item(&mut self, item: RealtimeItem) -> Result<(), Disconnected> {
+        let created = RealtimeServerEvent::ConversationItemCreated {
+            previous_item_id: self.last_item_id(),
+            item: item.clone(),
+        };
+        self.conversation.push(item);
+        self.send(created).await
+    }
+
+    async fn create_response(&mut self, config: RealtimeResponseConfig) -> Result<(), Disconnected> {
+        let request = self.chat_request(&config);
+        let stream_response = match self.state.engine.chat_completion_stream(&request).await {
+            Ok(response) => response,
+            Err(e) => {
+                let detail = e.to_error_response().error;
+                return self.send_error(&detail.error_type, detail.code.as_deref(), detail.message).await;
+            }
+        };
+
+        let audio = config.modalities.as_ref()
+            .map(|m| m.iter().any(|m| m == "audio"))
+            .unwrap_or_else(|| self.session.audio_enabled());
+
+        let mut response = RealtimeResponse::in_progress();
+        response.id = self.new_id("resp_");
+        let response_id = response.id.clone();
+        self.send(RealtimeServerEvent::ResponseCreated { response: response.clone() }).await?;
+
+        let empty_part = if audio {
+            RealtimeContentPart::Audio { audio: None, transcript: Some(String::new()) }
+        } else {
+            RealtimeContentPart::Text { text: String::new() }
+        };
+        let mut item = RealtimeItem::message("assistant", "in_progress", vec![empty_part.clone()]);
+        item.id = self.new_id("item_");
+        let item_id = item.id.clone();
+
+        self.send(RealtimeServerEvent::ResponseOutputItemAdded {
+            response_id: response_id.clone(),
+            output_index: 0,
+            item: item.clone(),
+        }).await?;
+        self.send(RealtimeServerEvent::ConversationItemCreated {
+            previous_item_id: self.last_item_id(),
+            item: item.clone(),
+        }).await?;
+        self.send(RealtimeServerEvent::ResponseContentPartAdded {
+            response_id: response_id.clone(),
+            item_id: item_id.clone(),
+            output_index: 0,
+            content_index: 0,
+            part: empty_part,
+        }).await?;
+
+        let schedule = &stream_response.schedule;
+        let mut synth = ToneSynthesizer::default();
+        let mut text = String::new();
+        let mut cancelled = false;
+
+        for (i, token) in stream_response.tokens.iter().enumerate() {
+            let mut delay = schedule.token_delays.get(i).copied().unwrap_or(Duration::ZERO);
+            if i == 0 {
+                delay += schedule.ttft + schedule.overhead;
+            }
+
+            match self.wait(stream_response.throttle.reserve(stream_response.pacer, delay)).await {
+                Interrupt::Elapsed => {}
+                Interrupt::Cancelled => {
+                    cancelled = true;
+                    break;
+                }
+                Interrupt::Disconnected => return Err(Disconnected),
+            }
+
+            text.push_str(token);
+            if audio {
+                // Whitespace is rendered as silence, everything else as tone
+                let pcm = if token.trim().is_empty() {
+                    synth.silence(AUDIO_PER_TOKEN)
+                } else {
+                    synth.tone(AUDIO_PER_TOKEN)
+                };
+                self.send(RealtimeServerEvent::ResponseAudioDelta {
+                    response_id: response_id.clone(),
+                    item_id: item_id.clone(),
+                    output_index: 0,
+                    content_index: 0,
+                    delta: BASE64.encode(pcm),
+                }).await?;
+                self.send(RealtimeServerEvent::ResponseAudioTranscriptDelta {
+                    response_id: response_id.clone(),
+                    item_id: item_id.clone(),
+                    output_index: 0,
+                    content_index: 0,
+                    delta: token.clone(),
+                }).await?;
+            } else {
+                self.send(RealtimeServerEvent::ResponseTextDelta {
+                    response_id: response_id.clone(),
+                    item_id: item_id.clone(),
+                    output_index: 0,
+                    content_index: 0,
+                    delta: token.clone(),
+                }).await?;
+            }
+        }
+
+        let part = if audio {
+            self.send(RealtimeServerEvent::ResponseAudioDone {
+                response_id: response_id.clone(),
+                item_id: item_id.clone(),
+                output_index: 0,
+                content_index: 0,
+            }).await?;
+            self.send(RealtimeServerEvent::ResponseAudioTranscriptDone {
+                response_id: response_id.clone(),
+                item_id: item_id.clone(),
+                output_index: 0,
+                content_index: 0,
+                transcript: text.clone(),
+            }).await?;
+            RealtimeContentPart::Audio { audio: None, transcript: Some(text) }
+        } else {
+            self.send(RealtimeServerEvent::ResponseTextDone {
+                response_id: response_id.clone(),
+                item_id: item_id.clone(),
+                output_index: 0,
+                content_index: 0,
+                text: text.clone(),
+            }).await?;
+            RealtimeContentPart::Text { text }
+        };
+
+        self.send(RealtimeServerEvent::ResponseContentPartDone {
+            response_id: response_id.clone(),
+            item_id: item_id.clone(),
+            output_index: 0,
+            content_index: 0,
+            part: part.clone(),
+        }).await?;
+
+        item.status = if cancelled { "incomplete" } else { "completed" }.to_string();
+        item.content = vec![part];
+        self.send(RealtimeServerEvent::ResponseOutputItemDone {
+            response_id,
+            output_index: 0,
+            item: item.clone(),
+        }).await?;
+        self.conversation.push(item.clone());
+
+        response.status = if cancelled { "cancelled" } else { "completed" }.to_string();
+        response.output = vec![item];
+        response.usage = Some(RealtimeUsage::new(
+            stream_response.usage.prompt_tokens,
+            stream_response.usage.completion_tokens,
+        ));
+        self.send(RealtimeServerEvent::ResponseDone { response }).await
+    }
+
+    /// Build the engine request from the conversation so far
+    fn chat_request(&self, config: &RealtimeResponseConfig) -> ChatCompletionRequest {
+        let mut messages = Vec::with_capacity(self.conversation.len() + 1);
+
+        let instructions = config.instructions.as_ref().unwrap_or(&self.session.instructions);
+        if !instructions.is_empty() {
+            messages.push(Message::system(instructions.clone()));
+        }
+
+        for item in &self.conversation {
+            let text = item.text();
+            messages.push(match item.role.as_str() {
+                "assistant" => Message::assistant(text),
+                "system" => Message::system(text),
+                _ => Message::user(text),
+            });
+        }
+
+        let mut request = ChatCompletionRequest::new(&self.session.model, messages);
+        request.stream = true;
+        request.temperature = config.temperature.or(self.session.temperature);
+        request.max_tokens = match &config.max_output_tokens {
+            Some(limit) => limit.limit(),
+            None => self.session.max_response_output_tokens,
+        };
+        request
+    }
+
+    /// Sleep for `delay`, watching the socket for `response.cancel`
+    async fn wait(&mut self, delay: Duration) -> Interrupt {
+        let sleep = tokio::time::sleep(delay);
+        tokio::pin!(sleep);
+
+        loop {
+            tokio::select! {
+                _ = &mut sleep => return Interrupt::Elapsed,
+                message = self.incoming.recv() => match message {
+                    Some(text) => {
+                        let is_cancel = matches!(
+                            serde_json::from_str::<RealtimeClientEvent>(&text),
+                            Ok(RealtimeClientEvent::ResponseCancel { .. })
+                        );
+                        if is_cancel {
+                            return Interrupt::Cancelled;
+                        }
+                        self.pending.push_back(text);
+                    }
+                    None => return Interrupt::Disconnected,
+                },
+            }
+        }
+    }
+
+    fn last_item_id(&self) -> Option<String> {
+        self.conversation.last().map(|item| item.id.clone())
+    }
+
+    /// Server-assigned object ID, deterministic when the engine's IDs are
+    fn new_id(&self, prefix: &str) -> String {
+        self.state.engine.ids().id(prefix, 20)
+    }
+
+    async fn send(&mut self, event: RealtimeServerEvent) -> Result<(), Disconnected> {
+        let message = RealtimeServerMessage {
+            event_id: self.new_id("event_"),
+            event,
+        };
+        let data = serde_json::to_string(&message).unwrap_or_default();
+        self.sender.send(WsMessage::Text(data)).await.map_err(|_| Disconnected)
+    }
+
+    async fn send_error(&mut self, error_type: &str, code: Option<&str>, message: String) -> Result<(), Disconnected> {
+        self.send(RealtimeServerEvent::Error {
+            error: RealtimeError {
+                error_type: error_type.to_string(),
+                code: code.map(str::to_string),
+                message,
+                event_id: None,
+            },
+        }).await
+    }
+}
@@ -1,13 +1,18 @@
 //! Route definitions
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
 
 use super::handlers;
+use super::realtime;
 use super::state::AppState;
 
+/// Upload limit for audio transcription (matches the OpenAI limit)
+const MAX_AUDIO_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
 /// OpenAI compatible API routes
 pub fn openai_routes() -> Router<AppState> {
     Router::new()
@@ -20,6 +25,20 @@ pub fn openai_routes() -> Router<AppState> {
         .route("/v1/models/:model_id", get(handlers::get_model))
         // Legacy completions (maps to chat)
         .route("/v1/completions", post(handlers::openai_chat_completions))
+        // Moderations
+        .route("/v1/moderations", post(handlers::openai_moderations))
+        // Images
+        .route("/v1/images/generations", post(handlers::openai_image_generations))
+        .route("/v1/images/placeholder/:seed/:file", get(handlers::openai_image_placeholder))
+        // Audio
+        .route("/v1/audio/speech", post(handlers::openai_audio_speech))
+        .route(
+            "/v1/audio/transcriptions",
+            post(handlers::openai_audio_transcriptions)
+                .layer(DefaultBodyLimit::max(MAX_AUDIO_UPLOAD_BYTES)),
+        )
+        // Realtime API (WebSocket)
+        .route("/v1/realtime", get(realtime::openai_realtime))
 }
 
 /// Anthropic compatible API routes
@@ -56,12 +75,30 @@ pub fn google_routes() -> Router<AppState> {
         )
 }
 
+/// Ollama compatible API routes
+pub fn ollama_routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/chat", post(handlers::ollama_chat))
+        .route("/api/generate", post(handlers::ollama_generate))
+        // Legacy single-prompt and current batch embeddings
+        .route("/api/embeddings", post(handlers::ollama_embeddings))
+        .route("/api/embed", post(handlers::ollama_embed))
+        // Local models
+        .route("/api/tags", get(handlers::ollama_tags))
+}
+
 /// Admin and configuration routes
 pub fn admin_routes() -> Router<AppState> {
     Router::new()
         // Statistics
         .route("/admin/stats", get(handlers::get_stats))
         .route("/admin/stats/reset", post(handlers::reset_stats))
+        // Conversation sessions
+        .route("/admin/sessions", get(handlers::list_sessions))
+        .route(
+            "/admin/sessions/:session_id",
+            get(handlers::get_session).delete(handlers::delete_session),
+        )
         // Configuration
         .route("/admin/config", get(handlers::get_config))
         .route("/admin/config", post(handlers::update_config))
@@ -101,6 +138,7 @@ mod tests {
         let _openai = openai_routes();
         let _anthropic = anthropic_routes();
         let _google = google_routes();
+        let _ollama = ollama_routes();
         let _admin = admin_routes();
         let _health = health_routes();
     }
@@ -112,6 +150,7 @@ mod tests {
             .merge(openai_routes())
             .merge(anthropic_routes())
             .merge(google_routes())
+            .merge(ollama_routes())
             .merge(admin_routes())
             .merge(health_routes())
             .with_state(state);
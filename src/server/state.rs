@@ -17,8 +17,13 @@ pub struct AppState {
 
 impl AppState {
     pub fn new(config: SimulatorConfig) -> Self {
+        Self::with_engine(config.clone(), Arc::new(SimulationEngine::new(config)))
+    }
+
+    /// State around an existing engine, e.g. one with registered response sources
+    pub fn with_engine(config: SimulatorConfig, engine: Arc<SimulationEngine>) -> Self {
         Self {
-            engine: Arc::new(SimulationEngine::new(config.clone())),
+            engine,
             metrics: Arc::new(SimulatorMetrics::new()),
             config: Arc::new(config.clone()),
             shutdown: Arc::new(ShutdownState::new(config.server.request_timeout)),
@@ -1,13 +1,16 @@
 //! Streaming response implementation
 
 use axum::response::sse::Event;
-use futures::stream::{self, Stream};
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt};
 use std::convert::Infallible;
 use std::pin::Pin;
 use std::time::Duration;
-use tokio::time::sleep;
 
-use crate::engine::StreamingResponse;
+use crate::engine::{StreamEvent, StreamFault, StreamingResponse};
+use crate::config::StreamFaultType;
+use crate::error::{ErrorResponse, SimulationError};
+use crate::providers::{anthropic_utils, google_utils, ollama_utils};
 use crate::types::*;
 
 /// Sanitize JSON for SSE - remove newlines and carriage returns that would break SSE format
@@ -17,198 +20,323 @@ fn sanitize_sse_data(data: &str) -> String {
     data.replace('\n', "\\n").replace('\r', "\\r")
 }
 
+/// Error aborting a stream's connection mid-flight
+fn dropped_connection() -> SimulationError {
+    SimulationError::StreamError("Injected connection drop".to_string())
+}
+
 /// Create an SSE stream for OpenAI-compatible responses
 pub fn create_sse_stream(
     response: StreamingResponse,
-) -> Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>> {
-    let chunks = response.into_chunks();
-
-    Box::pin(stream::unfold(
-        (chunks.into_iter(), false),
-        |(mut iter, done)| async move {
-            if done {
-                return None;
-            }
-
-            match iter.next() {
-                Some((delay, chunk)) => {
-                    // Apply the delay
-                    if delay > Duration::ZERO {
-                        sleep(delay).await;
-                    }
-
-                    let data = serde_json::to_string(&chunk).unwrap_or_default();
-                    let event = Event::default().data(data);
-                    Some((Ok(event), (iter, false)))
-                }
-                None => {
-                    // Send final [DONE] marker
-                    let event = Event::default().data("[DONE]");
-                    Some((Ok(event), (iter, true)))
-                }
-            }
-        },
-    ))
+) -> Pin<Box<dyn Stream<Item = Result<Event, SimulationError>> + Send>> {
+    Box::pin(response.paced_events().map(|event| match event {
+        StreamEvent::Chunk(chunk) => {
+            let data = serde_json::to_string(&chunk).unwrap_or_default();
+            Ok(Event::default().data(data))
+        }
+        StreamEvent::Error(message) => {
+            let error = ErrorResponse::new("server_error", &message);
+            Ok(Event::default().data(serde_json::to_string(&error).unwrap_or_default()))
+        }
+        StreamEvent::Disconnect => Err(dropped_connection()),
+        // Send final [DONE] marker
+        StreamEvent::Done => Ok(Event::default().data("[DONE]")),
+    }))
 }
 
 /// Create an SSE stream for Anthropic-compatible responses
 pub fn create_anthropic_sse_stream(
     response: StreamingResponse,
     model: &str,
-) -> Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>> {
-    let id = response.id.clone();
-    let model = model.to_string();
-    let chunks = response.into_chunks();
-    let usage = Usage::new(
-        chunks.iter().map(|(_, c)| c.usage.as_ref().map(|u| u.prompt_tokens).unwrap_or(0)).max().unwrap_or(0),
-        chunks.iter().map(|(_, c)| c.usage.as_ref().map(|u| u.completion_tokens).unwrap_or(0)).max().unwrap_or(0),
-    );
+) -> Pin<Box<dyn Stream<Item = Result<Event, SimulationError>> + Send>> {
+    let usage = response.usage.clone();
+    let message_start = AnthropicStreamEvent::MessageStart {
+        message: AnthropicStreamMessage {
+            id: anthropic_utils::message_id(&response.id),
+            message_type: "message".to_string(),
+            role: "assistant".to_string(),
+            content: vec![],
+            model: model.to_string(),
+            stop_reason: None,
+            stop_sequence: None,
+            usage: AnthropicStreamUsage {
+                input_tokens: usage.prompt_tokens,
+                output_tokens: 0,
+            },
+        },
+    };
+
+    let mut blocks = 0;
+    let mut text_open = false;
+    let body = response.paced_events()
+        .flat_map(move |event| {
+            let chunk = match event {
+                StreamEvent::Chunk(chunk) => chunk,
+                StreamEvent::Error(message) => {
+                    let error = AnthropicStreamEvent::Error {
+                        error: AnthropicStreamError { error_type: "api_error".to_string(), message },
+                    };
+                    return stream::iter(vec![Ok(anthropic_sse_event(error))]);
+                }
+                StreamEvent::Disconnect => return stream::iter(vec![Err(dropped_connection())]),
+                StreamEvent::Done => {
+                    return stream::iter(vec![Ok(anthropic_sse_event(AnthropicStreamEvent::MessageStop))]);
+                }
+            };
+            let choice = chunk.choices.first();
+            let content = choice.and_then(|c| c.delta.content.clone()).unwrap_or_default();
+            let finish_reason = choice.and_then(|c| c.finish_reason);
+            // Past zero only when a chunk overtook the block still open
+            let offset = choice.map_or(0, |c| c.index);
+            let mut events = Vec::new();
+
+            if !content.is_empty() {
+                if !text_open {
+                    text_open = true;
+                    blocks += 1;
+                    events.push(AnthropicStreamEvent::ContentBlockStart {
+                        index: 0,
+                        content_block: AnthropicContentBlockType::Text { text: String::new() },
+                    });
+                }
+                events.push(AnthropicStreamEvent::ContentBlockDelta {
+                    index: offset,
+                    delta: AnthropicDelta::TextDelta { text: content },
+                });
+            }
 
-    Box::pin(stream::unfold(
-        (chunks.into_iter(), 0usize, id, model, usage, false, false),
-        |(mut iter, index, id, model, usage, started, done)| async move {
-            if done {
-                return None;
+            // Each tool call arrives whole: a tool_use block after the text
+            let calls = choice.and_then(|c| c.delta.tool_calls.as_ref()).into_iter().flatten();
+            for call in calls {
+                let function = call.function.as_ref();
+                if std::mem::take(&mut text_open) {
+                    events.push(AnthropicStreamEvent::ContentBlockStop { index: 0 });
+                }
+                let index = blocks + offset;
+                blocks += 1;
+                events.push(AnthropicStreamEvent::ContentBlockStart {
+                    index,
+                    content_block: AnthropicContentBlockType::ToolUse {
+                        id: anthropic_utils::tool_use_id(call.id.as_deref().unwrap_or_default()),
+                        name: function.and_then(|f| f.name.clone()).unwrap_or_default(),
+                        input: serde_json::json!({}),
+                    },
+                });
+                events.push(AnthropicStreamEvent::ContentBlockDelta {
+                    index,
+                    delta: AnthropicDelta::InputJsonDelta {
+                        partial_json: function.and_then(|f| f.arguments.clone()).unwrap_or_default(),
+                    },
+                });
+                events.push(AnthropicStreamEvent::ContentBlockStop { index });
             }
 
-            // Send message_start first
-            if !started {
-                let event = AnthropicStreamEvent::MessageStart {
-                    message: AnthropicStreamMessage {
-                        id: id.clone(),
-                        message_type: "message".to_string(),
-                        role: "assistant".to_string(),
-                        content: vec![],
-                        model: model.clone(),
-                        stop_reason: None,
-                        stop_sequence: None,
+            match finish_reason {
+                Some(reason) => {
+                    if text_open {
+                        events.push(AnthropicStreamEvent::ContentBlockStop { index: 0 });
+                    }
+                    events.push(AnthropicStreamEvent::MessageDelta {
+                        delta: AnthropicMessageDelta {
+                            stop_reason: Some(anthropic_utils::stop_reason(reason).to_string()),
+                            stop_sequence: None,
+                        },
                         usage: AnthropicStreamUsage {
-                            input_tokens: usage.prompt_tokens,
-                            output_tokens: 0,
+                            input_tokens: 0,
+                            output_tokens: usage.completion_tokens,
                         },
-                    },
-                };
-                let data = sanitize_sse_data(&serde_json::to_string(&event).unwrap_or_default());
-                let sse_event = Event::default()
-                    .event("message_start")
-                    .data(data);
-                return Some((Ok(sse_event), (iter, 0, id, model, usage, true, false)));
+                    });
+                }
+                // Keep the connection visibly alive across empty deltas
+                None if events.is_empty() => events.push(AnthropicStreamEvent::Ping),
+                None => {}
             }
 
-            match iter.next() {
-                Some((delay, chunk)) => {
-                    if delay > Duration::ZERO {
-                        sleep(delay).await;
-                    }
+            stream::iter(events.into_iter().map(|event| Ok(anthropic_sse_event(event))).collect::<Vec<_>>())
+        });
 
-                    // Convert to Anthropic format
-                    let content = chunk.choices.first()
-                        .and_then(|c| c.delta.content.clone())
-                        .unwrap_or_default();
-
-                    if content.is_empty() && chunk.choices.first().and_then(|c| c.finish_reason).is_none() {
-                        // Skip empty deltas that aren't finish markers
-                        return Some((
-                            Ok(Event::default().event("ping").data("{}")),
-                            (iter, index, id, model, usage, started, false)
-                        ));
-                    }
+    Box::pin(stream::once(async move { Ok(anthropic_sse_event(message_start)) }).chain(body))
+}
 
-                    if chunk.choices.first().and_then(|c| c.finish_reason).is_some() {
-                        // Send content_block_stop, message_delta, and message_stop
-                        let stop_event = AnthropicStreamEvent::ContentBlockStop { index: 0 };
-                        let data = sanitize_sse_data(&serde_json::to_string(&stop_event).unwrap_or_default());
-                        let sse_event = Event::default()
-                            .event("content_block_stop")
-                            .data(data);
-                        return Some((Ok(sse_event), (iter, index, id, model, usage, started, true)));
-                    }
+/// Wrap an Anthropic stream event as a named SSE event
+fn anthropic_sse_event(event: AnthropicStreamEvent) -> Event {
+    let name = match &event {
+        AnthropicStreamEvent::MessageStart { .. } => "message_start",
+        AnthropicStreamEvent::ContentBlockStart { .. } => "content_block_start",
+        AnthropicStreamEvent::ContentBlockDelta { .. } => "content_block_delta",
+        AnthropicStreamEvent::ContentBlockStop { .. } => "content_block_stop",
+        AnthropicStreamEvent::MessageDelta { .. } => "message_delta",
+        AnthropicStreamEvent::MessageStop => "message_stop",
+        AnthropicStreamEvent::Ping => "ping",
+        AnthropicStreamEvent::Error { .. } => "error",
+    };
+    let data = sanitize_sse_data(&serde_json::to_string(&event).unwrap_or_default());
+    Event::default().event(name).data(data)
+}
 
-                    // Send content_block_start if this is first content
-                    if index == 0 {
-                        let start_event = AnthropicStreamEvent::ContentBlockStart {
-                            index: 0,
-                            content_block: AnthropicContentBlockType::Text { text: String::new() },
-                        };
-                        let data = sanitize_sse_data(&serde_json::to_string(&start_event).unwrap_or_default());
-                        let sse_event = Event::default()
-                            .event("content_block_start")
-                            .data(data);
-                        // We need to send content delta next
-                        return Some((Ok(sse_event), (iter, index + 1, id, model, usage, started, false)));
-                    }
+/// Create an SSE stream for Gemini-compatible responses
+///
+/// Gemini streams have no end marker, so a missing one drops the final
+/// chunk carrying the finish reason instead.
+pub fn create_gemini_sse_stream(
+    response: StreamingResponse,
+) -> Pin<Box<dyn Stream<Item = Result<Event, SimulationError>> + Send>> {
+    let drop_finish = matches!(
+        response.fault,
+        Some(StreamFault { fault_type: StreamFaultType::MissingDone, .. })
+    );
 
-                    // Send content delta
-                    let delta_event = AnthropicStreamEvent::ContentBlockDelta {
-                        index: 0,
-                        delta: AnthropicDelta::TextDelta { text: content },
-                    };
-                    let data = sanitize_sse_data(&serde_json::to_string(&delta_event).unwrap_or_default());
-                    let sse_event = Event::default()
-                        .event("content_block_delta")
-                        .data(data);
-                    Some((Ok(sse_event), (iter, index + 1, id, model, usage, started, false)))
-                }
-                None => {
-                    // Send message_stop
-                    let event = AnthropicStreamEvent::MessageStop;
-                    let data = sanitize_sse_data(&serde_json::to_string(&event).unwrap_or_default());
-                    let sse_event = Event::default()
-                        .event("message_stop")
-                        .data(data);
-                    Some((Ok(sse_event), (iter, index, id, model, usage, started, true)))
-                }
+    Box::pin(response.paced_events().filter_map(move |event| async move {
+        let chunk = match event {
+            StreamEvent::Chunk(chunk) => chunk,
+            StreamEvent::Error(message) => {
+                let error = serde_json::json!({
+                    "error": { "code": 500, "message": message, "status": "INTERNAL" }
+                });
+                return Some(Ok(Event::default().data(error.to_string())));
             }
-        },
-    ))
+            StreamEvent::Disconnect => return Some(Err(dropped_connection())),
+            StreamEvent::Done => return None,
+        };
+
+        let choice = chunk.choices.first();
+        let finish_reason = choice.and_then(|c| c.finish_reason);
+        if drop_finish && finish_reason.is_some() {
+            return None;
+        }
+
+        // Tool call chunks carry functionCall parts instead of text
+        let mut parts: Vec<GeminiResponsePart> = choice
+            .and_then(|c| c.delta.tool_calls.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|call| call.function.as_ref())
+            .map(|function| google_utils::function_call_part(
+                function.name.as_deref().unwrap_or_default(),
+                function.arguments.as_deref().unwrap_or_default(),
+            ))
+            .collect();
+        if parts.is_empty() {
+            parts.push(GeminiResponsePart::text(
+                choice.and_then(|c| c.delta.content.clone()).unwrap_or_default(),
+            ));
+        }
+
+        // Create Gemini-style response
+        let gemini_chunk = GeminiResponse {
+            candidates: vec![GeminiCandidate {
+                content: GeminiResponseContent {
+                    role: "model".to_string(),
+                    parts,
+                },
+                finish_reason: finish_reason
+                    .map(|r| google_utils::to_gemini_finish_reason(r).to_string()),
+                safety_ratings: None,
+            }],
+            usage_metadata: chunk.usage.map(|u| GeminiUsageMetadata {
+                prompt_token_count: u.prompt_tokens,
+                candidates_token_count: u.completion_tokens,
+                total_token_count: u.total_tokens,
+            }),
+        };
+
+        let data = serde_json::to_string(&gemini_chunk).unwrap_or_default();
+        Some(Ok(Event::default().data(data)))
+    }))
 }
 
-/// Create an SSE stream for Gemini-compatible responses
-pub fn create_gemini_sse_stream(
+/// Compute Ollama timing fields from a response's latency schedule
+pub fn ollama_metrics(response: &StreamingResponse) -> OllamaMetrics {
+    let eval_duration: Duration = response.schedule.token_delays.iter().sum();
+    OllamaMetrics::completed(
+        response.usage.prompt_tokens,
+        response.schedule.queued + response.schedule.ttft + response.schedule.overhead,
+        response.usage.completion_tokens,
+        eval_duration,
+    )
+}
+
+/// Create an NDJSON stream for Ollama-compatible `/api/chat` responses
+pub fn create_ollama_chat_stream(
     response: StreamingResponse,
-) -> Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>> {
-    let chunks = response.into_chunks();
+    model: &str,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes, Infallible>> + Send>> {
+    let model = model.to_string();
+    let created_at = response.created;
+    create_ollama_ndjson_stream(response, move |content, tool_calls, done, metrics| {
+        let mut line = OllamaChatResponse {
+            created_at,
+            ..OllamaChatResponse::new(model.clone(), content, done, metrics)
+        };
+        if !tool_calls.is_empty() {
+            line.message.tool_calls = Some(tool_calls.iter().map(ollama_utils::tool_call).collect());
+        }
+        serde_json::to_string(&line).unwrap_or_default()
+    })
+}
 
-    Box::pin(stream::unfold(
-        (chunks.into_iter(), String::new()),
-        |(mut iter, mut accumulated)| async move {
-            match iter.next() {
-                Some((delay, chunk)) => {
-                    if delay > Duration::ZERO {
-                        sleep(delay).await;
-                    }
+/// Create an NDJSON stream for Ollama-compatible `/api/generate` responses
+pub fn create_ollama_generate_stream(
+    response: StreamingResponse,
+    model: &str,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes, Infallible>> + Send>> {
+    let model = model.to_string();
+    let created_at = response.created;
+    create_ollama_ndjson_stream(response, move |content, _tool_calls, done, metrics| {
+        let line = OllamaGenerateResponse {
+            created_at,
+            ..OllamaGenerateResponse::new(model.clone(), content, done, metrics)
+        };
+        serde_json::to_string(&line).unwrap_or_default()
+    })
+}
 
-                    let content = chunk.choices.first()
-                        .and_then(|c| c.delta.content.clone())
-                        .unwrap_or_default();
-
-                    accumulated.push_str(&content);
-
-                    // Create Gemini-style response
-                    let gemini_chunk = GeminiResponse {
-                        candidates: vec![GeminiCandidate {
-                            content: GeminiResponseContent {
-                                role: "model".to_string(),
-                                parts: vec![GeminiResponsePart { text: content }],
-                            },
-                            finish_reason: chunk.choices.first()
-                                .and_then(|c| c.finish_reason)
-                                .map(|_| "STOP".to_string()),
-                            safety_ratings: None,
-                        }],
-                        usage_metadata: chunk.usage.map(|u| GeminiUsageMetadata {
-                            prompt_token_count: u.prompt_tokens,
-                            candidates_token_count: u.completion_tokens,
-                            total_token_count: u.total_tokens,
-                        }),
-                    };
+/// Emit one JSON object per line: a line per token, a line with any tool
+/// calls, then a final `done` line carrying the timing metrics. TTFT and
+/// overhead are folded into the first token.
+fn create_ollama_ndjson_stream<F>(
+    response: StreamingResponse,
+    render: F,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes, Infallible>> + Send>>
+where
+    F: Fn(String, &[ResponseToolCall], bool, OllamaMetrics) -> String + Send + 'static,
+{
+    let metrics = ollama_metrics(&response);
+    let (pacer, throttle) = (response.pacer, response.throttle);
+    let first_delay = response.schedule.ttft + response.schedule.overhead;
+    let mut final_delay = if response.tokens.is_empty() { first_delay } else { Duration::ZERO };
+
+    let mut lines: Vec<(Duration, String, Vec<ResponseToolCall>, bool)> = response.tokens
+        .into_iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let mut delay = response.schedule.token_delays.get(i).copied().unwrap_or(Duration::ZERO);
+            if i == 0 {
+                delay += first_delay;
+            }
+            (delay, token, Vec::new(), false)
+        })
+        .collect();
+    if !response.tool_calls.is_empty() {
+        lines.push((final_delay, String::new(), response.tool_calls, false));
+        final_delay = Duration::ZERO;
+    }
+    lines.push((final_delay, String::new(), Vec::new(), true));
 
-                    let data = serde_json::to_string(&gemini_chunk).unwrap_or_default();
-                    let event = Event::default().data(data);
-                    Some((Ok(event), (iter, accumulated)))
-                }
-                None => None,
+    Box::pin(stream::unfold(
+        (lines.into_iter(), Some(metrics), render, throttle),
+        move |(mut iter, mut metrics, render, throttle)| async move {
+            let (delay, content, tool_calls, done) = iter.next()?;
+            if done || !tool_calls.is_empty() {
+                pacer.sleep(delay).await;
+            } else {
+                throttle.sleep(pacer, delay).await;
             }
+
+            let line_metrics = if done { metrics.take().unwrap_or_default() } else { OllamaMetrics::default() };
+            let mut line = render(content, &tool_calls, done, line_metrics);
+            line.push('\n');
+            Some((Ok(Bytes::from(line)), (iter, metrics, render, throttle)))
         },
     ))
 }
@@ -216,7 +344,8 @@ pub fn create_gemini_sse_stream(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::latency::LatencySchedule;
+    use crate::engine::Throttle;
+    use crate::latency::{LatencySchedule, Pacer};
     use futures::StreamExt;
 
     fn test_streaming_response() -> StreamingResponse {
@@ -224,8 +353,16 @@ mod tests {
             id: "test-id".to_string(),
             model: "gpt-4".to_string(),
             tokens: vec!["Hello".to_string(), " ".to_string(), "World".to_string()],
+            tool_calls: vec![],
             schedule: LatencySchedule::instant(3),
+            pacer: Pacer::real_time(),
+            throttle: Throttle::default(),
+            fault: None,
+            protocol_fault: None,
             usage: Usage::new(10, 3),
+            finish_reason: FinishReason::Stop,
+            system_fingerprint: "fp_test".to_string(),
+            created: chrono::Utc::now(),
         }
     }
 
@@ -269,4 +406,29 @@ mod tests {
 
         assert!(!events.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_ollama_ndjson_stream() {
+        let response = test_streaming_response();
+        let mut stream = create_ollama_chat_stream(response, "llama3.2");
+
+        let mut lines = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            let bytes = chunk.unwrap();
+            assert!(bytes.ends_with(b"\n"));
+            lines.push(serde_json::from_slice::<serde_json::Value>(&bytes).unwrap());
+        }
+
+        // One line per token plus the final done line
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["message"]["content"], "Hello");
+        assert_eq!(lines[0]["done"], false);
+        assert!(lines[0].get("eval_count").is_none());
+
+        let last = lines.last().unwrap();
+        assert_eq!(last["done"], true);
+        assert_eq!(last["eval_count"], 3);
+        assert_eq!(last["prompt_eval_count"], 10);
+        assert_eq!(last["done_reason"], "stop");
+    }
 }
@@ -0,0 +1,164 @@
+//! Approximate tokenizers for models without a public vocabulary
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::Tokenizer;
+
+/// Approximation of Anthropic's Claude tokenizer
+pub static CLAUDE: ApproxTokenizer = ApproxTokenizer {
+    max_word_chars: 9,
+    chars_per_token: 3,
+    cjk_chars_per_token: 1,
+    digits_per_token: 3,
+};
+
+/// Approximation of Gemini's SentencePiece tokenizer
+pub static GEMINI: ApproxTokenizer = ApproxTokenizer {
+    max_word_chars: 10,
+    chars_per_token: 4,
+    cjk_chars_per_token: 2,
+    digits_per_token: 1,
+};
+
+/// Words, digit runs, punctuation runs and whitespace, GPT-style
+static PRE_TOKENIZER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"'(?:s|t|d|m|ll|ve|re)| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+")
+        .expect("valid pre-tokenizer pattern")
+});
+
+/// Script-aware tokenizer approximating a BPE/SentencePiece vocabulary.
+///
+/// Text is pre-split into words, numbers, punctuation and whitespace.
+/// Common-length words are one token; longer words, CJK runs and numbers
+/// are cut into fixed-size pieces.
+#[derive(Debug, Clone)]
+pub struct ApproxTokenizer {
+    /// Longest word (in characters) kept as a single token
+    pub max_word_chars: usize,
+    /// Characters per token when splitting longer words
+    pub chars_per_token: usize,
+    /// Characters per token for Chinese, Japanese and Korean text
+    pub cjk_chars_per_token: usize,
+    /// Digits per token
+    pub digits_per_token: usize,
+}
+
+impl ApproxTokenizer {
+    /// Split one pre-tokenized word into pieces
+    fn split_word(&self, word: &str, pieces: &mut Vec<String>) {
+        let (space, body) = match word.strip_prefix(' ') {
+            Some(body) if !body.is_empty() => (" ", body),
+            _ => ("", word),
+        };
+
+        let first = body.chars().next().unwrap_or(' ');
+        let chars: Vec<char> = body.chars().collect();
+
+        let mut chunks: Vec<String> = if first.is_numeric() {
+            chunk(&chars, self.digits_per_token)
+        } else if first.is_alphabetic() {
+            // Runs of CJK and other letters are chunked separately
+            let mut chunks = Vec::new();
+            let mut start = 0;
+            while start < chars.len() {
+                let cjk = is_cjk(chars[start]);
+                let end = chars[start..].iter()
+                    .position(|c| is_cjk(*c) != cjk)
+                    .map_or(chars.len(), |n| start + n);
+                let run = &chars[start..end];
+                if cjk {
+                    chunks.extend(chunk(run, self.cjk_chars_per_token));
+                } else if run.len() <= self.max_word_chars {
+                    chunks.push(run.iter().collect());
+                } else {
+                    chunks.extend(chunk(run, self.chars_per_token));
+                }
+                start = end;
+            }
+            chunks
+        } else if first.is_whitespace() {
+            vec![body.to_string()]
+        } else {
+            // Punctuation and symbols pair up at most
+            chunk(&chars, 2)
+        };
+
+        if let Some(first) = chunks.first_mut() {
+            first.insert_str(0, space);
+        }
+        pieces.append(&mut chunks);
+    }
+}
+
+impl Tokenizer for ApproxTokenizer {
+    fn count(&self, text: &str) -> usize {
+        self.split(text).len()
+    }
+
+    fn split(&self, text: &str) -> Vec<String> {
+        let mut pieces = Vec::new();
+        for word in PRE_TOKENIZER.find_iter(text) {
+            self.split_word(word.as_str(), &mut pieces);
+        }
+        pieces
+    }
+}
+
+/// Legacy estimate of ~4 characters per token
+#[derive(Debug, Clone, Copy)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+
+    fn split(&self, text: &str) -> Vec<String> {
+        chunk(&text.chars().collect::<Vec<_>>(), 4)
+    }
+}
+
+/// Cut characters into strings of at most `size`
+fn chunk(chars: &[char], size: usize) -> Vec<String> {
+    chars.chunks(size.max(1)).map(|c| c.iter().collect()).collect()
+}
+
+/// Han, kana and hangul characters
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul syllables
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0x20000..=0x2FFFF // CJK Extensions B+
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_approximation() {
+        assert_eq!(CLAUDE.split("Hello, world!"), vec!["Hello", ",", " world", "!"]);
+        // Long words are split into pieces
+        assert_eq!(CLAUDE.count(" internationalization"), 7);
+        // Each CJK character is a token
+        assert_eq!(CLAUDE.count("日本語"), 3);
+    }
+
+    #[test]
+    fn test_gemini_approximation() {
+        assert_eq!(GEMINI.count("日本語です"), 3);
+        assert_eq!(GEMINI.split("in 2024"), vec!["in", " 2", "0", "2", "4"]);
+    }
+
+    #[test]
+    fn test_heuristic() {
+        assert_eq!(HeuristicTokenizer.count("test"), 1);
+        assert_eq!(HeuristicTokenizer.count("hello world"), 3);
+        assert_eq!(HeuristicTokenizer.split("hello world"), vec!["hell", "o wo", "rld"]);
+    }
+}
@@ -0,0 +1,67 @@
+//! Byte-pair encodings bundled with tiktoken
+
+use once_cell::sync::Lazy;
+use tiktoken_rs::CoreBPE;
+
+use super::Tokenizer;
+
+/// GPT-4 / GPT-3.5 encoding
+pub static CL100K_BASE: Lazy<BpeTokenizer> = Lazy::new(|| {
+    BpeTokenizer::new(tiktoken_rs::cl100k_base().expect("bundled cl100k_base vocabulary"))
+});
+
+/// GPT-4o encoding
+pub static O200K_BASE: Lazy<BpeTokenizer> = Lazy::new(|| {
+    BpeTokenizer::new(tiktoken_rs::o200k_base().expect("bundled o200k_base vocabulary"))
+});
+
+/// Tokenizer backed by a tiktoken byte-pair encoding
+pub struct BpeTokenizer {
+    bpe: CoreBPE,
+}
+
+impl BpeTokenizer {
+    pub fn new(bpe: CoreBPE) -> Self {
+        Self { bpe }
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    /// One piece per token. A character spread over several byte-level
+    /// tokens lands in the piece of its last token, and the tokens before it
+    /// yield empty pieces, so `split(text).len() == count(text)`.
+    fn split(&self, text: &str) -> Vec<String> {
+        let tokens = self.bpe.encode_ordinary(text);
+        let mut pieces = Vec::with_capacity(tokens.len());
+        let mut start = 0;
+        let mut consumed = 0;
+
+        for end in 1..=tokens.len() {
+            match self.bpe.decode(tokens[start..end].to_vec()) {
+                Ok(piece) => {
+                    consumed += piece.len();
+                    pieces.push(piece);
+                    start = end;
+                }
+                Err(_) => pieces.push(String::new()),
+            }
+        }
+
+        // Whole text always decodes, but keep the round trip exact regardless
+        if consumed < text.len() {
+            if let Some(last) = pieces.last_mut() {
+                last.push_str(&text[consumed..]);
+            }
+        }
+
+        pieces
+    }
+
+    fn encode(&self, text: &str) -> Option<Vec<u32>> {
+        Some(self.bpe.encode_ordinary(text))
+    }
+}
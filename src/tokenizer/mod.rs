@@ -0,0 +1,179 @@
+//! Tokenizers for token counting and stream chunking
+//!
+//! OpenAI-family models use the real cl100k/o200k byte-pair encodings,
+//! bundled with the binary. Claude and Gemini vocabularies are not public,
+//! so those models use a script-aware approximation tuned to their typical
+//! token density.
+
+mod approx;
+mod bpe;
+
+pub use approx::*;
+pub use bpe::*;
+
+use serde::{Deserialize, Serialize};
+use crate::types::Provider;
+
+/// Splits text into tokens
+pub trait Tokenizer: Send + Sync {
+    /// Number of tokens in `text`
+    fn count(&self, text: &str) -> usize;
+
+    /// Split `text` into token strings that concatenate back to `text`
+    fn split(&self, text: &str) -> Vec<String>;
+
+    /// Token ids of `text`, for tokenizers with a public vocabulary
+    fn encode(&self, _text: &str) -> Option<Vec<u32>> {
+        None
+    }
+}
+
+/// Tokenizer selectable per model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerKind {
+    /// GPT-4 / GPT-3.5 byte-pair encoding
+    #[default]
+    Cl100kBase,
+    /// GPT-4o / o-series byte-pair encoding
+    O200kBase,
+    /// Approximation of Anthropic's Claude tokenizer
+    Claude,
+    /// Approximation of Google's Gemini SentencePiece tokenizer
+    Gemini,
+    /// Legacy ~4 characters per token estimate
+    Heuristic,
+}
+
+impl TokenizerKind {
+    /// Default tokenizer for a model that doesn't configure one
+    pub fn for_model(provider: Provider, model_id: &str) -> Self {
+        match provider {
+            Provider::Anthropic => Self::Claude,
+            Provider::Google => Self::Gemini,
+            Provider::OpenAI | Provider::Azure if uses_o200k(model_id) => Self::O200kBase,
+            _ => Self::Cl100kBase,
+        }
+    }
+
+    /// The tokenizer implementation
+    pub fn tokenizer(&self) -> &'static dyn Tokenizer {
+        match self {
+            Self::Cl100kBase => &*CL100K_BASE,
+            Self::O200kBase => &*O200K_BASE,
+            Self::Claude => &CLAUDE,
+            Self::Gemini => &GEMINI,
+            Self::Heuristic => &HeuristicTokenizer,
+        }
+    }
+
+    /// Number of tokens in `text`
+    pub fn count(&self, text: &str) -> usize {
+        self.tokenizer().count(text)
+    }
+
+    /// Split `text` into token strings
+    pub fn split(&self, text: &str) -> Vec<String> {
+        self.tokenizer().split(text)
+    }
+
+    /// Token ids of `text`, if the vocabulary is known
+    pub fn encode(&self, text: &str) -> Option<Vec<u32>> {
+        self.tokenizer().encode(text)
+    }
+
+    /// Truncate `text` to at most `max_tokens` tokens
+    pub fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        let tokens = self.split(text);
+        if tokens.len() <= max_tokens {
+            return text.to_string();
+        }
+        tokens[..max_tokens].concat()
+    }
+}
+
+impl std::fmt::Display for TokenizerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cl100kBase => write!(f, "cl100k_base"),
+            Self::O200kBase => write!(f, "o200k_base"),
+            Self::Claude => write!(f, "claude"),
+            Self::Gemini => write!(f, "gemini"),
+            Self::Heuristic => write!(f, "heuristic"),
+        }
+    }
+}
+
+/// OpenAI model families encoded with o200k_base
+fn uses_o200k(model_id: &str) -> bool {
+    ["gpt-4o", "gpt-4.1", "gpt-4.5", "chatgpt-4o", "o1", "o3", "o4", "omni-moderation"]
+        .iter()
+        .any(|prefix| model_id.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: &[&str] = &[
+        "Hello, world! How are you?",
+        "fn main() {\n    println!(\"{}\", 42);\n}\n",
+        "日本語のテキストを分割します。",
+        "Ünïcödé — with emoji 🎉 and   extra   spaces",
+        "",
+    ];
+
+    const KINDS: &[TokenizerKind] = &[
+        TokenizerKind::Cl100kBase,
+        TokenizerKind::O200kBase,
+        TokenizerKind::Claude,
+        TokenizerKind::Gemini,
+        TokenizerKind::Heuristic,
+    ];
+
+    #[test]
+    fn test_split_round_trips() {
+        for kind in KINDS {
+            for sample in SAMPLES {
+                let tokens = kind.split(sample);
+                assert_eq!(tokens.concat(), *sample, "{} split of {:?}", kind, sample);
+                assert_eq!(tokens.len(), kind.count(sample), "{} count of {:?}", kind, sample);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bpe_counts() {
+        assert_eq!(TokenizerKind::Cl100kBase.count("hello world"), 2);
+        assert_eq!(TokenizerKind::O200kBase.count("What is in this image?"), 6);
+        assert_eq!(
+            TokenizerKind::Cl100kBase.split("This is a test"),
+            vec!["This", " is", " a", " test"]
+        );
+        assert_eq!(TokenizerKind::Cl100kBase.encode("hello world"), Some(vec![15339, 1917]));
+        assert_eq!(TokenizerKind::Claude.encode("hello world"), None);
+    }
+
+    #[test]
+    fn test_model_defaults() {
+        assert_eq!(TokenizerKind::for_model(Provider::OpenAI, "gpt-4"), TokenizerKind::Cl100kBase);
+        assert_eq!(TokenizerKind::for_model(Provider::OpenAI, "gpt-4o-mini"), TokenizerKind::O200kBase);
+        assert_eq!(TokenizerKind::for_model(Provider::Anthropic, "claude-3-haiku"), TokenizerKind::Claude);
+        assert_eq!(TokenizerKind::for_model(Provider::Google, "gemini-1.5-pro"), TokenizerKind::Gemini);
+        assert_eq!(TokenizerKind::for_model(Provider::Ollama, "llama3.2"), TokenizerKind::Cl100kBase);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let kind = TokenizerKind::Cl100kBase;
+        assert_eq!(kind.truncate("This is a test", 2), "This is");
+        assert_eq!(kind.truncate("short", 10), "short");
+    }
+
+    #[test]
+    fn test_serde_names() {
+        let kind: TokenizerKind = serde_json::from_str("\"o200k_base\"").unwrap();
+        assert_eq!(kind, TokenizerKind::O200kBase);
+        assert_eq!(serde_json::to_string(&TokenizerKind::Claude).unwrap(), "\"claude\"");
+    }
+}
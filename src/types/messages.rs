@@ -1,7 +1,8 @@
 //! Message types for chat completions
 
 use serde::{Deserialize, Serialize};
-use super::Role;
+use super::{vision, Provider, Role};
+use crate::tokenizer::TokenizerKind;
 
 /// A message in a chat conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,9 +74,15 @@ impl Message {
 
     /// Estimate token count for this message
     pub fn estimate_tokens(&self) -> usize {
-        // Rough estimation: ~4 characters per token
-        let text_len = self.text().len();
-        (text_len / 4).max(1)
+        self.estimate_tokens_for(Provider::OpenAI, TokenizerKind::default())
+    }
+
+    /// Count text with `tokenizer` and cost images the way `provider` does
+    pub fn estimate_tokens_for(&self, provider: Provider, tokenizer: TokenizerKind) -> usize {
+        let image_tokens: usize = self.content.images()
+            .map(|image| image.estimate_tokens(provider))
+            .sum();
+        tokenizer.count(&self.text()).max(1) + image_tokens
     }
 }
 
@@ -114,6 +121,20 @@ impl MessageContent {
     }
 }
 
+impl MessageContent {
+    /// Image parts of the content
+    pub fn images(&self) -> impl Iterator<Item = &ImageUrl> {
+        let parts = match self {
+            MessageContent::Text(_) => &[][..],
+            MessageContent::Parts(parts) => parts.as_slice(),
+        };
+        parts.iter().filter_map(|p| match p {
+            ContentPart::ImageUrl { image_url } => Some(image_url),
+            _ => None,
+        })
+    }
+}
+
 /// A part of multipart content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -130,6 +151,40 @@ pub struct ImageUrl {
     pub detail: Option<String>,
 }
 
+impl ImageUrl {
+    /// Create a `data:` URL image from base64 data
+    pub fn inline(media_type: &str, base64_data: &str) -> Self {
+        Self {
+            url: format!("data:{};base64,{}", media_type, base64_data),
+            detail: None,
+        }
+    }
+
+    /// Dimensions of an inline image; `None` for remote URLs
+    pub fn dimensions(&self) -> Result<Option<(u32, u32)>, String> {
+        match vision::decode_data_url(&self.url) {
+            None => Ok(None),
+            Some(bytes) => vision::image_dimensions(&bytes?)
+                .map(Some)
+                .ok_or_else(|| "unsupported image format".to_string()),
+        }
+    }
+
+    /// Estimate the input tokens this image costs on `provider`
+    pub fn estimate_tokens(&self, provider: Provider) -> usize {
+        let (width, height) = self.dimensions()
+            .ok()
+            .flatten()
+            .unwrap_or(vision::DEFAULT_IMAGE_DIMENSIONS);
+
+        match provider {
+            Provider::Anthropic => vision::anthropic_image_tokens(width, height),
+            Provider::Google => vision::GEMINI_IMAGE_TOKENS,
+            _ => vision::openai_image_tokens(width, height, self.detail.as_deref()),
+        }
+    }
+}
+
 /// Tool call made by the assistant
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
@@ -208,6 +263,20 @@ mod tests {
         assert!(tokens > 0);
     }
 
+    #[test]
+    fn test_image_tokens_counted() {
+        let json = r#"{"role": "user", "content": [
+            {"type": "text", "text": "What is this?"},
+            {"type": "image_url", "image_url": {"url": "https://example.com/cat.png", "detail": "low"}}
+        ]}"#;
+        let msg: Message = serde_json::from_str(json).unwrap();
+
+        assert_eq!(msg.text(), "What is this?");
+        assert_eq!(msg.content.images().count(), 1);
+        assert_eq!(msg.estimate_tokens(), 4 + 85);
+        assert_eq!(msg.estimate_tokens_for(Provider::Google, TokenizerKind::Gemini), 4 + 258);
+    }
+
     #[test]
     fn test_message_content_deserialization() {
         let json = r#"{"role": "user", "content": "Hello"}"#;
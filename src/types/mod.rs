@@ -1,14 +1,17 @@
 //! API types for LLM-Simulator
 //!
 //! This module defines request and response types compatible with
-//! OpenAI, Anthropic, Google, and Azure APIs.
+//! OpenAI, Anthropic, Google, Azure, and Ollama APIs.
 
 mod messages;
+mod realtime;
 mod request;
 mod response;
 mod streaming;
+pub mod vision;
 
 pub use messages::*;
+pub use realtime::*;
 pub use request::*;
 pub use response::*;
 pub use streaming::*;
@@ -24,6 +27,7 @@ pub enum Provider {
     Anthropic,
     Google,
     Azure,
+    Ollama,
 }
 
 impl std::fmt::Display for Provider {
@@ -33,6 +37,7 @@ impl std::fmt::Display for Provider {
             Self::Anthropic => write!(f, "anthropic"),
             Self::Google => write!(f, "google"),
             Self::Azure => write!(f, "azure"),
+            Self::Ollama => write!(f, "ollama"),
         }
     }
 }
@@ -46,6 +51,7 @@ impl std::str::FromStr for Provider {
             "anthropic" => Ok(Self::Anthropic),
             "google" => Ok(Self::Google),
             "azure" => Ok(Self::Azure),
+            "ollama" => Ok(Self::Ollama),
             _ => Err(format!("Unknown provider: {}", s)),
         }
     }
@@ -123,7 +129,7 @@ pub struct ModelPricing {
 }
 
 /// Role in a conversation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     System,
@@ -0,0 +1,491 @@
+//! Event types for the OpenAI Realtime API (WebSocket)
+
+use serde::{Deserialize, Serialize};
+
+/// Events sent by the client over the realtime socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RealtimeClientEvent {
+    #[serde(rename = "session.update")]
+    SessionUpdate {
+        #[serde(default)]
+        event_id: Option<String>,
+        session: RealtimeSessionUpdate,
+    },
+    #[serde(rename = "input_audio_buffer.append")]
+    InputAudioBufferAppend {
+        #[serde(default)]
+        event_id: Option<String>,
+        /// Base64-encoded audio bytes
+        audio: String,
+    },
+    #[serde(rename = "input_audio_buffer.commit")]
+    InputAudioBufferCommit {
+        #[serde(default)]
+        event_id: Option<String>,
+    },
+    #[serde(rename = "input_audio_buffer.clear")]
+    InputAudioBufferClear {
+        #[serde(default)]
+        event_id: Option<String>,
+    },
+    #[serde(rename = "conversation.item.create")]
+    ConversationItemCreate {
+        #[serde(default)]
+        event_id: Option<String>,
+        #[serde(default)]
+        previous_item_id: Option<String>,
+        item: RealtimeItem,
+    },
+    #[serde(rename = "response.create")]
+    ResponseCreate {
+        #[serde(default)]
+        event_id: Option<String>,
+        #[serde(default)]
+        response: Option<RealtimeResponseConfig>,
+    },
+    #[serde(rename = "response.cancel")]
+    ResponseCancel {
+        #[serde(default)]
+        event_id: Option<String>,
+    },
+}
+
+/// Events sent by the server over the realtime socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RealtimeServerEvent {
+    #[serde(rename = "error")]
+    Error {
+        error: RealtimeError,
+    },
+    #[serde(rename = "session.created")]
+    SessionCreated {
+        session: RealtimeSession,
+    },
+    #[serde(rename = "session.updated")]
+    SessionUpdated {
+        session: RealtimeSession,
+    },
+    #[serde(rename = "input_audio_buffer.committed")]
+    InputAudioBufferCommitted {
+        previous_item_id: Option<String>,
+        item_id: String,
+    },
+    #[serde(rename = "input_audio_buffer.cleared")]
+    InputAudioBufferCleared,
+    #[serde(rename = "conversation.item.created")]
+    ConversationItemCreated {
+        previous_item_id: Option<String>,
+        item: RealtimeItem,
+    },
+    #[serde(rename = "response.created")]
+    ResponseCreated {
+        response: RealtimeResponse,
+    },
+    #[serde(rename = "response.output_item.added")]
+    ResponseOutputItemAdded {
+        response_id: String,
+        output_index: u32,
+        item: RealtimeItem,
+    },
+    #[serde(rename = "response.content_part.added")]
+    ResponseContentPartAdded {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        part: RealtimeContentPart,
+    },
+    #[serde(rename = "response.text.delta")]
+    ResponseTextDelta {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        delta: String,
+    },
+    #[serde(rename = "response.text.done")]
+    ResponseTextDone {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        text: String,
+    },
+    #[serde(rename = "response.audio.delta")]
+    ResponseAudioDelta {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        /// Base64-encoded audio bytes
+        delta: String,
+    },
+    #[serde(rename = "response.audio.done")]
+    ResponseAudioDone {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+    },
+    #[serde(rename = "response.audio_transcript.delta")]
+    ResponseAudioTranscriptDelta {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        delta: String,
+    },
+    #[serde(rename = "response.audio_transcript.done")]
+    ResponseAudioTranscriptDone {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        transcript: String,
+    },
+    #[serde(rename = "response.content_part.done")]
+    ResponseContentPartDone {
+        response_id: String,
+        item_id: String,
+        output_index: u32,
+        content_index: u32,
+        part: RealtimeContentPart,
+    },
+    #[serde(rename = "response.output_item.done")]
+    ResponseOutputItemDone {
+        response_id: String,
+        output_index: u32,
+        item: RealtimeItem,
+    },
+    #[serde(rename = "response.done")]
+    ResponseDone {
+        response: RealtimeResponse,
+    },
+}
+
+/// A server event together with its unique event ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeServerMessage {
+    pub event_id: String,
+    #[serde(flatten)]
+    pub event: RealtimeServerEvent,
+}
+
+impl RealtimeServerMessage {
+    pub fn new(event: RealtimeServerEvent) -> Self {
+        Self {
+            event_id: realtime_id("event"),
+            event,
+        }
+    }
+}
+
+/// Realtime session configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeSession {
+    pub id: String,
+    pub object: String,
+    pub model: String,
+    pub modalities: Vec<String>,
+    pub instructions: String,
+    pub voice: String,
+    pub input_audio_format: String,
+    pub output_audio_format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_response_output_tokens: Option<u32>,
+}
+
+impl RealtimeSession {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            id: realtime_id("sess"),
+            object: "realtime.session".to_string(),
+            model: model.into(),
+            modalities: vec!["text".to_string(), "audio".to_string()],
+            instructions: String::new(),
+            voice: "alloy".to_string(),
+            input_audio_format: "pcm16".to_string(),
+            output_audio_format: "pcm16".to_string(),
+            temperature: None,
+            max_response_output_tokens: None,
+        }
+    }
+
+    /// Apply a partial `session.update` payload
+    pub fn apply(&mut self, update: RealtimeSessionUpdate) {
+        if let Some(modalities) = update.modalities {
+            self.modalities = modalities;
+        }
+        if let Some(instructions) = update.instructions {
+            self.instructions = instructions;
+        }
+        if let Some(voice) = update.voice {
+            self.voice = voice;
+        }
+        if let Some(format) = update.input_audio_format {
+            self.input_audio_format = format;
+        }
+        if let Some(format) = update.output_audio_format {
+            self.output_audio_format = format;
+        }
+        if update.temperature.is_some() {
+            self.temperature = update.temperature;
+        }
+        if let Some(max_tokens) = update.max_response_output_tokens {
+            self.max_response_output_tokens = max_tokens.limit();
+        }
+    }
+
+    /// Whether responses should include audio output
+    pub fn audio_enabled(&self) -> bool {
+        self.modalities.iter().any(|m| m == "audio")
+    }
+}
+
+/// Partial session configuration sent with `session.update`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RealtimeSessionUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_audio_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_audio_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_response_output_tokens: Option<RealtimeMaxTokens>,
+}
+
+/// Token limit: a number or `"inf"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RealtimeMaxTokens {
+    Limited(u32),
+    Infinite(String),
+}
+
+impl RealtimeMaxTokens {
+    pub fn limit(&self) -> Option<u32> {
+        match self {
+            Self::Limited(n) => Some(*n),
+            Self::Infinite(_) => None,
+        }
+    }
+}
+
+/// Per-response overrides sent with `response.create`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RealtimeResponseConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<RealtimeMaxTokens>,
+}
+
+/// A conversation item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeItem {
+    #[serde(default = "default_item_id")]
+    pub id: String,
+    #[serde(default = "default_item_object")]
+    pub object: String,
+    #[serde(rename = "type", default = "default_item_type")]
+    pub item_type: String,
+    #[serde(default = "default_item_status")]
+    pub status: String,
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub content: Vec<RealtimeContentPart>,
+}
+
+impl RealtimeItem {
+    /// Create a message item with the given role and content
+    pub fn message(role: impl Into<String>, status: impl Into<String>, content: Vec<RealtimeContentPart>) -> Self {
+        Self {
+            id: default_item_id(),
+            object: default_item_object(),
+            item_type: default_item_type(),
+            status: status.into(),
+            role: role.into(),
+            content,
+        }
+    }
+
+    /// Concatenated text and transcripts of all content parts
+    pub fn text(&self) -> String {
+        self.content.iter()
+            .filter_map(|part| match part {
+                RealtimeContentPart::InputText { text } | RealtimeContentPart::Text { text } => Some(text.as_str()),
+                RealtimeContentPart::InputAudio { transcript, .. }
+                | RealtimeContentPart::Audio { transcript, .. } => transcript.as_deref(),
+            })
+            .collect::<Vec<_>>()
+            .join("")
+    }
+}
+
+fn default_item_id() -> String {
+    realtime_id("item")
+}
+
+fn default_item_object() -> String {
+    "realtime.item".to_string()
+}
+
+fn default_item_type() -> String {
+    "message".to_string()
+}
+
+fn default_item_status() -> String {
+    "completed".to_string()
+}
+
+/// Content part within a conversation item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RealtimeContentPart {
+    InputText {
+        text: String,
+    },
+    InputAudio {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        audio: Option<String>,
+        #[serde(default)]
+        transcript: Option<String>,
+    },
+    Text {
+        text: String,
+    },
+    Audio {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        audio: Option<String>,
+        #[serde(default)]
+        transcript: Option<String>,
+    },
+}
+
+/// A model response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeResponse {
+    pub id: String,
+    pub object: String,
+    /// One of `in_progress`, `completed`, `cancelled`, `failed`
+    pub status: String,
+    pub output: Vec<RealtimeItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<RealtimeUsage>,
+}
+
+impl RealtimeResponse {
+    pub fn in_progress() -> Self {
+        Self {
+            id: realtime_id("resp"),
+            object: "realtime.response".to_string(),
+            status: "in_progress".to_string(),
+            output: Vec::new(),
+            usage: None,
+        }
+    }
+}
+
+/// Realtime token usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeUsage {
+    pub total_tokens: u32,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+impl RealtimeUsage {
+    pub fn new(input_tokens: u32, output_tokens: u32) -> Self {
+        Self {
+            total_tokens: input_tokens + output_tokens,
+            input_tokens,
+            output_tokens,
+        }
+    }
+}
+
+/// Error payload of the `error` server event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeError {
+    #[serde(rename = "type")]
+    pub error_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+}
+
+/// Generate a Realtime-style object ID, e.g. `item_3f9a...`
+pub fn realtime_id(prefix: &str) -> String {
+    let uuid = uuid::Uuid::new_v4().to_string().replace('-', "");
+    format!("{}_{}", prefix, &uuid[..20])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_event_parsing() {
+        let event: RealtimeClientEvent = serde_json::from_str(
+            r#"{"type": "conversation.item.create", "item": {"type": "message", "role": "user", "content": [{"type": "input_text", "text": "Hi"}]}}"#,
+        ).unwrap();
+
+        match event {
+            RealtimeClientEvent::ConversationItemCreate { item, .. } => {
+                assert_eq!(item.role, "user");
+                assert_eq!(item.text(), "Hi");
+                assert!(item.id.starts_with("item_"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_server_event_serialization() {
+        let message = RealtimeServerMessage::new(RealtimeServerEvent::ResponseTextDelta {
+            response_id: "resp_1".to_string(),
+            item_id: "item_1".to_string(),
+            output_index: 0,
+            content_index: 0,
+            delta: "Hello".to_string(),
+        });
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["type"], "response.text.delta");
+        assert_eq!(json["delta"], "Hello");
+        assert!(json["event_id"].as_str().unwrap().starts_with("event_"));
+    }
+
+    #[test]
+    fn test_session_update() {
+        let mut session = RealtimeSession::new("gpt-4o-realtime-preview");
+        assert!(session.audio_enabled());
+
+        let update: RealtimeSessionUpdate = serde_json::from_str(
+            r#"{"modalities": ["text"], "instructions": "Be brief", "max_response_output_tokens": "inf"}"#,
+        ).unwrap();
+        session.apply(update);
+
+        assert!(!session.audio_enabled());
+        assert_eq!(session.instructions, "Be brief");
+        assert_eq!(session.max_response_output_tokens, None);
+    }
+}
@@ -1,7 +1,8 @@
 //! Request types for LLM APIs
 
 use serde::{Deserialize, Serialize};
-use super::{Message, ResponseFormat, Tool};
+use super::{Message, Provider, ResponseFormat, Tool};
+use crate::tokenizer::TokenizerKind;
 
 /// Chat completion request (OpenAI-compatible)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,7 +102,12 @@ impl ChatCompletionRequest {
 
     /// Estimate input token count
     pub fn estimate_input_tokens(&self) -> usize {
-        self.messages.iter().map(|m| m.estimate_tokens()).sum()
+        self.estimate_input_tokens_for(Provider::OpenAI, TokenizerKind::default())
+    }
+
+    /// Count input tokens with `tokenizer`, costing images the way `provider` does
+    pub fn estimate_input_tokens_for(&self, provider: Provider, tokenizer: TokenizerKind) -> usize {
+        self.messages.iter().map(|m| m.estimate_tokens_for(provider, tokenizer)).sum()
     }
 
     /// Validate the request
@@ -127,6 +133,22 @@ impl ChatCompletionRequest {
                 return Err("n must be between 1 and 128".to_string());
             }
         }
+        for (name, penalty) in [
+            ("presence_penalty", self.presence_penalty),
+            ("frequency_penalty", self.frequency_penalty),
+        ] {
+            if penalty.is_some_and(|p| !(-2.0..=2.0).contains(&p)) {
+                return Err(format!("{} must be between -2 and 2", name));
+            }
+        }
+        for (token, bias) in self.logit_bias.iter().flatten() {
+            if token.parse::<u32>().is_err() {
+                return Err(format!("logit_bias keys must be token ids, got '{}'", token));
+            }
+            if !(-100.0..=100.0).contains(bias) {
+                return Err("logit_bias values must be between -100 and 100".to_string());
+            }
+        }
         Ok(())
     }
 }
@@ -196,6 +218,147 @@ impl EmbeddingInput {
     }
 }
 
+/// Moderation request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationRequest {
+    pub input: EmbeddingInput,
+    #[serde(default = "default_moderation_model")]
+    pub model: String,
+}
+
+fn default_moderation_model() -> String {
+    "omni-moderation-latest".to_string()
+}
+
+/// Image generation request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationRequest {
+    pub prompt: String,
+    #[serde(default = "default_image_model")]
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// `url` (default) or `b64_json`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+fn default_image_model() -> String {
+    "dall-e-2".to_string()
+}
+
+impl ImageGenerationRequest {
+    /// Number of images to generate
+    pub fn count(&self) -> u32 {
+        self.n.unwrap_or(1)
+    }
+
+    /// Parsed `size` as (width, height)
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        let size = self.size.as_deref().unwrap_or("1024x1024");
+        let (width, height) = size.split_once('x')?;
+        Some((width.parse().ok()?, height.parse().ok()?))
+    }
+
+    /// Validate the request
+    pub fn validate(&self) -> Result<(), String> {
+        if self.prompt.is_empty() {
+            return Err("prompt is required".to_string());
+        }
+        if !(1..=10).contains(&self.count()) {
+            return Err("n must be between 1 and 10".to_string());
+        }
+        match self.dimensions() {
+            Some((w, h)) if (1..=4096).contains(&w) && (1..=4096).contains(&h) => {}
+            _ => return Err("size must be of the form WIDTHxHEIGHT, e.g. 1024x1024".to_string()),
+        }
+        match self.response_format.as_deref() {
+            None | Some("url") | Some("b64_json") => Ok(()),
+            Some(other) => Err(format!("Unsupported response_format: {}", other)),
+        }
+    }
+}
+
+/// Text-to-speech request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    /// `mp3` (default), `wav` or `pcm`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+}
+
+impl SpeechRequest {
+    pub fn format(&self) -> &str {
+        self.response_format.as_deref().unwrap_or("mp3")
+    }
+
+    /// Validate the request
+    pub fn validate(&self) -> Result<(), String> {
+        if self.input.is_empty() {
+            return Err("input is required".to_string());
+        }
+        if self.input.chars().count() > 4096 {
+            return Err("input must be at most 4096 characters".to_string());
+        }
+        if let Some(speed) = self.speed {
+            if !(0.25..=4.0).contains(&speed) {
+                return Err("speed must be between 0.25 and 4.0".to_string());
+            }
+        }
+        match self.format() {
+            "mp3" | "wav" | "pcm" => Ok(()),
+            other => Err(format!("Unsupported response_format: {}", other)),
+        }
+    }
+}
+
+/// Audio transcription request (parsed from multipart form data)
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionRequest {
+    pub model: String,
+    pub file: Vec<u8>,
+    pub filename: Option<String>,
+    pub language: Option<String>,
+    pub prompt: Option<String>,
+    /// `json` (default), `text`, `srt`, `vtt` or `verbose_json`
+    pub response_format: Option<String>,
+    pub temperature: Option<f32>,
+    pub timestamp_granularities: Vec<String>,
+}
+
+impl TranscriptionRequest {
+    pub fn format(&self) -> &str {
+        self.response_format.as_deref().unwrap_or("json")
+    }
+
+    /// Validate the request
+    pub fn validate(&self) -> Result<(), String> {
+        if self.model.is_empty() {
+            return Err("model is required".to_string());
+        }
+        if self.file.is_empty() {
+            return Err("file is required".to_string());
+        }
+        match self.format() {
+            "json" | "text" | "srt" | "vtt" | "verbose_json" => Ok(()),
+            other => Err(format!("Unsupported response_format: {}", other)),
+        }
+    }
+}
+
 /// Anthropic Messages API request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnthropicMessagesRequest {
@@ -288,6 +451,140 @@ pub struct GeminiGenerationConfig {
     pub stop_sequences: Option<Vec<String>>,
 }
 
+/// Ollama chat request (`/api/chat`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaChatRequest {
+    pub model: String,
+    pub messages: Vec<OllamaMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<serde_json::Value>,
+}
+
+impl OllamaChatRequest {
+    /// Ollama streams by default unless `stream: false` is sent
+    pub fn is_streaming(&self) -> bool {
+        self.stream.unwrap_or(true)
+    }
+}
+
+/// Ollama generate request (`/api/generate`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaGenerateRequest {
+    pub model: String,
+    #[serde(default)]
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<serde_json::Value>,
+}
+
+impl OllamaGenerateRequest {
+    /// Ollama streams by default unless `stream: false` is sent
+    pub fn is_streaming(&self) -> bool {
+        self.stream.unwrap_or(true)
+    }
+}
+
+/// Ollama chat message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<OllamaToolCall>>,
+}
+
+impl OllamaMessage {
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: content.into(),
+            images: None,
+            tool_calls: None,
+        }
+    }
+}
+
+/// Ollama tool call; arguments are a JSON object rather than a string
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaToolCall {
+    pub function: OllamaFunctionCall,
+}
+
+/// Ollama function call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaFunctionCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// Ollama model options (subset relevant to simulation)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Maximum tokens to generate (-1 = unlimited)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+}
+
+impl OllamaOptions {
+    /// Get the output token limit, treating negative values as unlimited
+    pub fn max_tokens(&self) -> Option<u32> {
+        self.num_predict.filter(|n| *n >= 0).map(|n| n as u32)
+    }
+}
+
+/// Ollama legacy embeddings request (`/api/embeddings`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaEmbeddingsRequest {
+    pub model: String,
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<serde_json::Value>,
+}
+
+/// Ollama batch embeddings request (`/api/embed`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaEmbedRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<OllamaOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<serde_json::Value>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -304,6 +601,18 @@ mod tests {
             ..ChatCompletionRequest::new("", vec![])
         };
         assert!(invalid.validate().is_err());
+
+        let penalty = ChatCompletionRequest {
+            frequency_penalty: Some(2.5),
+            ..ChatCompletionRequest::new("gpt-4", vec![Message::user("Hi")])
+        };
+        assert!(penalty.validate().unwrap_err().contains("frequency_penalty"));
+
+        let bias = ChatCompletionRequest {
+            logit_bias: Some([("lorem".to_string(), 1.0)].into()),
+            ..ChatCompletionRequest::new("gpt-4", vec![Message::user("Hi")])
+        };
+        assert!(bias.validate().is_err());
     }
 
     #[test]
@@ -314,4 +623,34 @@ mod tests {
         let multiple: StopSequence = serde_json::from_str(r#"["stop", "end"]"#).unwrap();
         assert_eq!(multiple.to_vec(), vec!["stop".to_string(), "end".to_string()]);
     }
+
+    #[test]
+    fn test_ollama_request_defaults() {
+        let request: OllamaChatRequest = serde_json::from_str(
+            r#"{"model": "llama3.2", "messages": [{"role": "user", "content": "Hi"}]}"#,
+        ).unwrap();
+        assert!(request.is_streaming());
+
+        let request: OllamaGenerateRequest = serde_json::from_str(
+            r#"{"model": "llama3.2", "prompt": "Hi", "stream": false, "options": {"num_predict": -1}}"#,
+        ).unwrap();
+        assert!(!request.is_streaming());
+        assert_eq!(request.options.unwrap().max_tokens(), None);
+    }
+
+    #[test]
+    fn test_image_request_validation() {
+        let request: ImageGenerationRequest = serde_json::from_str(
+            r#"{"prompt": "a cat", "size": "512x256"}"#,
+        ).unwrap();
+        assert!(request.validate().is_ok());
+        assert_eq!(request.dimensions(), Some((512, 256)));
+        assert_eq!(request.model, "dall-e-2");
+
+        let invalid = ImageGenerationRequest {
+            size: Some("huge".to_string()),
+            ..request
+        };
+        assert!(invalid.validate().is_err());
+    }
 }
@@ -1,8 +1,12 @@
 //! Response types for LLM APIs
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use super::{FinishReason, Role, Usage};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use super::{FinishReason, OllamaMessage, Role, Usage};
+use crate::error::ProtocolFault;
+use crate::latency::ServerTiming;
 
 /// Chat completion response (OpenAI-compatible)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +20,12 @@ pub struct ChatCompletionResponse {
     pub usage: Option<Usage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_fingerprint: Option<String>,
+    /// Simulated latency of the response, sent as a header rather than in the body
+    #[serde(skip)]
+    pub timing: Option<ServerTiming>,
+    /// Protocol fault the response is broken by when sent
+    #[serde(skip)]
+    pub protocol_fault: Option<ProtocolFault>,
 }
 
 impl ChatCompletionResponse {
@@ -28,6 +38,8 @@ impl ChatCompletionResponse {
             choices,
             usage: Some(usage),
             system_fingerprint: Some(format!("fp_simulator_{}", env!("CARGO_PKG_VERSION").replace('.', ""))),
+            timing: None,
+            protocol_fault: None,
         }
     }
 
@@ -46,6 +58,12 @@ impl ChatCompletionResponse {
         };
         Self::new(id, model, vec![choice], usage)
     }
+
+    /// Set the system fingerprint
+    pub fn with_system_fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.system_fingerprint = Some(fingerprint.into());
+        self
+    }
 }
 
 /// A choice in the chat completion response
@@ -128,6 +146,7 @@ impl EmbeddingsResponse {
                 object: "embedding".to_string(),
                 index: i as u32,
                 embedding,
+                encoding: EmbeddingEncoding::Float,
             })
             .collect();
 
@@ -141,14 +160,93 @@ impl EmbeddingsResponse {
             },
         }
     }
+
+    /// Serialize every vector with the given encoding
+    pub fn with_encoding(mut self, encoding: EmbeddingEncoding) -> Self {
+        for object in &mut self.data {
+            object.encoding = encoding;
+        }
+        self
+    }
 }
 
 /// Individual embedding object
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct EmbeddingObject {
     pub object: String,
     pub index: u32,
+    #[serde(deserialize_with = "deserialize_embedding")]
     pub embedding: Vec<f32>,
+    /// Wire format of `embedding`
+    #[serde(skip)]
+    pub encoding: EmbeddingEncoding,
+}
+
+/// Wire format of an embedding vector
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmbeddingEncoding {
+    /// JSON array of floats
+    #[default]
+    Float,
+    /// Base64 of little-endian f32 bytes
+    Base64,
+}
+
+impl Serialize for EmbeddingObject {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(untagged)]
+        enum Vector<'a> {
+            Float(&'a [f32]),
+            Base64(String),
+        }
+
+        #[derive(Serialize)]
+        struct Wire<'a> {
+            object: &'a str,
+            index: u32,
+            embedding: Vector<'a>,
+        }
+
+        let embedding = match self.encoding {
+            EmbeddingEncoding::Float => Vector::Float(&self.embedding),
+            EmbeddingEncoding::Base64 => {
+                let bytes: Vec<u8> = self.embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+                Vector::Base64(BASE64.encode(bytes))
+            }
+        };
+
+        Wire {
+            object: &self.object,
+            index: self.index,
+            embedding,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Accept either a float array or a base64 string
+fn deserialize_embedding<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Vector {
+        Float(Vec<f32>),
+        Base64(String),
+    }
+
+    match Vector::deserialize(deserializer)? {
+        Vector::Float(values) => Ok(values),
+        Vector::Base64(encoded) => {
+            let bytes = BASE64.decode(encoded).map_err(serde::de::Error::custom)?;
+            if bytes.len() % 4 != 0 {
+                return Err(serde::de::Error::custom("embedding bytes are not a multiple of 4"));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect())
+        }
+    }
 }
 
 /// Embedding usage info
@@ -194,6 +292,134 @@ impl ModelObject {
     }
 }
 
+/// Moderation response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationResult>,
+}
+
+/// Moderation verdict for a single input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    pub categories: BTreeMap<String, bool>,
+    pub category_scores: BTreeMap<String, f64>,
+}
+
+/// Image generation response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationResponse {
+    pub created: i64,
+    pub data: Vec<ImageData>,
+}
+
+impl ImageGenerationResponse {
+    pub fn new(data: Vec<ImageData>) -> Self {
+        Self {
+            created: Utc::now().timestamp(),
+            data,
+        }
+    }
+}
+
+/// A generated image, returned by URL or inline as base64
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b64_json: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revised_prompt: Option<String>,
+}
+
+/// Transcription response (`json` format)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResponse {
+    pub text: String,
+}
+
+/// Transcription response (`verbose_json` format)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerboseTranscriptionResponse {
+    pub task: String,
+    pub language: String,
+    /// Audio duration in seconds
+    pub duration: f64,
+    pub text: String,
+    pub segments: Vec<TranscriptionSegment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<TranscriptionWord>>,
+}
+
+impl VerboseTranscriptionResponse {
+    /// Render segments as SubRip subtitles
+    pub fn to_srt(&self) -> String {
+        self.segments.iter()
+            .map(|s| format!(
+                "{}\n{} --> {}\n{}\n",
+                s.id + 1,
+                subtitle_timestamp(s.start, ','),
+                subtitle_timestamp(s.end, ','),
+                s.text.trim(),
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Render segments as WebVTT subtitles
+    pub fn to_vtt(&self) -> String {
+        let cues = self.segments.iter()
+            .map(|s| format!(
+                "{} --> {}\n{}\n",
+                subtitle_timestamp(s.start, '.'),
+                subtitle_timestamp(s.end, '.'),
+                s.text.trim(),
+            ))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("WEBVTT\n\n{}", cues)
+    }
+}
+
+/// Format seconds as `HH:MM:SS<sep>mmm`
+fn subtitle_timestamp(seconds: f64, separator: char) -> String {
+    let total_ms = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        separator,
+        total_ms % 1000,
+    )
+}
+
+/// A timed segment of a transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    pub id: u32,
+    pub seek: u32,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    pub tokens: Vec<u32>,
+    pub temperature: f32,
+    pub avg_logprob: f64,
+    pub compression_ratio: f64,
+    pub no_speech_prob: f64,
+}
+
+/// A timed word of a transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
 /// Anthropic Messages API response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnthropicMessagesResponse {
@@ -265,7 +491,7 @@ impl GeminiResponse {
             candidates: vec![GeminiCandidate {
                 content: GeminiResponseContent {
                     role: "model".to_string(),
-                    parts: vec![GeminiResponsePart { text: content }],
+                    parts: vec![GeminiResponsePart::text(content)],
                 },
                 finish_reason: Some("STOP".to_string()),
                 safety_ratings: None,
@@ -296,10 +522,30 @@ pub struct GeminiResponseContent {
     pub parts: Vec<GeminiResponsePart>,
 }
 
-/// Gemini response part
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Gemini response part: text or a function call
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GeminiResponsePart {
-    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, alias = "functionCall", skip_serializing_if = "Option::is_none")]
+    pub function_call: Option<GeminiFunctionCall>,
+}
+
+impl GeminiResponsePart {
+    /// Create a text part
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Function call requested by a Gemini model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiFunctionCall {
+    pub name: String,
+    pub args: serde_json::Value,
 }
 
 /// Gemini usage metadata
@@ -310,6 +556,162 @@ pub struct GeminiUsageMetadata {
     pub total_token_count: u32,
 }
 
+/// Ollama chat response, also used for each NDJSON stream line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaChatResponse {
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+    pub message: OllamaMessage,
+    pub done: bool,
+    #[serde(flatten)]
+    pub metrics: OllamaMetrics,
+}
+
+impl OllamaChatResponse {
+    pub fn new(model: String, content: String, done: bool, metrics: OllamaMetrics) -> Self {
+        Self {
+            model,
+            created_at: Utc::now(),
+            message: OllamaMessage::assistant(content),
+            done,
+            metrics,
+        }
+    }
+}
+
+/// Ollama generate response, also used for each NDJSON stream line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaGenerateResponse {
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+    pub response: String,
+    pub done: bool,
+    #[serde(flatten)]
+    pub metrics: OllamaMetrics,
+}
+
+impl OllamaGenerateResponse {
+    pub fn new(model: String, response: String, done: bool, metrics: OllamaMetrics) -> Self {
+        Self {
+            model,
+            created_at: Utc::now(),
+            response,
+            done,
+            metrics,
+        }
+    }
+}
+
+/// Ollama timing and token counters, present only on the final (`done`) message.
+/// All durations are in nanoseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OllamaMetrics {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_eval_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_eval_duration: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eval_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eval_duration: Option<u64>,
+}
+
+impl OllamaMetrics {
+    /// Build final-message metrics from prompt and generation phases
+    pub fn completed(
+        prompt_eval_count: u32,
+        prompt_eval_duration: std::time::Duration,
+        eval_count: u32,
+        eval_duration: std::time::Duration,
+    ) -> Self {
+        Self {
+            done_reason: Some("stop".to_string()),
+            total_duration: Some((prompt_eval_duration + eval_duration).as_nanos() as u64),
+            load_duration: Some(0),
+            prompt_eval_count: Some(prompt_eval_count),
+            prompt_eval_duration: Some(prompt_eval_duration.as_nanos() as u64),
+            eval_count: Some(eval_count),
+            eval_duration: Some(eval_duration.as_nanos() as u64),
+        }
+    }
+}
+
+/// Ollama legacy embeddings response (`/api/embeddings`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaEmbeddingsResponse {
+    pub embedding: Vec<f32>,
+}
+
+/// Ollama batch embeddings response (`/api/embed`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaEmbedResponse {
+    pub model: String,
+    pub embeddings: Vec<Vec<f32>>,
+    pub total_duration: u64,
+    pub load_duration: u64,
+    pub prompt_eval_count: u32,
+}
+
+/// Ollama local models response (`/api/tags`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaTagsResponse {
+    pub models: Vec<OllamaModelTag>,
+}
+
+/// Individual Ollama model entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModelTag {
+    pub name: String,
+    pub model: String,
+    pub modified_at: DateTime<Utc>,
+    pub size: u64,
+    pub digest: String,
+    pub details: OllamaModelDetails,
+}
+
+impl OllamaModelTag {
+    pub fn new(name: impl Into<String>, family: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            model: name.clone(),
+            modified_at: Utc::now(),
+            size: 0,
+            digest: simulated_digest(&name),
+            details: OllamaModelDetails {
+                format: "gguf".to_string(),
+                family: family.into(),
+                parameter_size: String::new(),
+                quantization_level: String::new(),
+            },
+            name,
+        }
+    }
+}
+
+/// Ollama model details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModelDetails {
+    pub format: String,
+    pub family: String,
+    pub parameter_size: String,
+    pub quantization_level: String,
+}
+
+/// Stable 64-hex-digit digest derived from the model name
+fn simulated_digest(name: &str) -> String {
+    use crate::engine::StableHasher;
+
+    (0..4u64)
+        .map(|salt| format!("{:016x}", StableHasher::new().u64(salt).str(name).finish()))
+        .collect()
+}
+
 /// Health check response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthResponse {
@@ -360,6 +762,22 @@ mod tests {
         assert_eq!(response.data[0].embedding.len(), 3);
     }
 
+    #[test]
+    fn test_embeddings_base64_round_trip() {
+        let response = EmbeddingsResponse::new(
+            "text-embedding-3-small".to_string(),
+            vec![vec![0.5, -0.25, 1.0]],
+            3,
+        )
+        .with_encoding(EmbeddingEncoding::Base64);
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["data"][0]["embedding"], "AAAAPwAAgL4AAIA/");
+
+        let parsed: EmbeddingsResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.data[0].embedding, vec![0.5, -0.25, 1.0]);
+    }
+
     #[test]
     fn test_response_serialization() {
         let response = ChatCompletionResponse::simple(
@@ -372,4 +790,55 @@ mod tests {
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("chat.completion"));
     }
+
+    #[test]
+    fn test_subtitle_formats() {
+        let response = VerboseTranscriptionResponse {
+            task: "transcribe".to_string(),
+            language: "english".to_string(),
+            duration: 3.5,
+            text: "Hello there.".to_string(),
+            segments: vec![TranscriptionSegment {
+                id: 0,
+                seek: 0,
+                start: 0.0,
+                end: 3.5,
+                text: " Hello there.".to_string(),
+                tokens: vec![],
+                temperature: 0.0,
+                avg_logprob: -0.2,
+                compression_ratio: 1.0,
+                no_speech_prob: 0.01,
+            }],
+            words: None,
+        };
+
+        assert_eq!(response.to_srt(), "1\n00:00:00,000 --> 00:00:03,500\nHello there.\n");
+        assert!(response.to_vtt().starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:03.500"));
+    }
+
+    #[test]
+    fn test_ollama_metrics_only_on_final_message() {
+        let partial = OllamaGenerateResponse::new(
+            "llama3.2".to_string(),
+            "Hi".to_string(),
+            false,
+            OllamaMetrics::default(),
+        );
+        let json = serde_json::to_value(&partial).unwrap();
+        assert!(json.get("eval_count").is_none());
+
+        let metrics = OllamaMetrics::completed(
+            10,
+            std::time::Duration::from_millis(200),
+            5,
+            std::time::Duration::from_millis(100),
+        );
+        let done = OllamaChatResponse::new("llama3.2".to_string(), String::new(), true, metrics);
+        let json = serde_json::to_value(&done).unwrap();
+        assert_eq!(json["done"], true);
+        assert_eq!(json["eval_count"], 5);
+        assert_eq!(json["prompt_eval_duration"], 200_000_000u64);
+        assert_eq!(json["total_duration"], 300_000_000u64);
+    }
 }
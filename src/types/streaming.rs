@@ -1,7 +1,7 @@
 //! Streaming types for Server-Sent Events
 
 use serde::{Deserialize, Serialize};
-use super::{FinishReason, Role, Usage};
+use super::{FinishReason, ResponseToolCall, Role, Usage};
 
 /// Streaming chat completion chunk (OpenAI-compatible)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +94,27 @@ impl ChunkChoice {
         }
     }
 
+    /// Create a choice carrying a complete tool call
+    pub fn tool_call_delta(call_index: u32, call: &ResponseToolCall, index: u32) -> Self {
+        Self {
+            index,
+            delta: ChunkDelta {
+                tool_calls: Some(vec![ChunkToolCall {
+                    index: call_index,
+                    id: Some(call.id.clone()),
+                    call_type: Some(call.call_type.clone()),
+                    function: Some(ChunkFunctionCall {
+                        name: Some(call.function.name.clone()),
+                        arguments: Some(call.function.arguments.clone()),
+                    }),
+                }]),
+                ..Default::default()
+            },
+            finish_reason: None,
+            logprobs: None,
+        }
+    }
+
     /// Create a choice with finish reason
     pub fn finish(finish_reason: FinishReason, index: u32) -> Self {
         Self {
@@ -0,0 +1,176 @@
+//! Image input decoding and token accounting
+//!
+//! Inline (data URL) images are decoded just far enough to read their
+//! dimensions from the file header; remote URLs are never fetched and are
+//! costed at a default size.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+/// Dimensions assumed for images that cannot be inspected (remote URLs)
+pub const DEFAULT_IMAGE_DIMENSIONS: (u32, u32) = (1024, 1024);
+
+/// Tokens charged per image by Gemini models
+pub const GEMINI_IMAGE_TOKENS: usize = 258;
+
+/// Decode the payload of a `data:` URL.
+///
+/// Returns `None` for any other URL scheme.
+pub fn decode_data_url(url: &str) -> Option<Result<Vec<u8>, String>> {
+    let rest = url.strip_prefix("data:")?;
+    Some(match rest.split_once(',') {
+        Some((meta, data)) if meta.ends_with(";base64") => {
+            BASE64.decode(data.trim()).map_err(|e| format!("invalid base64 image data: {}", e))
+        }
+        _ => Err("image data URLs must be base64 encoded".to_string()),
+    })
+}
+
+/// Read width and height from a PNG, JPEG, GIF or WebP header
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] if bytes.len() >= 24 => Some((
+            u32::from_be_bytes(bytes[16..20].try_into().ok()?),
+            u32::from_be_bytes(bytes[20..24].try_into().ok()?),
+        )),
+        [b'G', b'I', b'F', b'8', ..] if bytes.len() >= 10 => Some((
+            u16::from_le_bytes([bytes[6], bytes[7]]) as u32,
+            u16::from_le_bytes([bytes[8], bytes[9]]) as u32,
+        )),
+        [0xFF, 0xD8, ..] => jpeg_dimensions(bytes),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => webp_dimensions(bytes),
+        _ => None,
+    }
+    .filter(|(w, h)| *w > 0 && *h > 0)
+}
+
+/// Scan JPEG segments for a start-of-frame marker
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    while i + 9 < bytes.len() {
+        if bytes[i] != 0xFF {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        // Fill bytes may precede a marker
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let height = u16::from_be_bytes([bytes[i + 5], bytes[i + 6]]) as u32;
+            let width = u16::from_be_bytes([bytes[i + 7], bytes[i + 8]]) as u32;
+            return Some((width, height));
+        }
+        let length = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        i += 2 + length;
+    }
+    None
+}
+
+/// Read dimensions from the first WebP chunk (lossy, lossless or extended)
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let chunk = bytes.get(12..16)?;
+    let le24 = |at: usize| -> Option<u32> {
+        let b = bytes.get(at..at + 3)?;
+        Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+    };
+
+    match chunk {
+        b"VP8 " => {
+            let b = bytes.get(26..30)?;
+            Some((
+                u16::from_le_bytes([b[0], b[1]]) as u32 & 0x3FFF,
+                u16::from_le_bytes([b[2], b[3]]) as u32 & 0x3FFF,
+            ))
+        }
+        b"VP8L" => {
+            let b = bytes.get(21..25)?;
+            let bits = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+        _ => None,
+    }
+}
+
+/// OpenAI tile-based image cost.
+///
+/// `low` detail is a flat 85 tokens. Otherwise the image is fitted within
+/// 2048x2048, its shortest side scaled down to 768, and each 512px tile
+/// costs 170 tokens on top of the 85 base.
+pub fn openai_image_tokens(width: u32, height: u32, detail: Option<&str>) -> usize {
+    if detail == Some("low") {
+        return 85;
+    }
+
+    let (mut w, mut h) = (width as f64, height as f64);
+    let fit = (2048.0 / w.max(h)).min(1.0);
+    w *= fit;
+    h *= fit;
+    let shorten = (768.0 / w.min(h)).min(1.0);
+    w *= shorten;
+    h *= shorten;
+
+    let tiles = (w / 512.0).ceil() as usize * (h / 512.0).ceil() as usize;
+    170 * tiles + 85
+}
+
+/// Anthropic pixel-based image cost: `width * height / 750`, after scaling
+/// the image down to at most 1568px on the long edge and ~1.15 megapixels.
+pub fn anthropic_image_tokens(width: u32, height: u32) -> usize {
+    let (w, h) = (width as f64, height as f64);
+    let scale = (1568.0 / w.max(h))
+        .min((1_150_000.0 / (w * h)).sqrt())
+        .min(1.0);
+
+    ((w * scale) * (h * scale) / 750.0).ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_tile_costs() {
+        assert_eq!(openai_image_tokens(4096, 4096, Some("low")), 85);
+        // 1024x1024 -> 768x768 -> 4 tiles
+        assert_eq!(openai_image_tokens(1024, 1024, Some("high")), 765);
+        // 2048x4096 -> 1024x2048 -> 768x1536 -> 6 tiles
+        assert_eq!(openai_image_tokens(2048, 4096, None), 1105);
+        assert_eq!(openai_image_tokens(100, 100, None), 255);
+    }
+
+    #[test]
+    fn test_anthropic_pixel_costs() {
+        assert_eq!(anthropic_image_tokens(200, 200), 54);
+        // Large images are capped around 1.15 megapixels
+        assert_eq!(anthropic_image_tokens(4000, 4000), 1534);
+    }
+
+    #[test]
+    fn test_header_dimensions() {
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((640, 480)));
+
+        let gif = [b'G', b'I', b'F', b'8', b'9', b'a', 0x20, 0x00, 0x10, 0x00];
+        assert_eq!(image_dimensions(&gif), Some((32, 16)));
+
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00,
+            0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0x2C, 0x01, 0x90, 0x03,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some((400, 300)));
+
+        assert_eq!(image_dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_decode_data_url() {
+        assert!(decode_data_url("https://example.com/cat.png").is_none());
+        assert_eq!(decode_data_url("data:image/png;base64,AAEC").unwrap().unwrap(), vec![0, 1, 2]);
+        assert!(decode_data_url("data:image/png,raw").unwrap().is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Capacity model integration tests
+
+use std::time::{Duration, Instant};
+
+use super::common::*;
+use llm_simulator::config::{CapacityConfig, LatencyDistribution, LatencyProfile, SimulatorConfig};
+
+/// Every request to `model` takes 300ms; one slot and one queue place
+fn single_slot_config(model: &str) -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    config.latency.profiles.insert("slow-first-token".to_string(), LatencyProfile {
+        ttft: LatencyDistribution::fixed(300.0),
+        itl: LatencyDistribution::fixed(0.0),
+        ..Default::default()
+    });
+
+    let model = config.models.get_mut(model).unwrap();
+    model.latency_profile = Some("slow-first-token".to_string());
+    model.capacity = Some(CapacityConfig {
+        slots: 1,
+        max_queue: 1,
+        ..Default::default()
+    });
+    config
+}
+
+#[tokio::test]
+async fn test_requests_queue_then_overload() {
+    let server = TestServer::spawn_with_config(single_slot_config("gpt-4")).await;
+
+    let start = Instant::now();
+    let send = |delay_ms: u64| {
+        let server = &server;
+        async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            let response = server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await;
+            (response.status().as_u16(), start.elapsed(), response.headers().get("retry-after").cloned())
+        }
+    };
+
+    let (served, queued, rejected) = tokio::join!(send(0), send(50), send(100));
+
+    assert_eq!(served.0, 200);
+    assert_eq!(queued.0, 200);
+    assert_eq!(rejected.0, 429);
+    assert!(rejected.2.is_some());
+
+    // The queued request waited for the first to finish
+    assert!(queued.1 >= Duration::from_millis(600));
+    assert!(served.1 < queued.1);
+
+    let metrics = server.get("/metrics").await.text().await.unwrap();
+    assert!(metrics.contains("llm_simulator_queue_capacity 1"));
+    assert!(metrics.contains("llm_simulator_queue_depth 0"));
+}
+
+#[tokio::test]
+async fn test_anthropic_overload_is_529() {
+    let model = "claude-3-5-sonnet-20241022";
+    let server = TestServer::spawn_with_config(single_slot_config(model)).await;
+
+    let send = |delay_ms: u64| {
+        let server = &server;
+        async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            server.post("/v1/messages", anthropic_request(model, "Hello", 50)).await
+        }
+    };
+
+    let (_, _, rejected) = tokio::join!(send(0), send(50), send(100));
+    assert_eq!(rejected.status().as_u16(), 529);
+
+    let body: serde_json::Value = rejected.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "overloaded_error");
+}
+
+/// Simulated TTFT in milliseconds from a `Server-Timing` header
+fn ttft_ms(response: &reqwest::Response) -> f64 {
+    let header = response.headers().get("server-timing").unwrap().to_str().unwrap();
+    header.split(", ")
+        .find_map(|metric| metric.strip_prefix("ttft;dur="))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_queueing_raises_reported_ttft() {
+    let server = TestServer::spawn_with_config(single_slot_config("gpt-4")).await;
+
+    let send = |delay_ms: u64| {
+        let server = &server;
+        async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await
+        }
+    };
+
+    let (served, queued) = tokio::join!(send(0), send(50));
+    assert_eq!(ttft_ms(&served), 300.0);
+
+    // About 250ms in the queue, then its own 300ms to the first token
+    assert!(ttft_ms(&queued) >= 500.0, "ttft {}", ttft_ms(&queued));
+}
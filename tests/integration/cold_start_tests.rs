@@ -0,0 +1,50 @@
+//! Cold-start integration tests
+
+use std::time::{Duration, Instant};
+
+use super::common::*;
+use llm_simulator::config::{ColdStartConfig, LatencyDistribution, SimulatorConfig};
+use serde_json::Value;
+
+/// gpt-4 answers instantly once warm, and takes two seconds from cold
+fn serverless_config() -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    config.latency.cold_start.insert("gpt-4".to_string(), ColdStartConfig {
+        penalty: LatencyDistribution::fixed(2000.0),
+        ..Default::default()
+    });
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("instant".to_string());
+    }
+    config.security.admin.require_admin_key = false;
+    config
+}
+
+#[tokio::test]
+async fn test_first_request_times_out_then_model_is_warm() {
+    let server = TestServer::spawn_with_config(serverless_config()).await;
+    let send = |timeout: Duration| {
+        server.client.post(server.url("/v1/chat/completions"))
+            .json(&chat_request("gpt-4", "Hello"))
+            .timeout(timeout)
+            .send()
+    };
+
+    let start = Instant::now();
+    let first = send(Duration::from_millis(500)).await;
+    assert!(first.unwrap_err().is_timeout());
+
+    // Retry once the instance has started
+    tokio::time::sleep(Duration::from_millis(2200).saturating_sub(start.elapsed())).await;
+    let start = Instant::now();
+    let retry = send(Duration::from_millis(500)).await.unwrap();
+    assert_eq!(retry.status().as_u16(), 200);
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+    // Models without cold starts are unaffected
+    let other = server.post("/v1/chat/completions", chat_request("gpt-3.5-turbo", "Hello")).await;
+    assert_eq!(other.status().as_u16(), 200);
+
+    let stats: Value = server.get("/admin/stats").await.json().await.unwrap();
+    assert_eq!(stats["cold_starts"], 1);
+}
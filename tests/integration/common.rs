@@ -3,6 +3,7 @@
 //! Provides test server spawning, request builders, and assertions.
 
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use reqwest::Client;
@@ -11,6 +12,7 @@ use tokio::sync::oneshot;
 
 use llm_simulator::{
     config::SimulatorConfig,
+    engine::SimulationEngine,
     security::SecurityState,
     server::{create_router, AppState},
 };
@@ -30,7 +32,13 @@ impl TestServer {
     }
 
     /// Spawn a test server with custom configuration
-    pub async fn spawn_with_config(mut config: SimulatorConfig) -> Self {
+    pub async fn spawn_with_config(config: SimulatorConfig) -> Self {
+        let engine = Arc::new(SimulationEngine::new(config.clone()));
+        Self::spawn_with_engine(config, engine).await
+    }
+
+    /// Spawn a test server around a prepared engine
+    pub async fn spawn_with_engine(mut config: SimulatorConfig, engine: Arc<SimulationEngine>) -> Self {
         // Find an available port
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
@@ -39,7 +47,7 @@ impl TestServer {
         config.server.port = addr.port();
 
         // Create state and router
-        let state = AppState::new(config.clone());
+        let state = AppState::with_engine(config.clone(), engine);
         let security = SecurityState::new(&config.security);
         let app = create_router(state, security);
 
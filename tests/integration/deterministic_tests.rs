@@ -0,0 +1,92 @@
+//! Deterministic ID and timestamp integration tests
+
+use super::common::*;
+use llm_simulator::config::{ClockConfig, DeterministicConfig, SimulatorConfig};
+use serde_json::{json, Value};
+
+fn golden_config() -> SimulatorConfig {
+    let mut config = SimulatorConfig {
+        seed: Some(42),
+        deterministic: DeterministicConfig {
+            ids: true,
+            clock: ClockConfig::Fixed {
+                at: "2024-01-01T00:00:00Z".parse().unwrap(),
+            },
+        },
+        ..Default::default()
+    };
+    skip_latency(&mut config);
+    config
+}
+
+fn skip_latency(config: &mut SimulatorConfig) {
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("instant".to_string());
+    }
+}
+
+/// Replay the same requests against a fresh server, returning raw bodies
+async fn transcript() -> Vec<String> {
+    let server = TestServer::spawn_with_config(golden_config()).await;
+    let mut bodies = Vec::new();
+
+    bodies.push(server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await.text().await.unwrap());
+    bodies.push(
+        server.post("/v1/chat/completions", chat_request_with_options(
+            "gpt-4",
+            vec![json!({"role": "user", "content": "Stream this"})],
+            Some(20),
+            None,
+            true,
+        )).await.text().await.unwrap(),
+    );
+    bodies.push(
+        server.post("/v1/messages", anthropic_request("claude-3-haiku-20240307", "Hi", 50))
+            .await.text().await.unwrap(),
+    );
+    bodies.push(
+        server.post("/api/chat", json!({
+            "model": "llama3.2",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "stream": false
+        })).await.text().await.unwrap(),
+    );
+
+    bodies
+}
+
+#[tokio::test]
+async fn test_transcripts_are_byte_identical() {
+    let first = transcript().await;
+    assert_eq!(first, transcript().await);
+}
+
+#[tokio::test]
+async fn test_ids_and_timestamps_follow_config() {
+    let bodies = transcript().await;
+
+    let chat: Value = serde_json::from_str(&bodies[0]).unwrap();
+    assert!(chat["id"].as_str().unwrap().starts_with("chatcmpl-"));
+    assert_eq!(chat["created"], 1704067200);
+
+    // Every chunk of a stream shares the response's timestamp
+    assert!(bodies[1].matches(r#""created":1704067200"#).count() > 2);
+
+    let message: Value = serde_json::from_str(&bodies[2]).unwrap();
+    assert!(message["id"].as_str().unwrap().starts_with("msg_"));
+
+    let ollama: Value = serde_json::from_str(&bodies[3]).unwrap();
+    assert_eq!(ollama["created_at"], "2024-01-01T00:00:00Z");
+}
+
+#[tokio::test]
+async fn test_ids_are_random_by_default() {
+    let mut config = SimulatorConfig::default();
+    skip_latency(&mut config);
+    let server = TestServer::spawn_with_config(config).await;
+    let id = |body: Value| body["id"].as_str().unwrap().to_string();
+
+    let first = id(server.post("/v1/chat/completions", chat_request("gpt-4", "Hi")).await.json().await.unwrap());
+    let second = id(server.post("/v1/chat/completions", chat_request("gpt-4", "Hi")).await.json().await.unwrap());
+    assert_ne!(first, second);
+}
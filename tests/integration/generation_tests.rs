@@ -0,0 +1,67 @@
+//! Corpus-trained generation integration tests
+
+use super::common::*;
+use std::io::Write;
+use llm_simulator::config::{CorpusConfig, GenerationStrategy, SimulatorConfig};
+use serde_json::Value;
+
+const CORPUS: &str = "\
+# Scaling guide
+
+Kubernetes clusters schedule pods onto nodes. The scheduler weighs resource requests.
+
+- Drain the node before maintenance.
+- Cordon nodes that report disk pressure.
+
+Databases replicate writes to followers. The leader acknowledges each write.
+";
+
+async fn markov_server(corpus: &tempfile::NamedTempFile) -> TestServer {
+    let mut config = SimulatorConfig {
+        corpus: Some(CorpusConfig {
+            files: vec![corpus.path().to_path_buf()],
+            order: 2,
+        }),
+        seed: Some(7),
+        ..Default::default()
+    };
+    config.models.get_mut("gpt-4").unwrap().generation.strategy = GenerationStrategy::Markov;
+    TestServer::spawn_with_config(config).await
+}
+
+fn corpus_file() -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(CORPUS.as_bytes()).unwrap();
+    file
+}
+
+async fn completion(server: &TestServer, prompt: &str) -> String {
+    let body: Value = server.post("/v1/chat/completions", chat_request("gpt-4", prompt))
+        .await
+        .json()
+        .await
+        .unwrap();
+    body["choices"][0]["message"]["content"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_markov_generation_uses_corpus() {
+    let corpus = corpus_file();
+    let server = markov_server(&corpus).await;
+
+    let content = completion(&server, "How do databases replicate data?").await;
+    assert!(content.starts_with("Databases replicate"), "{}", content);
+
+    let vocabulary: Vec<&str> = CORPUS.split_whitespace().collect();
+    assert!(content.split_whitespace().all(|word| vocabulary.contains(&word)), "{}", content);
+}
+
+#[tokio::test]
+async fn test_markov_generation_is_seeded() {
+    let corpus = corpus_file();
+    let server = markov_server(&corpus).await;
+
+    let first = completion(&server, "Tell me about kubernetes").await;
+    let second = completion(&server, "Tell me about kubernetes").await;
+    assert_eq!(first, second);
+}
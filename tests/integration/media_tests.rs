@@ -0,0 +1,202 @@
+//! Image and audio endpoint integration tests
+
+use super::common::*;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use llm_simulator::engine::audio::{encode_wav, synthesize_speech, PCM16_SAMPLE_RATE};
+use serde_json::{json, Value};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const BOUNDARY: &str = "simulator-test-boundary";
+
+/// Build a multipart/form-data body with an audio file and text fields
+fn multipart_body(file: &[u8], fields: &[(&str, &str)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            BOUNDARY, name, value
+        ).as_bytes());
+    }
+    body.extend_from_slice(format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\n",
+        BOUNDARY
+    ).as_bytes());
+    body.extend_from_slice(file);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+    body
+}
+
+async fn transcribe(server: &TestServer, fields: &[(&str, &str)]) -> reqwest::Response {
+    // Three seconds of synthetic audio
+    let wav = encode_wav(
+        &synthesize_speech("one two three four five six seven eight", 1.0)[..PCM16_SAMPLE_RATE as usize * 6],
+        PCM16_SAMPLE_RATE,
+    );
+
+    server.client
+        .post(server.url("/v1/audio/transcriptions"))
+        .header("content-type", format!("multipart/form-data; boundary={}", BOUNDARY))
+        .body(multipart_body(&wav, fields))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_image_generation_b64() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "dall-e-2",
+        "prompt": "a lighthouse at dusk",
+        "n": 2,
+        "size": "256x256",
+        "response_format": "b64_json"
+    });
+
+    let response = server.post("/v1/images/generations", request.clone()).await;
+    assert_status(&response, 200);
+
+    let body: Value = response.json().await.unwrap();
+    assert_json_field(&body, "created");
+    let data = body["data"].as_array().unwrap();
+    assert_eq!(data.len(), 2);
+    assert_ne!(data[0]["b64_json"], data[1]["b64_json"]);
+
+    let png = BASE64.decode(data[0]["b64_json"].as_str().unwrap()).unwrap();
+    assert_eq!(png[..8], PNG_SIGNATURE);
+
+    // Same prompt, same pixels
+    let again: Value = server.post("/v1/images/generations", request).await.json().await.unwrap();
+    assert_eq!(again["data"][0]["b64_json"], data[0]["b64_json"]);
+}
+
+#[tokio::test]
+async fn test_image_generation_url_is_served() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({"model": "dall-e-3", "prompt": "a red fox", "size": "64x32"});
+    let body: Value = server.post("/v1/images/generations", request).await.json().await.unwrap();
+
+    assert_eq!(body["data"][0]["revised_prompt"], "a red fox");
+    let url = body["data"][0]["url"].as_str().unwrap();
+    assert!(url.ends_with("/64x32.png"));
+
+    let image = server.client.get(url).send().await.unwrap();
+    assert_status(&image, 200);
+    assert_eq!(image.headers()["content-type"], "image/png");
+    assert_eq!(image.bytes().await.unwrap()[..8], PNG_SIGNATURE);
+}
+
+#[tokio::test]
+async fn test_image_generation_rejects_bad_size() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({"prompt": "anything", "size": "large"});
+    let response = server.post("/v1/images/generations", request).await;
+    assert_status(&response, 400);
+}
+
+#[tokio::test]
+async fn test_speech_wav_duration_follows_text() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "tts-1",
+        "input": "The quick brown fox jumps",
+        "voice": "alloy",
+        "response_format": "wav"
+    });
+
+    let response = server.post("/v1/audio/speech", request).await;
+    assert_status(&response, 200);
+    assert_eq!(response.headers()["content-type"], "audio/wav");
+
+    let wav = response.bytes().await.unwrap();
+    assert_eq!(&wav[..4], b"RIFF");
+    // Five words at 400ms each, 24kHz PCM16 mono
+    assert_eq!(wav.len(), 44 + 5 * 9_600 * 2);
+}
+
+#[tokio::test]
+async fn test_speech_mp3_default() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({"model": "tts-1", "input": "Hello there", "voice": "nova"});
+    let response = server.post("/v1/audio/speech", request).await;
+    assert_status(&response, 200);
+    assert_eq!(response.headers()["content-type"], "audio/mpeg");
+
+    let mp3 = response.bytes().await.unwrap();
+    assert_eq!(&mp3[..2], &[0xFF, 0xFB]);
+}
+
+#[tokio::test]
+async fn test_speech_rejects_unsupported_format() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({"model": "tts-1", "input": "Hi", "voice": "alloy", "response_format": "opus"});
+    let response = server.post("/v1/audio/speech", request).await;
+    assert_status(&response, 400);
+}
+
+#[tokio::test]
+async fn test_transcription_json() {
+    let server = TestServer::spawn().await;
+
+    let response = transcribe(&server, &[("model", "whisper-1")]).await;
+    assert_status(&response, 200);
+
+    let body: Value = response.json().await.unwrap();
+    assert!(!body["text"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_transcription_verbose_json_segments() {
+    let server = TestServer::spawn().await;
+
+    let response = transcribe(&server, &[
+        ("model", "whisper-1"),
+        ("response_format", "verbose_json"),
+        ("timestamp_granularities[]", "word"),
+    ]).await;
+    assert_status(&response, 200);
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["task"], "transcribe");
+    assert!((body["duration"].as_f64().unwrap() - 3.0).abs() < 0.01);
+
+    let segments = body["segments"].as_array().unwrap();
+    assert!(!segments.is_empty());
+    assert_eq!(segments[0]["start"], 0.0);
+    assert!((segments.last().unwrap()["end"].as_f64().unwrap() - 3.0).abs() < 0.01);
+    assert!(!body["words"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_transcription_srt() {
+    let server = TestServer::spawn().await;
+
+    let response = transcribe(&server, &[("model", "whisper-1"), ("response_format", "srt")]).await;
+    assert_status(&response, 200);
+
+    let body = response.text().await.unwrap();
+    assert!(body.starts_with("1\n00:00:00,000 --> "));
+}
+
+#[tokio::test]
+async fn test_transcription_requires_file() {
+    let server = TestServer::spawn().await;
+
+    let response = server.client
+        .post(server.url("/v1/audio/transcriptions"))
+        .header("content-type", format!("multipart/form-data; boundary={}", BOUNDARY))
+        .body(format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n--{b}--\r\n",
+            b = BOUNDARY
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, 400);
+}
@@ -6,6 +6,22 @@ pub mod common;
 pub mod openai_tests;
 pub mod anthropic_tests;
 pub mod google_tests;
+pub mod ollama_tests;
+pub mod realtime_tests;
+pub mod media_tests;
+pub mod moderation_tests;
+pub mod vision_tests;
+pub mod generation_tests;
+pub mod source_tests;
+pub mod deterministic_tests;
+pub mod session_tests;
+pub mod capacity_tests;
+pub mod cold_start_tests;
+pub mod timeline_tests;
+pub mod throughput_tests;
+pub mod stream_fault_tests;
+pub mod protocol_fault_tests;
 pub mod streaming_tests;
 pub mod security_tests;
 pub mod failure_tests;
+pub mod virtual_time_tests;
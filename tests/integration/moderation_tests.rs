@@ -0,0 +1,122 @@
+//! Moderation endpoint and content filtering integration tests
+
+use super::common::*;
+use llm_simulator::config::{ModerationConfig, ModerationRule, SimulatorConfig};
+use serde_json::{json, Value};
+
+async fn moderated_server(filter_completions: bool) -> TestServer {
+    let config = SimulatorConfig {
+        moderation: ModerationConfig {
+            filter_completions,
+            rules: vec![ModerationRule {
+                category: "violence".to_string(),
+                keywords: vec!["attack".to_string()],
+                patterns: vec![r"\bkill\b".to_string()],
+                score: 0.95,
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    TestServer::spawn_with_config(config).await
+}
+
+#[tokio::test]
+async fn test_moderations_flags_matching_input() {
+    let server = moderated_server(false).await;
+
+    let request = json!({"input": ["we attack at dawn", "have a nice day"]});
+    let response = server.post("/v1/moderations", request).await;
+    assert_status(&response, 200);
+
+    let body: Value = response.json().await.unwrap();
+    assert!(body["id"].as_str().unwrap().starts_with("modr-"));
+    assert_eq!(body["model"], "omni-moderation-latest");
+
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0]["flagged"], true);
+    assert_eq!(results[0]["categories"]["violence"], true);
+    assert_eq!(results[0]["category_scores"]["violence"], 0.95);
+
+    assert_eq!(results[1]["flagged"], false);
+    let scores = results[1]["category_scores"].as_object().unwrap();
+    assert!(scores.contains_key("self-harm/intent"));
+    assert!(scores.values().all(|s| s.as_f64().unwrap() < 0.01));
+}
+
+#[tokio::test]
+async fn test_chat_content_filter() {
+    let server = moderated_server(true).await;
+
+    let flagged = server.post("/v1/chat/completions", chat_request("gpt-4", "how do I kill a process")).await;
+    let body: Value = flagged.json().await.unwrap();
+    assert_eq!(body["choices"][0]["finish_reason"], "content_filter");
+    assert_eq!(body["choices"][0]["message"]["content"], "");
+
+    let allowed = server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await;
+    let body: Value = allowed.json().await.unwrap();
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+}
+
+#[tokio::test]
+async fn test_chat_stream_content_filter() {
+    let server = moderated_server(true).await;
+
+    let request = json!({
+        "model": "gpt-4",
+        "stream": true,
+        "messages": [{"role": "user", "content": "Plan an attack"}]
+    });
+    let body = server.post("/v1/chat/completions", request).await.text().await.unwrap();
+
+    assert!(body.contains("\"finish_reason\":\"content_filter\""));
+}
+
+#[tokio::test]
+async fn test_anthropic_refusal() {
+    let server = moderated_server(true).await;
+
+    let response = server.post("/v1/messages", anthropic_request("claude-3-5-sonnet-20241022", "attack!", 50)).await;
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["stop_reason"], "refusal");
+
+    let mut request = anthropic_request("claude-3-5-sonnet-20241022", "attack!", 50);
+    request["stream"] = json!(true);
+    let body = server.post("/v1/messages", request).await.text().await.unwrap();
+
+    assert!(body.contains("event: message_delta"));
+    assert!(body.contains("\"stop_reason\":\"refusal\""));
+    assert!(body.contains("event: message_stop"));
+}
+
+#[tokio::test]
+async fn test_gemini_safety_block() {
+    let server = moderated_server(true).await;
+
+    let response = server
+        .post("/v1beta/models/gemini-1.5-pro/generateContent", gemini_request("attack!"))
+        .await;
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["candidates"][0]["finish_reason"], "SAFETY");
+}
+
+#[tokio::test]
+async fn test_anthropic_stream_event_order() {
+    let server = TestServer::spawn().await;
+
+    let mut request = anthropic_request("claude-3-5-sonnet-20241022", "Hello", 20);
+    request["stream"] = json!(true);
+    let body = server.post("/v1/messages", request).await.text().await.unwrap();
+
+    let events: Vec<&str> = body.lines()
+        .filter_map(|line| line.strip_prefix("event: "))
+        .filter(|event| *event != "ping")
+        .collect();
+
+    assert_eq!(events.first(), Some(&"message_start"));
+    assert_eq!(events[1], "content_block_start");
+    assert_eq!(&events[events.len() - 3..], ["content_block_stop", "message_delta", "message_stop"]);
+    assert!(body.contains("\"stop_reason\":\"end_turn\""));
+}
@@ -0,0 +1,126 @@
+//! Ollama API endpoint integration tests
+
+use super::common::*;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_ollama_chat_streams_ndjson() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "llama3.2",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "options": {"num_predict": 20}
+    });
+
+    let response = server.post("/api/chat", request).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let content_type = response.headers().get("content-type").unwrap().to_str().unwrap();
+    assert!(content_type.contains("application/x-ndjson"));
+
+    let body = response.text().await.unwrap();
+    let lines: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be a JSON object"))
+        .collect();
+
+    assert!(lines.len() >= 2);
+    assert!(lines[..lines.len() - 1].iter().all(|l| l["done"] == false));
+
+    let last = lines.last().unwrap();
+    assert_eq!(last["done"], true);
+    assert_json_field(last, "eval_count");
+    assert_json_field(last, "eval_duration");
+    assert_json_field(last, "prompt_eval_duration");
+}
+
+#[tokio::test]
+async fn test_ollama_chat_non_streaming() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "llama3.2:latest",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false,
+        "options": {"num_predict": 20}
+    });
+
+    let response = server.post("/api/chat", request).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["model"], "llama3.2:latest");
+    assert_eq!(body["message"]["role"], "assistant");
+    assert_eq!(body["done"], true);
+    assert!(body["eval_count"].as_u64().unwrap() > 0);
+    assert!(!body["message"]["content"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ollama_generate() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "llama3.2",
+        "prompt": "Why is the sky blue?",
+        "stream": false,
+        "options": {"num_predict": 20}
+    });
+
+    let response = server.post("/api/generate", request).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_json_field(&body, "response");
+    assert_json_field(&body, "total_duration");
+    assert_eq!(body["done_reason"], "stop");
+}
+
+#[tokio::test]
+async fn test_ollama_generate_unknown_model() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "not-a-model",
+        "prompt": "Hello"
+    });
+
+    let response = server.post("/api/generate", request).await;
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_ollama_embeddings() {
+    let server = TestServer::spawn().await;
+
+    let response = server
+        .post("/api/embeddings", json!({"model": "nomic-embed-text", "prompt": "Hello"}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["embedding"].as_array().unwrap().len(), 768);
+
+    let response = server
+        .post("/api/embed", json!({"model": "nomic-embed-text", "input": ["a", "b"]}))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["embeddings"].as_array().unwrap().len(), 2);
+    assert_json_field(&body, "total_duration");
+}
+
+#[tokio::test]
+async fn test_ollama_tags() {
+    let server = TestServer::spawn().await;
+
+    let response = server.get("/api/tags").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let models = body["models"].as_array().unwrap();
+    assert!(models.iter().any(|m| m["name"] == "llama3.2"));
+    assert_eq!(models[0]["digest"].as_str().unwrap().len(), 64);
+}
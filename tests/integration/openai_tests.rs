@@ -1,7 +1,10 @@
 //! OpenAI API endpoint integration tests
 
 use super::common::*;
-use serde_json::json;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use llm_simulator::tokenizer::TokenizerKind;
+use llm_simulator::SimulatorConfig;
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_chat_completions_basic() {
@@ -135,6 +138,67 @@ async fn test_embeddings_3_small() {
     assert_eq!(body["model"], "text-embedding-3-small");
 }
 
+#[tokio::test]
+async fn test_embeddings_base64_and_dimensions() {
+    let server = TestServer::spawn().await;
+
+    let response = server
+        .post(
+            "/v1/embeddings",
+            json!({
+                "model": "text-embedding-3-small",
+                "input": "Test embedding",
+                "encoding_format": "base64",
+                "dimensions": 256
+            }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let encoded = body["data"][0]["embedding"].as_str().unwrap();
+    assert_eq!(BASE64.decode(encoded).unwrap().len(), 256 * 4);
+
+    let response = server
+        .post(
+            "/v1/embeddings",
+            json!({"model": "text-embedding-3-small", "input": "Test", "dimensions": 4096}),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_embeddings_similar_texts_are_closer() {
+    let server = TestServer::spawn().await;
+
+    let response = server
+        .post(
+            "/v1/embeddings",
+            json!({
+                "model": "text-embedding-3-small",
+                "input": [
+                    "How do I reset my password?",
+                    "Steps to reset a forgotten password",
+                    "Quarterly revenue grew in Europe"
+                ]
+            }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let vectors: Vec<Vec<f64>> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["embedding"].as_array().unwrap().iter().map(|x| x.as_f64().unwrap()).collect())
+        .collect();
+    let cosine = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+
+    assert!(cosine(&vectors[0], &vectors[1]) > cosine(&vectors[0], &vectors[2]));
+}
+
 #[tokio::test]
 async fn test_list_models() {
     let server = TestServer::spawn().await;
@@ -189,3 +253,118 @@ async fn test_legacy_completions_endpoint() {
 
     assert_eq!(response.status().as_u16(), 200);
 }
+
+#[tokio::test]
+async fn test_usage_counted_with_model_tokenizer() {
+    let server = TestServer::spawn().await;
+    let prompt = "日本語のテキストを数えてください";
+
+    for (model, tokenizer) in [("gpt-4", TokenizerKind::Cl100kBase), ("gpt-4o", TokenizerKind::O200kBase)] {
+        let body: Value = server.post("/v1/chat/completions", chat_request(model, prompt))
+            .await
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(body["usage"]["prompt_tokens"], tokenizer.count(prompt));
+        let content = body["choices"][0]["message"]["content"].as_str().unwrap();
+        assert_eq!(body["usage"]["completion_tokens"], tokenizer.count(content));
+    }
+}
+
+#[tokio::test]
+async fn test_tokenizer_override() {
+    let mut config = SimulatorConfig::default();
+    config.models.get_mut("gpt-4").unwrap().tokenizer = Some(TokenizerKind::Heuristic);
+    let server = TestServer::spawn_with_config(config).await;
+
+    let body: Value = server.post("/v1/chat/completions", chat_request("gpt-4", "hello world"))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    // ~4 characters per token
+    assert_eq!(body["usage"]["prompt_tokens"], 3);
+}
+
+#[tokio::test]
+async fn test_zero_temperature_repeats_output() {
+    let request = chat_request_with_options(
+        "gpt-4",
+        vec![json!({"role": "user", "content": "Summarize the release notes"})],
+        Some(200),
+        Some(0.0),
+        false,
+    );
+
+    // Separate servers share no random state
+    let mut contents = Vec::new();
+    for _ in 0..2 {
+        let server = TestServer::spawn().await;
+        let body: Value = server.post("/v1/chat/completions", request.clone())
+            .await
+            .json()
+            .await
+            .unwrap();
+        contents.push(body["choices"][0]["message"]["content"].clone());
+    }
+
+    assert_eq!(contents[0], contents[1]);
+}
+
+#[tokio::test]
+async fn test_invalid_penalty_rejected() {
+    let server = TestServer::spawn().await;
+    let mut request = chat_request("gpt-4", "Hello");
+    request["presence_penalty"] = json!(3.0);
+
+    let response = server.post("/v1/chat/completions", request).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_request_seed_reproduces_output() {
+    let request = |prompt: &str| {
+        let mut request = chat_request("gpt-4", prompt);
+        request["seed"] = json!(1234);
+        request
+    };
+
+    // Separate servers share no random state
+    let mut contents = Vec::new();
+    for prompt in ["Plan a trip", "Plan a trip", "Write a poem"] {
+        let server = TestServer::spawn().await;
+        let body: Value = server.post("/v1/chat/completions", request(prompt))
+            .await
+            .json()
+            .await
+            .unwrap();
+        contents.push(body["choices"][0]["message"]["content"].clone());
+    }
+
+    assert_eq!(contents[0], contents[1]);
+    assert_ne!(contents[0], contents[2]);
+}
+
+#[tokio::test]
+async fn test_system_fingerprint_tracks_config() {
+    let fingerprint = |server: TestServer| async move {
+        let body: Value = server.post("/v1/chat/completions", chat_request("gpt-4", "Hi"))
+            .await
+            .json()
+            .await
+            .unwrap();
+        body["system_fingerprint"].as_str().unwrap().to_string()
+    };
+
+    let default = fingerprint(TestServer::spawn().await).await;
+    assert!(default.starts_with("fp_"));
+    assert_eq!(default, fingerprint(TestServer::spawn().await).await);
+
+    let config = SimulatorConfig {
+        seed: Some(99),
+        ..Default::default()
+    };
+    assert_ne!(default, fingerprint(TestServer::spawn_with_config(config).await).await);
+}
@@ -0,0 +1,87 @@
+//! Protocol fault integration tests
+
+use super::common::*;
+use llm_simulator::config::{ErrorInjectionRule, SimulatorConfig};
+use llm_simulator::error::InjectedErrorType;
+
+/// Every chat request meets `error_type`
+fn malformed_config(error_type: InjectedErrorType) -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("instant".to_string());
+    }
+    config.chaos.enabled = true;
+    config.chaos.errors.push(ErrorInjectionRule {
+        name: "malformed".to_string(),
+        error_type,
+        probability: 1.0,
+        models: vec![],
+        endpoints: vec!["/chat/completions".to_string()],
+        message: None,
+        status_code: None,
+        delay_ms: None,
+        enabled: true,
+    });
+    config
+}
+
+#[tokio::test]
+async fn test_truncated_json_cuts_the_completion() {
+    let mut config = malformed_config(InjectedErrorType::TruncatedJson);
+    config.chaos.errors[0].endpoints.clear();
+    let server = TestServer::spawn_with_config(config).await;
+
+    let response = server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.text().await.unwrap();
+    assert!(body.starts_with("{\"id\":\"chatcmpl-"), "{body}");
+    assert!(serde_json::from_str::<serde_json::Value>(&body).is_err());
+
+    // Other endpoints have no completion to break
+    let embeddings = server.post("/v1/embeddings", embeddings_request("text-embedding-ada-002", "Hello")).await;
+    assert_eq!(embeddings.status().as_u16(), 200);
+    assert!(embeddings.json::<serde_json::Value>().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_mislabeled_gzip_body_is_plain_completion() {
+    let server = TestServer::spawn_with_config(malformed_config(InjectedErrorType::MislabeledGzip)).await;
+
+    let response = server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await;
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    let body = response.bytes().await.unwrap();
+    let completion: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(completion["object"], "chat.completion");
+}
+
+#[tokio::test]
+async fn test_sse_chunks_lack_data_prefix() {
+    let server = TestServer::spawn_with_config(malformed_config(InjectedErrorType::MissingDataPrefix)).await;
+
+    let mut request = chat_request("gpt-4", "Hello");
+    request["stream"] = true.into();
+    let response = server.post("/v1/chat/completions", request).await;
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let body = response.text().await.unwrap();
+    assert!(body.lines().all(|line| !line.starts_with("data:")));
+
+    let chunks: Vec<serde_json::Value> = body.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    assert!(chunks.len() > 2);
+    assert!(chunks.iter().all(|chunk| chunk["object"] == "chat.completion.chunk"));
+    assert!(body.contains("[DONE]"));
+}
+
+#[tokio::test]
+async fn test_invalid_utf8_inside_streamed_content() {
+    let server = TestServer::spawn_with_config(malformed_config(InjectedErrorType::InvalidUtf8)).await;
+
+    let mut request = anthropic_request("claude-3-5-sonnet-20241022", "Hello", 50);
+    request["stream"] = true.into();
+    let body = server.post("/v1/messages", request).await.bytes().await.unwrap();
+    assert!(std::str::from_utf8(&body).is_err());
+    let text = String::from_utf8_lossy(&body);
+    assert!(text.contains("event: content_block_delta"));
+    assert!(text.contains("\"text\":\"\u{FFFD}\u{FFFD}"));
+}
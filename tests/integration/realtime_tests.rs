@@ -0,0 +1,127 @@
+//! Realtime API (WebSocket) integration tests
+
+use super::common::*;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn connect(server: &TestServer) -> Socket {
+    let url = format!("ws://{}/v1/realtime?model=gpt-4o-realtime-preview", server.addr);
+    let (socket, _) = connect_async(url).await.expect("websocket handshake should succeed");
+    socket
+}
+
+async fn send(socket: &mut Socket, event: Value) {
+    socket.send(Message::Text(event.to_string())).await.unwrap();
+}
+
+async fn next_event(socket: &mut Socket) -> Value {
+    loop {
+        match socket.next().await.expect("socket closed").unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        }
+    }
+}
+
+/// Collect events until (and including) the first event of the given type
+async fn events_until(socket: &mut Socket, event_type: &str) -> Vec<Value> {
+    let mut events = Vec::new();
+    loop {
+        let event = next_event(socket).await;
+        let done = event["type"] == event_type;
+        events.push(event);
+        if done {
+            return events;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_realtime_text_response() {
+    let server = TestServer::spawn().await;
+    let mut socket = connect(&server).await;
+
+    let created = next_event(&mut socket).await;
+    assert_eq!(created["type"], "session.created");
+
+    send(&mut socket, json!({
+        "type": "session.update",
+        "session": {"modalities": ["text"], "max_response_output_tokens": 20}
+    })).await;
+    let updated = next_event(&mut socket).await;
+    assert_eq!(updated["type"], "session.updated");
+    assert_eq!(updated["session"]["modalities"], json!(["text"]));
+
+    send(&mut socket, json!({
+        "type": "conversation.item.create",
+        "item": {"type": "message", "role": "user", "content": [{"type": "input_text", "text": "Hello"}]}
+    })).await;
+    assert_eq!(next_event(&mut socket).await["type"], "conversation.item.created");
+
+    send(&mut socket, json!({"type": "response.create"})).await;
+    let events = events_until(&mut socket, "response.done").await;
+
+    let deltas: String = events.iter()
+        .filter(|e| e["type"] == "response.text.delta")
+        .map(|e| e["delta"].as_str().unwrap().to_string())
+        .collect();
+    assert!(!deltas.is_empty());
+
+    let text_done = events.iter().find(|e| e["type"] == "response.text.done").unwrap();
+    assert_eq!(text_done["text"], deltas);
+
+    let done = events.last().unwrap();
+    assert_eq!(done["response"]["status"], "completed");
+    assert!(done["response"]["usage"]["output_tokens"].as_u64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_realtime_audio_flow() {
+    let server = TestServer::spawn().await;
+    let mut socket = connect(&server).await;
+    next_event(&mut socket).await;
+
+    send(&mut socket, json!({
+        "type": "session.update",
+        "session": {"max_response_output_tokens": 20}
+    })).await;
+    next_event(&mut socket).await;
+
+    // 10ms of PCM16 silence, base64 encoded
+    let chunk = "AAAA".repeat(80);
+    send(&mut socket, json!({"type": "input_audio_buffer.append", "audio": chunk})).await;
+    send(&mut socket, json!({"type": "input_audio_buffer.commit"})).await;
+
+    assert_eq!(next_event(&mut socket).await["type"], "input_audio_buffer.committed");
+    assert_eq!(next_event(&mut socket).await["type"], "conversation.item.created");
+
+    send(&mut socket, json!({"type": "response.create"})).await;
+    let events = events_until(&mut socket, "response.done").await;
+
+    assert!(events.iter().any(|e| e["type"] == "response.audio.delta"));
+    assert!(events.iter().any(|e| e["type"] == "response.audio_transcript.done"));
+}
+
+#[tokio::test]
+async fn test_realtime_empty_commit_is_error() {
+    let server = TestServer::spawn().await;
+    let mut socket = connect(&server).await;
+    next_event(&mut socket).await;
+
+    send(&mut socket, json!({"type": "input_audio_buffer.commit"})).await;
+    let error = next_event(&mut socket).await;
+
+    assert_eq!(error["type"], "error");
+    assert_eq!(error["error"]["code"], "input_audio_buffer_commit_empty");
+}
+
+#[tokio::test]
+async fn test_realtime_unknown_model_rejected() {
+    let server = TestServer::spawn().await;
+
+    let url = format!("ws://{}/v1/realtime?model=not-a-model", server.addr);
+    assert!(connect_async(url).await.is_err());
+}
@@ -0,0 +1,108 @@
+//! Conversation session integration tests
+
+use super::common::*;
+use llm_simulator::config::{LatencyConfig, SessionConfig, SimulatorConfig};
+use serde_json::{json, Value};
+
+fn session_config() -> SimulatorConfig {
+    let mut config = SimulatorConfig {
+        latency: LatencyConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        sessions: SessionConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    config.security.admin.require_admin_key = false;
+    config
+}
+
+/// Send one user turn in `session`, returning the reply text
+async fn say(server: &TestServer, session: &str, content: &str) -> String {
+    let response = server.client
+        .post(server.url("/v1/chat/completions"))
+        .header("x-session-id", session)
+        .json(&chat_request("gpt-4", content))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: Value = response.json().await.unwrap();
+    body["choices"][0]["message"]["content"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_session_recalls_earlier_turns() {
+    let server = TestServer::spawn_with_config(session_config()).await;
+
+    say(&server, "chat-1", "Hi! My name is Ada and I work at Analytical Engines.").await;
+    say(&server, "chat-1", "Tell me about compilers").await;
+
+    let reply = say(&server, "chat-1", "What is my name?").await;
+    assert!(reply.starts_with("Your name is Ada."), "{}", reply);
+    assert!(say(&server, "chat-1", "Where do I work?").await.starts_with("You work at Analytical Engines."));
+
+    // Other sessions know nothing about it
+    assert!(say(&server, "chat-2", "What is my name?").await.starts_with("You haven't told me your name yet."));
+}
+
+#[tokio::test]
+async fn test_session_keyed_by_user_field() {
+    let server = TestServer::spawn_with_config(session_config()).await;
+
+    let turn = |content: &str| {
+        json!({
+            "model": "gpt-4",
+            "messages": [{"role": "user", "content": content}],
+            "user": "user-42"
+        })
+    };
+
+    server.post("/v1/chat/completions", turn("Call me Grace.")).await;
+    let body: Value = server.post("/v1/chat/completions", turn("Who am I?")).await.json().await.unwrap();
+    let content = body["choices"][0]["message"]["content"].as_str().unwrap();
+    assert!(content.starts_with("Your name is Grace."));
+
+    // Earlier turns count as input tokens
+    assert!(body["usage"]["prompt_tokens"].as_u64().unwrap() > 10);
+}
+
+#[tokio::test]
+async fn test_session_admin_inspect_and_delete() {
+    let server = TestServer::spawn_with_config(session_config()).await;
+
+    say(&server, "chat-1", "My favorite color is teal.").await;
+    say(&server, "chat-1", "Hello again").await;
+
+    let sessions: Value = server.get("/admin/sessions").await.json().await.unwrap();
+    assert_eq!(sessions[0]["session_id"], "chat-1");
+    assert_eq!(sessions[0]["message_count"], 4);
+
+    let session: Value = server.get("/admin/sessions/chat-1").await.json().await.unwrap();
+    assert_eq!(session["messages"][0]["content"], "My favorite color is teal.");
+    assert_eq!(session["messages"][1]["role"], "assistant");
+
+    let response = server.client.delete(server.url("/admin/sessions/chat-1")).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 204);
+
+    assert_eq!(server.get("/admin/sessions/chat-1").await.status().as_u16(), 404);
+    let response = server.client.delete(server.url("/admin/sessions/chat-1")).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_sessions_disabled_by_default() {
+    let mut config = session_config();
+    config.sessions = SessionConfig::default();
+    let server = TestServer::spawn_with_config(config).await;
+
+    say(&server, "chat-1", "My name is Ada.").await;
+    assert!(say(&server, "chat-1", "What is my name?").await.starts_with("You haven't told me your name yet."));
+
+    let sessions: Value = server.get("/admin/sessions").await.json().await.unwrap();
+    assert_eq!(sessions, json!([]));
+}
@@ -0,0 +1,202 @@
+//! Custom response source integration tests
+
+use super::common::*;
+use std::sync::Arc;
+use async_trait::async_trait;
+use llm_simulator::config::{ModelConfig, SimulatorConfig};
+use llm_simulator::engine::{ResponseSource, SimulationEngine, SourceResponse};
+use llm_simulator::types::ChatCompletionRequest;
+use llm_simulator::SimulatorResult;
+use serde_json::{json, Value};
+
+/// Tiny rule engine: weather questions call a tool, everything else echoes
+struct WeatherRules;
+
+#[async_trait]
+impl ResponseSource for WeatherRules {
+    async fn respond(&self, request: &ChatCompletionRequest, _: &ModelConfig) -> SimulatorResult<SourceResponse> {
+        let prompt = request.messages.last().map(|m| m.text()).unwrap_or_default();
+        if prompt.contains("weather") {
+            Ok(SourceResponse::text("").with_tool_call("get_weather", r#"{"city":"Paris"}"#))
+        } else {
+            Ok(SourceResponse::text(format!("rules: {}", prompt)))
+        }
+    }
+}
+
+struct Constant(&'static str);
+
+#[async_trait]
+impl ResponseSource for Constant {
+    async fn respond(&self, _: &ChatCompletionRequest, _: &ModelConfig) -> SimulatorResult<SourceResponse> {
+        Ok(SourceResponse::text(self.0))
+    }
+}
+
+async fn server_with_sources() -> TestServer {
+    let config = SimulatorConfig::default();
+    let engine = Arc::new(SimulationEngine::new(config.clone()));
+    engine.set_default_source(Arc::new(Constant("global answer")));
+    engine.register_source("gpt-4", Arc::new(WeatherRules));
+    TestServer::spawn_with_engine(config, engine).await
+}
+
+#[tokio::test]
+async fn test_model_source_answers() {
+    let server = server_with_sources().await;
+
+    let body: Value = server.post("/v1/chat/completions", chat_request("gpt-4", "hi there"))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["choices"][0]["message"]["content"], "rules: hi there");
+    assert_eq!(body["choices"][0]["finish_reason"], "stop");
+    assert_eq!(body["usage"]["completion_tokens"], 4);
+
+    let body: Value = server.post("/v1/chat/completions", chat_request("gpt-4o", "hi"))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["choices"][0]["message"]["content"], "global answer");
+}
+
+#[tokio::test]
+async fn test_source_tool_calls() {
+    let server = server_with_sources().await;
+
+    let body: Value = server.post("/v1/chat/completions", chat_request("gpt-4", "what's the weather?"))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let choice = &body["choices"][0];
+    assert_eq!(choice["finish_reason"], "tool_calls");
+    assert!(choice["message"]["content"].is_null());
+    assert_eq!(choice["message"]["tool_calls"][0]["function"]["name"], "get_weather");
+
+    let mut request = chat_request("gpt-4", "what's the weather?");
+    request["stream"] = json!(true);
+    let body = server.post("/v1/chat/completions", request).await.text().await.unwrap();
+    assert!(body.contains("\"tool_calls\":[{\"index\":0"));
+    assert!(body.contains("\"finish_reason\":\"tool_calls\""));
+}
+
+#[tokio::test]
+async fn test_source_output_truncated_to_max_tokens() {
+    let server = server_with_sources().await;
+
+    let mut request = chat_request("gpt-4", "one two three four five six");
+    request["max_tokens"] = json!(3);
+    let body: Value = server.post("/v1/chat/completions", request).await.json().await.unwrap();
+
+    assert_eq!(body["choices"][0]["message"]["content"], "rules: one");
+    assert_eq!(body["choices"][0]["finish_reason"], "length");
+}
+
+async fn weather_server() -> TestServer {
+    let config = SimulatorConfig::default();
+    let engine = Arc::new(SimulationEngine::new(config.clone()));
+    engine.set_default_source(Arc::new(WeatherRules));
+    TestServer::spawn_with_engine(config, engine).await
+}
+
+/// `data:` payloads of an SSE body
+fn sse_data(body: &str) -> Vec<Value> {
+    body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_anthropic_tool_use_from_source() {
+    let server = weather_server().await;
+
+    let body: Value = server
+        .post("/v1/messages", anthropic_request("claude-3-5-sonnet-20241022", "weather in Paris?", 50))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(body["stop_reason"], "tool_use");
+    assert_eq!(body["content"][0]["type"], "tool_use");
+    assert_eq!(body["content"][0]["input"]["city"], "Paris");
+}
+
+#[tokio::test]
+async fn test_anthropic_stream_tool_use_from_source() {
+    let server = weather_server().await;
+
+    let mut request = anthropic_request("claude-3-5-sonnet-20241022", "weather in Paris?", 50);
+    request["stream"] = json!(true);
+    let body = server.post("/v1/messages", request).await.text().await.unwrap();
+    let events = sse_data(&body);
+
+    let start = events.iter().find(|e| e["type"] == "content_block_start").unwrap();
+    assert_eq!(start["content_block"]["type"], "tool_use");
+    assert_eq!(start["content_block"]["name"], "get_weather");
+    assert!(start["content_block"]["id"].as_str().unwrap().starts_with("toolu_"));
+
+    let delta = events.iter().find(|e| e["type"] == "content_block_delta").unwrap();
+    assert_eq!(delta["index"], start["index"]);
+    assert_eq!(delta["delta"]["type"], "input_json_delta");
+    assert_eq!(delta["delta"]["partial_json"], r#"{"city":"Paris"}"#);
+
+    assert!(events.iter().any(|e| e["type"] == "content_block_stop" && e["index"] == start["index"]));
+    let message_delta = events.iter().find(|e| e["type"] == "message_delta").unwrap();
+    assert_eq!(message_delta["delta"]["stop_reason"], "tool_use");
+}
+
+#[tokio::test]
+async fn test_gemini_function_call_from_source() {
+    let server = weather_server().await;
+
+    let body: Value = server
+        .post("/v1/models/gemini-1.5-pro/generateContent", gemini_request("weather in Paris?"))
+        .await
+        .json()
+        .await
+        .unwrap();
+    let parts = body["candidates"][0]["content"]["parts"].as_array().unwrap();
+    assert_eq!(parts.len(), 1);
+    assert_eq!(parts[0]["function_call"]["name"], "get_weather");
+    assert_eq!(parts[0]["function_call"]["args"]["city"], "Paris");
+
+    let body = server
+        .post("/v1/models/gemini-1.5-pro/streamGenerateContent", gemini_request("weather in Paris?"))
+        .await
+        .text()
+        .await
+        .unwrap();
+    let call = sse_data(&body).into_iter()
+        .flat_map(|chunk| chunk["candidates"][0]["content"]["parts"].as_array().cloned().unwrap_or_default())
+        .find_map(|part| part.get("function_call").cloned())
+        .unwrap();
+    assert_eq!(call["name"], "get_weather");
+    assert_eq!(call["args"]["city"], "Paris");
+}
+
+#[tokio::test]
+async fn test_ollama_tool_calls_from_source() {
+    let server = weather_server().await;
+    let request = |stream: bool| json!({
+        "model": "llama3.2",
+        "messages": [{"role": "user", "content": "weather in Paris?"}],
+        "stream": stream
+    });
+
+    let body: Value = server.post("/api/chat", request(false)).await.json().await.unwrap();
+    let call = &body["message"]["tool_calls"][0]["function"];
+    assert_eq!(call["name"], "get_weather");
+    assert_eq!(call["arguments"]["city"], "Paris");
+
+    let body = server.post("/api/chat", request(true)).await.text().await.unwrap();
+    let lines: Vec<Value> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let line = lines.iter().find(|line| line["message"]["tool_calls"].is_array()).unwrap();
+    assert_eq!(line["done"], false);
+    assert_eq!(line["message"]["tool_calls"][0]["function"]["arguments"]["city"], "Paris");
+    assert_eq!(lines.last().unwrap()["done"], true);
+}
@@ -0,0 +1,92 @@
+//! Mid-stream fault integration tests
+
+use super::common::*;
+use llm_simulator::config::{SimulatorConfig, StreamFaultRule, StreamFaultType};
+use serde_json::Value;
+
+/// Every stream meets `fault_type` after three tokens
+fn faulty_config(fault_type: StreamFaultType) -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("instant".to_string());
+    }
+    config.chaos.enabled = true;
+    config.chaos.stream_faults.push(StreamFaultRule {
+        name: "mid_stream".to_string(),
+        fault_type,
+        probability: 1.0,
+        models: vec![],
+        after_tokens: 3,
+        stall_ms: None,
+        message: Some("Upstream overloaded".to_string()),
+        enabled: true,
+    });
+    config
+}
+
+fn stream_request(model: &str) -> Value {
+    let mut request = chat_request(model, "Tell me a story");
+    request["stream"] = true.into();
+    request
+}
+
+#[tokio::test]
+async fn test_disconnect_aborts_the_body() {
+    // Paced tokens let the headers and first chunks out before the drop
+    let mut config = faulty_config(StreamFaultType::Disconnect);
+    config.models.get_mut("gpt-4").unwrap().latency_profile = None;
+    let server = TestServer::spawn_with_config(config).await;
+
+    let response = server.post("/v1/chat/completions", stream_request("gpt-4")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.is_err());
+}
+
+#[tokio::test]
+async fn test_error_event_ends_anthropic_stream() {
+    let server = TestServer::spawn_with_config(faulty_config(StreamFaultType::ErrorEvent)).await;
+
+    let mut request = anthropic_request("claude-3-5-sonnet-20241022", "Hello", 100);
+    request["stream"] = true.into();
+
+    let body = server.post("/v1/messages", request).await.text().await.unwrap();
+    assert_eq!(body.matches("event: content_block_delta").count(), 3);
+    assert!(body.contains("event: error"));
+    assert!(body.contains("Upstream overloaded"));
+    assert!(!body.contains("message_stop"));
+}
+
+#[tokio::test]
+async fn test_missing_done_keeps_finish_chunk() {
+    let server = TestServer::spawn_with_config(faulty_config(StreamFaultType::MissingDone)).await;
+
+    let body = server.post("/v1/chat/completions", stream_request("gpt-4")).await.text().await.unwrap();
+    assert!(body.contains("\"finish_reason\":\"stop\"") || body.contains("\"finish_reason\":\"length\""));
+    assert!(!body.contains("[DONE]"));
+}
+
+#[tokio::test]
+async fn test_out_of_order_chunk_claims_next_index() {
+    let server = TestServer::spawn_with_config(faulty_config(StreamFaultType::OutOfOrder)).await;
+
+    let body = server.post("/v1/chat/completions", stream_request("gpt-4")).await.text().await.unwrap();
+    let indexes: Vec<u64> = body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter_map(|data| serde_json::from_str::<Value>(data).ok())
+        .map(|chunk| chunk["choices"][0]["index"].as_u64().unwrap())
+        .collect();
+    assert_eq!(indexes[..6], [0, 0, 0, 0, 1, 0]);
+
+    // On Anthropic streams a delta for block 1 arrives while block 0 is open
+    let mut request = anthropic_request("claude-3-5-sonnet-20241022", "Tell me a story", 100);
+    request["stream"] = true.into();
+    let body = server.post("/v1/messages", request).await.text().await.unwrap();
+    let events: Vec<(String, u64)> = body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter_map(|data| serde_json::from_str::<Value>(data).ok())
+        .filter_map(|event| Some((event["type"].as_str()?.to_string(), event["index"].as_u64()?)))
+        .collect();
+    let early = events.iter().position(|event| *event == ("content_block_delta".to_string(), 1)).unwrap();
+    assert!(!events[..early].contains(&("content_block_start".to_string(), 1)));
+    assert!(events[early + 1..].contains(&("content_block_delta".to_string(), 0)));
+}
@@ -1,7 +1,9 @@
 //! Streaming endpoint integration tests
 
 use super::common::*;
-use serde_json::json;
+use llm_simulator::config::{GenerationStrategy, SimulatorConfig};
+use llm_simulator::tokenizer::TokenizerKind;
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_openai_streaming_basic() {
@@ -183,3 +185,60 @@ async fn test_streaming_longer_response() {
     let data_count = body.matches("data:").count();
     assert!(data_count > 1, "Expected multiple stream chunks");
 }
+
+#[tokio::test]
+async fn test_stream_chunks_follow_model_tokenizer() {
+    let server = TestServer::spawn().await;
+
+    let request = chat_request_with_options(
+        "gpt-4o",
+        vec![json!({"role": "user", "content": "Hello"})],
+        Some(50),
+        None,
+        true,
+    );
+    let body = server.post("/v1/chat/completions", request).await.text().await.unwrap();
+
+    let deltas: Vec<String> = body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter(|data| *data != "[DONE]")
+        .filter_map(|data| serde_json::from_str::<Value>(data).ok())
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str().map(String::from))
+        .filter(|content| !content.is_empty())
+        .collect();
+
+    assert!(!deltas.is_empty());
+    assert_eq!(deltas, TokenizerKind::O200kBase.split(&deltas.concat()));
+}
+
+#[tokio::test]
+async fn test_stream_chunk_count_matches_usage_for_multibyte_text() {
+    let mut config = SimulatorConfig::default();
+    let model = config.models.get_mut("gpt-4o").unwrap();
+    model.latency_profile = Some("instant".into());
+    model.generation.strategy = GenerationStrategy::Fixed;
+    model.generation.templates = vec!["日本語のテキストを分割します。絵文字 🎉🚀🧑‍💻 も含みます。".into()];
+    let server = TestServer::spawn_with_config(config).await;
+
+    let request = chat_request_with_options(
+        "gpt-4o",
+        vec![json!({"role": "user", "content": "Hello"})],
+        Some(200),
+        None,
+        true,
+    );
+    let body = server.post("/v1/chat/completions", request).await.text().await.unwrap();
+
+    let chunks: Vec<Value> = body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .filter(|data| *data != "[DONE]")
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect();
+    let content_chunks = chunks.iter()
+        .filter(|chunk| chunk["choices"][0]["delta"]["content"].is_string())
+        .count();
+    let usage = chunks.iter().find_map(|chunk| chunk["usage"].as_object()).unwrap();
+
+    assert!(content_chunks > 0);
+    assert_eq!(content_chunks as u64, usage["completion_tokens"].as_u64().unwrap());
+}
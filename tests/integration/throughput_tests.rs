@@ -0,0 +1,53 @@
+//! Throughput pacing integration tests
+
+use std::time::{Duration, Instant};
+
+use super::common::*;
+use llm_simulator::config::{SimulatorConfig, ThroughputConfig};
+
+/// gpt-4 decodes at 50 tokens per second, shared by all of its streams
+fn shared_capacity_config() -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("instant".to_string());
+    }
+    config.models.get_mut("gpt-4").unwrap().throughput = Some(ThroughputConfig {
+        tokens_per_second: 50.0,
+        global_tokens_per_second: Some(50.0),
+        per_key_tokens_per_second: None,
+    });
+    config
+}
+
+/// Stream a seeded completion, returning its token count and duration
+async fn timed_stream(server: &TestServer) -> (usize, Duration) {
+    let mut request = chat_request("gpt-4", "Tell me a story");
+    request["stream"] = true.into();
+    request["seed"] = 7.into();
+    request["max_tokens"] = 20.into();
+
+    let start = Instant::now();
+    let body = server.post("/v1/chat/completions", request).await.text().await.unwrap();
+    let tokens = body.matches("\"content\":").count();
+    (tokens, start.elapsed())
+}
+
+#[tokio::test]
+async fn test_concurrent_streams_share_throughput() {
+    let server = TestServer::spawn_with_config(shared_capacity_config()).await;
+    // Warm up the connection and generator outside the timings
+    server.post("/v1/chat/completions", chat_request("gpt-3.5-turbo", "Hello")).await;
+
+    // Alone, a stream runs at the nominal rate
+    let (tokens, alone) = timed_stream(&server).await;
+    let nominal = Duration::from_secs_f64(tokens as f64 / 50.0);
+    assert!(tokens > 5);
+    assert!(alone >= nominal.mul_f64(0.8), "{alone:?} for {tokens} tokens");
+    assert!(alone < nominal.mul_f64(1.5), "{alone:?} for {tokens} tokens");
+
+    // Two at once split the ceiling, each taking about twice as long
+    let ((_, first), (_, second)) = tokio::join!(timed_stream(&server), timed_stream(&server));
+    for elapsed in [first, second] {
+        assert!(elapsed >= nominal.mul_f64(1.5), "{elapsed:?} for {tokens} tokens");
+    }
+}
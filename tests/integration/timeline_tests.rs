@@ -0,0 +1,65 @@
+//! Latency timeline integration tests
+
+use super::common::*;
+use llm_simulator::config::{
+    LatencyDistribution, LatencyProfile, LatencyTimeline, SimulatorConfig, TimelinePhase, VirtualTimeConfig,
+};
+
+/// Every model on a steady 100ms TTFT, with a spiky 1s profile to swap in,
+/// reported on virtual time so nothing sleeps
+fn timeline_config(timeline: LatencyTimeline) -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    let fixed = |ttft_ms: f64| LatencyProfile {
+        ttft: LatencyDistribution::fixed(ttft_ms),
+        itl: LatencyDistribution::fixed(0.0),
+        ..Default::default()
+    };
+    config.latency.profiles.insert("steady".to_string(), fixed(100.0));
+    config.latency.profiles.insert("spiky".to_string(), fixed(1000.0));
+    config.latency.virtual_time = VirtualTimeConfig { enabled: true, time_scale: 0.0 };
+    config.latency.timeline = timeline;
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("steady".to_string());
+    }
+    config
+}
+
+/// Simulated TTFT in milliseconds from a `Server-Timing` header
+async fn ttft(server: &TestServer, model: &str) -> f64 {
+    let response = server.post("/v1/chat/completions", chat_request(model, "Hello")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let header = response.headers().get("server-timing").unwrap().to_str().unwrap();
+    header.split(", ")
+        .find_map(|metric| metric.strip_prefix("ttft;dur="))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_phase_from_start_scales_latency() {
+    let timeline = LatencyTimeline {
+        phases: vec![TimelinePhase {
+            name: Some("degraded".to_string()),
+            multiplier: 4.0,
+            ..TimelinePhase::between(0.0, 3600.0)
+        }],
+        cycle_secs: None,
+    };
+    let server = TestServer::spawn_with_config(timeline_config(timeline)).await;
+
+    assert_eq!(ttft(&server, "gpt-4").await, 400.0);
+    assert_eq!(ttft(&server, "gpt-3.5-turbo").await, 400.0);
+}
+
+#[tokio::test]
+async fn test_cron_phase_from_config_swaps_model_profile() {
+    // Every minute matches, so the phase is always in effect
+    let timeline: LatencyTimeline = serde_yaml::from_str(
+        "phases:\n  - name: spikes\n    cron: '* * * * *'\n    duration_secs: 120\n    models: {gpt-4: spiky}\n",
+    ).unwrap();
+    let server = TestServer::spawn_with_config(timeline_config(timeline)).await;
+
+    assert_eq!(ttft(&server, "gpt-4").await, 1000.0);
+    assert_eq!(ttft(&server, "gpt-3.5-turbo").await, 100.0);
+}
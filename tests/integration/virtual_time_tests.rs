@@ -0,0 +1,68 @@
+//! Virtual time integration tests
+
+use std::time::{Duration, Instant};
+
+use super::common::*;
+use llm_simulator::config::{LatencyDistribution, LatencyProfile, SimulatorConfig, VirtualTimeConfig};
+use serde_json::{json, Value};
+
+/// Every model answers after three seconds plus 50ms a token, on virtual time
+fn virtual_config(time_scale: f64) -> SimulatorConfig {
+    let mut config = SimulatorConfig::default();
+    config.latency.profiles.insert("three-seconds".to_string(), LatencyProfile {
+        ttft: LatencyDistribution::fixed(3000.0),
+        itl: LatencyDistribution::fixed(50.0),
+        ..Default::default()
+    });
+    config.latency.virtual_time = VirtualTimeConfig { enabled: true, time_scale };
+    for model in config.models.values_mut() {
+        model.latency_profile = Some("three-seconds".to_string());
+    }
+    config
+}
+
+/// Total simulated milliseconds in a `Server-Timing` header
+fn server_timing_total(response: &reqwest::Response) -> f64 {
+    let header = response.headers().get("server-timing").unwrap().to_str().unwrap();
+    let total = header.split(", ").find_map(|metric| metric.strip_prefix("total;dur=")).unwrap();
+    total.parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_virtual_time_skips_sleeps_but_reports_latency() {
+    let server = TestServer::spawn_with_config(virtual_config(0.0)).await;
+
+    let start = Instant::now();
+    let response = server.post("/v1/chat/completions", chat_request("gpt-4", "Hello")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(server_timing_total(&response) >= 3000.0);
+
+    let request = json!({
+        "model": "llama3.2",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": false
+    });
+    let start = Instant::now();
+    let body: Value = server.post("/api/chat", request).await.json().await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(body["total_duration"].as_u64().unwrap() >= 3_000_000_000);
+}
+
+#[tokio::test]
+async fn test_virtual_time_compresses_streams() {
+    let server = TestServer::spawn_with_config(virtual_config(0.1)).await;
+
+    let start = Instant::now();
+    let messages = vec![json!({"role": "user", "content": "Hello"})];
+    let request = chat_request_with_options("gpt-4", messages, Some(10), None, true);
+    let response = server.post("/v1/chat/completions", request).await;
+    let simulated = server_timing_total(&response);
+    let body = response.text().await.unwrap();
+    let elapsed = start.elapsed();
+
+    assert!(body.contains("[DONE]"));
+    assert!(simulated >= 3000.0);
+    assert!(elapsed >= Duration::from_millis(300));
+    assert!(elapsed < Duration::from_millis(simulated as u64));
+}
@@ -0,0 +1,127 @@
+//! Vision input integration tests
+
+use super::common::*;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use llm_simulator::engine::image::render_placeholder_png;
+use serde_json::{json, Value};
+
+fn png_base64(width: u32, height: u32) -> String {
+    BASE64.encode(render_placeholder_png(1, width, height))
+}
+
+fn vision_request(model: &str, image_url: Value) -> Value {
+    json!({
+        "model": model,
+        "max_tokens": 10,
+        "messages": [{
+            "role": "user",
+            "content": [
+                {"type": "text", "text": "What is in this image?"},
+                {"type": "image_url", "image_url": image_url}
+            ]
+        }]
+    })
+}
+
+#[tokio::test]
+async fn test_image_rejected_for_non_vision_model() {
+    let server = TestServer::spawn().await;
+
+    let request = vision_request("gpt-4", json!({"url": "https://example.com/cat.png"}));
+    let response = server.post("/v1/chat/completions", request).await;
+    assert_status(&response, 400);
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    assert_eq!(body["error"]["param"], "messages.[0].content.[1].type");
+}
+
+#[tokio::test]
+async fn test_image_tokens_in_usage() {
+    let server = TestServer::spawn().await;
+    let url = format!("data:image/png;base64,{}", png_base64(512, 512));
+
+    // "What is in this image?" is 6 o200k tokens; one 512px tile is 255
+    let high = vision_request("gpt-4o", json!({"url": url, "detail": "high"}));
+    let body: Value = server.post("/v1/chat/completions", high).await.json().await.unwrap();
+    assert_eq!(body["usage"]["prompt_tokens"], 261);
+
+    let low = vision_request("gpt-4o", json!({"url": url, "detail": "low"}));
+    let body: Value = server.post("/v1/chat/completions", low).await.json().await.unwrap();
+    assert_eq!(body["usage"]["prompt_tokens"], 91);
+}
+
+#[tokio::test]
+async fn test_invalid_inline_image() {
+    let server = TestServer::spawn().await;
+
+    let request = vision_request("gpt-4o", json!({"url": "data:image/png;base64,bm90IGFuIGltYWdl"}));
+    let response = server.post("/v1/chat/completions", request).await;
+    assert_status(&response, 400);
+}
+
+#[tokio::test]
+async fn test_image_counts_toward_context_length() {
+    let server = TestServer::spawn().await;
+
+    // Remote images are costed at 1024x1024 (765 tokens); 200 exceed 128k
+    let content: Vec<Value> = (0..200)
+        .map(|_| json!({"type": "image_url", "image_url": {"url": "https://example.com/cat.png"}}))
+        .collect();
+    let request = json!({
+        "model": "gpt-4o",
+        "max_tokens": 10,
+        "messages": [{"role": "user", "content": content}]
+    });
+
+    let response = server.post("/v1/chat/completions", request).await;
+    assert_status(&response, 400);
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "context_length_exceeded");
+}
+
+#[tokio::test]
+async fn test_anthropic_image_block() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "model": "claude-3-5-sonnet-20241022",
+        "max_tokens": 10,
+        "messages": [{
+            "role": "user",
+            "content": [
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": png_base64(200, 200)}},
+                {"type": "text", "text": "Describe"}
+            ]
+        }]
+    });
+
+    let body: Value = server.post("/v1/messages", request).await.json().await.unwrap();
+    // 200 * 200 / 750 rounds up to 54, plus 1 text token
+    assert_eq!(body["usage"]["input_tokens"], 55);
+}
+
+#[tokio::test]
+async fn test_gemini_inline_data() {
+    let server = TestServer::spawn().await;
+
+    let request = json!({
+        "contents": [{
+            "role": "user",
+            "parts": [
+                {"text": "Describe"},
+                {"inline_data": {"mime_type": "image/png", "data": png_base64(64, 64)}}
+            ]
+        }],
+        "generation_config": {"max_output_tokens": 10}
+    });
+
+    let body: Value = server
+        .post("/v1beta/models/gemini-1.5-pro/generateContent", request)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["usage_metadata"]["prompt_token_count"], 259);
+}
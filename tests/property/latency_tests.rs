@@ -1,8 +1,11 @@
 //! Property-based tests for latency distribution
 
 use proptest::prelude::*;
-use llm_simulator::config::LatencyDistribution;
-use llm_simulator::latency::DistributionSampler;
+use llm_simulator::config::{
+    interpolate_percentile, percentile_points, ItlCorrelation, LatencyDistribution, LatencyProfile, PrefillConfig,
+};
+use llm_simulator::latency::{itl_sequence, DistributionSampler};
+use rand::{rngs::StdRng, SeedableRng};
 
 proptest! {
     /// Test that normal distribution produces positive values
@@ -154,6 +157,67 @@ proptest! {
             );
         }
     }
+
+    /// Test that prefill time never shrinks as the prompt grows
+    #[test]
+    fn test_prefill_monotonic_in_input(
+        ms_per_1k in 0.0f64..500.0,
+        exponent in 0.5f64..2.0,
+        shorter in 0usize..200_000,
+        extra in 0usize..200_000,
+    ) {
+        let prefill = PrefillConfig { ms_per_1k_tokens: ms_per_1k, exponent };
+        let short = prefill.duration_ms(shorter);
+        let long = prefill.duration_ms(shorter + extra);
+        prop_assert!(short >= 0.0);
+        prop_assert!(long >= short, "prefill {} for longer prompt < {}", long, short);
+    }
+
+    /// Test that an interpolated percentile table is monotonic and passes through its points
+    #[test]
+    fn test_percentile_interpolation_monotonic(
+        p50 in 1.0f64..500.0,
+        spread in proptest::collection::vec(0.0f64..1000.0, 3),
+        q1 in 0.0f64..1.0,
+        q2 in 0.0f64..1.0,
+    ) {
+        let p90 = p50 + spread[0];
+        let p99 = p90 + spread[1];
+        let p999 = p99 + spread[2];
+        let dist = LatencyDistribution::percentiles(&[("p50", p50), ("p90", p90), ("p99", p99), ("p999", p999)]);
+        prop_assert!(dist.validate().is_ok());
+
+        let LatencyDistribution::Empirical { percentiles, .. } = &dist else { unreachable!() };
+        let points = percentile_points(percentiles).unwrap();
+        let (low, high) = if q1 <= q2 { (q1, q2) } else { (q2, q1) };
+        prop_assert!(interpolate_percentile(&points, low) <= interpolate_percentile(&points, high) + 1e-9);
+        prop_assert!((interpolate_percentile(&points, 0.9) - p90).abs() < 1e-6);
+        prop_assert!(interpolate_percentile(&points, 0.0) >= 0.0);
+    }
+
+    /// Test that correlated ITL models never produce negative delays
+    #[test]
+    fn test_correlated_itl_non_negative(
+        mean in 1.0f64..200.0,
+        std_dev in 0.0f64..200.0,
+        phi in -0.99f64..0.99,
+        slow_multiplier in 0.1f64..20.0,
+        seed in any::<u64>(),
+    ) {
+        for itl_correlation in [
+            ItlCorrelation::Ar1 { phi },
+            ItlCorrelation::Regimes { slow_multiplier, enter_slow: 0.1, exit_slow: 0.3 },
+        ] {
+            let profile = LatencyProfile {
+                itl: LatencyDistribution::normal(mean, std_dev),
+                itl_correlation,
+                ..Default::default()
+            };
+            let delays = itl_sequence(&profile, 200, &DistributionSampler::new(), &mut StdRng::seed_from_u64(seed));
+            prop_assert_eq!(delays.len(), 200);
+            prop_assert!(delays.iter().all(|d| *d >= 0.0 && d.is_finite()));
+        }
+    }
 }
 
 #[cfg(test)]